@@ -3,7 +3,7 @@
     windows_subsystem = "windows"
 )]
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate};
 use futures::future::join_all;
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
@@ -11,22 +11,23 @@ use std::env;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, Manager, Runtime, WebviewWindow};
-use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
 use tauri::menu::{Menu, MenuItem, Submenu, CheckMenuItem};
 use std::collections::HashMap;
 use tauri::tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState};
 use winreg::enums::*;
 use winreg::RegKey;
 use zip::ZipArchive;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
 use tokio::process::Command as AsyncCommand;
-use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 
 #[cfg(windows)]
 extern "system" {
@@ -42,12 +43,61 @@ struct TaskInfo {
     cancel_tx: broadcast::Sender<()>,
     pause_flag: Arc<AtomicBool>,
     pid: Arc<Mutex<Option<u32>>>,
+    // 任务结束并完成清理后置为 true（发送端被丢弃同样视为已结束）
+    done_rx: watch::Receiver<bool>,
+    // 版本安装的总进度（0-100），供托盘菜单显示；npm 包安装任务为 None
+    progress: Option<Arc<AtomicU32>>,
 }
 
 struct DownloadState {
     tasks: Mutex<HashMap<String, TaskInfo>>,
 }
 
+// 批量安装（导入时依次安装多个版本），整批的暂停/取消作用于其中的每个任务，
+// 单个版本仍可通过 pause_download 等单独控制
+struct BatchInfo {
+    id: String,
+    versions: Vec<String>, // 带 v 前缀，与 DownloadState.tasks 的键一致
+    paused: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+}
+
+struct BatchState {
+    current: Mutex<Option<BatchInfo>>,
+}
+
+// 关闭确认对话框是否正在显示，防止连续触发的关闭事件弹出多个对话框
+static CLOSE_DIALOG_OPEN: AtomicBool = AtomicBool::new(false);
+
+// nvm 自安装的取消通道，进入写配置/环境变量阶段后置为 None，不再允许取消
+struct NvmInstallState {
+    cancel_tx: Mutex<Option<broadcast::Sender<()>>>,
+}
+
+struct PrefixMigrationState {
+    cancel_flag: Mutex<Arc<AtomicBool>>,
+}
+
+// 启动后的后台预取（版本列表、镜像新鲜度），用户可取消
+struct PrefetchState {
+    cancel_flag: Arc<AtomicBool>,
+}
+
+struct StorageScanState {
+    cancel_flag: Mutex<Arc<AtomicBool>>,
+    // get_total_size 单独使用的取消标志，与存储扫描互不影响
+    total_cancel_flag: Mutex<Arc<AtomicBool>>,
+    // 进行中的 get_total_size，统计完成前为 None；并发调用等待同一个结果
+    total_in_flight: Mutex<Option<watch::Receiver<Option<Result<u64, String>>>>>,
+    size_cache: Mutex<HashMap<String, (u64, u64)>>, // 路径 -> (目录修改时间, 大小)
+}
+
+// 监听 nvm_path 下版本目录的增删（例如在终端里执行 nvm install），
+// 丢弃 watcher 即停止监听，后台线程随通道关闭退出
+struct VersionWatcherState {
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NvmConfig {
     #[serde(rename = "nvmPath")]
@@ -65,6 +115,48 @@ pub struct NvmConfig {
     pub close_action: String, // "ask", "quit", "hide"
     #[serde(rename = "globalPrefix")]
     pub global_prefix: Option<String>, // 共享全局包路径
+    #[serde(rename = "autoSwitchMode", default = "default_auto_switch_mode")]
+    pub auto_switch_mode: String, // "off", "prompt", "auto"
+    #[serde(rename = "keepArchives", default)]
+    pub keep_archives: bool, // 安装后保留下载的 zip，重装时直接使用
+    #[serde(default = "default_locale")]
+    pub locale: String, // 后端消息语言："zh-CN" 或 "en"
+    #[serde(rename = "hiddenMirrors", default)]
+    pub hidden_mirrors: Vec<String>, // 隐藏的内置镜像 ID
+    #[serde(rename = "customMirrorsOnly", default)]
+    pub custom_mirrors_only: bool, // 只使用自定义镜像，隐藏全部内置镜像
+    #[serde(rename = "downloadStallTimeout", default = "default_stall_timeout")]
+    pub download_stall_timeout: u64, // 下载多少秒没有收到数据视为停滞
+    #[serde(rename = "downloadRetries", default = "default_download_retries")]
+    pub download_retries: u32, // 连接中断、超时或 5xx 时的重试次数
+    #[serde(rename = "downloadRetryBackoff", default = "default_retry_backoff")]
+    pub download_retry_backoff: u64, // 首次重试前等待的秒数，之后每次翻倍
+    #[serde(rename = "packageManager", default = "default_package_manager")]
+    pub package_manager: String, // 全局包操作使用的包管理器："npm"、"yarn" 或 "pnpm"
+}
+
+fn default_stall_timeout() -> u64 {
+    30
+}
+
+fn default_download_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff() -> u64 {
+    2
+}
+
+fn default_locale() -> String {
+    "zh-CN".to_string()
+}
+
+fn default_auto_switch_mode() -> String {
+    "off".to_string()
+}
+
+fn default_package_manager() -> String {
+    "npm".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -73,9 +165,12 @@ pub struct NodeVersion {
     pub path: String,
     #[serde(rename = "isActive")]
     pub is_active: bool,
+    #[serde(rename = "isDefault", default)]
+    pub is_default: bool, // nvm-windows 中新开终端使用的版本即符号链接目标，目前与 isActive 一致
     #[serde(rename = "installedDate")]
     pub installed_date: String,
     pub size: u64,
+    pub arch: Option<String>, // "x64" / "x86" / "arm64"
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -98,6 +193,9 @@ pub struct MirrorPreset {
     #[serde(rename = "registryUrl")]
     pub registry_url: String,
     pub description: String,
+    // 安装包地址模板，{version} 为带 v 前缀的版本号，{arch} 为 x64 / x86 / arm64
+    #[serde(rename = "downloadTemplate")]
+    pub download_template: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -106,12 +204,39 @@ pub struct SpeedTestResult {
     pub mirror_id: String,
     pub latency: i64,
     pub success: bool,
+    // 失败原因：超时、连接被拒绝或 HTTP 错误状态
+    pub error: Option<String>,
+    #[serde(rename = "statusCode")]
+    pub status_code: Option<u16>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MirrorFreshness {
+    #[serde(rename = "mirrorId")]
+    pub mirror_id: String,
+    pub newest: Option<String>,
+    // 比官方源落后的发布数量，无法获取时为 None
+    pub behind: Option<u32>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionAvailability {
+    #[serde(rename = "mirrorId")]
+    pub mirror_id: String,
+    pub available: bool,
+    #[serde(rename = "statusCode")]
+    pub status_code: Option<u16>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Package {
     pub name: String,
     pub version: String,
+    pub location: String, // "shared" 或 "version"
+    // 通过 npm link 链接的本地开发包
+    #[serde(rename = "isLinked", default)]
+    pub is_linked: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -137,6 +262,17 @@ pub struct OutdatedPackage {
     pub current: String,
     pub wanted: String,
     pub latest: String,
+    pub manager: String, // "npm" / "yarn" / "pnpm"
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditFinding {
+    pub package: String,
+    pub severity: String,
+    pub title: String,
+    pub url: Option<String>,
+    #[serde(rename = "fixAvailable")]
+    pub fix_available: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -151,6 +287,21 @@ pub struct SearchedPackage {
     pub last_updated: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DownloadPoint {
+    pub day: String,
+    pub downloads: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackageDownloadTrend {
+    pub package: String,
+    pub start: String,
+    pub end: String,
+    pub total: u64,
+    pub downloads: Vec<DownloadPoint>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NvmInstallStatus {
     pub installed: bool,
@@ -187,6 +338,7 @@ fn get_all_mirror_presets() -> Vec<MirrorPreset> {
             npm_url: "https://github.com/npm/cli/archive/".to_string(),
             registry_url: "https://registry.npmjs.org/".to_string(),
             description: "Node.js 官方源，国外服务器，速度较慢".to_string(),
+            download_template: "https://nodejs.org/dist/{version}/node-{version}-win-{arch}.zip".to_string(),
         },
         MirrorPreset {
             id: "taobao".to_string(),
@@ -195,6 +347,7 @@ fn get_all_mirror_presets() -> Vec<MirrorPreset> {
             npm_url: "https://npmmirror.com/mirrors/npm/".to_string(),
             registry_url: "https://registry.npmmirror.com".to_string(),
             description: "淘宝 npmmirror，国内推荐，速度快".to_string(),
+            download_template: "https://npmmirror.com/mirrors/node/{version}/node-{version}-win-{arch}.zip".to_string(),
         },
         MirrorPreset {
             id: "huawei".to_string(),
@@ -203,6 +356,7 @@ fn get_all_mirror_presets() -> Vec<MirrorPreset> {
             npm_url: "https://repo.huaweicloud.com/npm/".to_string(),
             registry_url: "https://repo.huaweicloud.com/repository/npm/".to_string(),
             description: "华为云镜像，国内备选".to_string(),
+            download_template: "https://repo.huaweicloud.com/nodejs/{version}/node-{version}-win-{arch}.zip".to_string(),
         },
         MirrorPreset {
             id: "tsinghua".to_string(),
@@ -211,6 +365,7 @@ fn get_all_mirror_presets() -> Vec<MirrorPreset> {
             npm_url: "https://mirrors.tuna.tsinghua.edu.cn/npm/".to_string(),
             registry_url: "https://mirrors.tuna.tsinghua.edu.cn/npm/".to_string(),
             description: "清华大学开源镜像站".to_string(),
+            download_template: "https://mirrors.tuna.tsinghua.edu.cn/nodejs-release/{version}/node-{version}-win-{arch}.zip".to_string(),
         },
     ]
 }
@@ -239,6 +394,14 @@ fn get_registry_for_npm(npm_mirror: &str) -> Option<String> {
     }
 }
 
+// 单次操作使用的 registry，优先级：显式传入的 registry > 根据配置推断的 registry
+fn effective_registry(registry: Option<&str>, npm_mirror: &str) -> Result<Option<String>, String> {
+    match registry.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(r) => normalize_mirror_url(r).map(Some),
+        None => Ok(get_registry_for_npm(npm_mirror)),
+    }
+}
+
 // --- 进程序列化与控制 ---
 
 #[cfg(windows)]
@@ -300,6 +463,30 @@ fn suspend_process_tree(pid: u32) -> Result<(), String> {
     Ok(())
 }
 
+// 先结束子进程再结束父进程，避免子进程被重新挂到其他父进程下
+#[cfg(windows)]
+fn terminate_process_tree(pid: u32) -> Result<(), String> {
+    use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+    use windows_sys::Win32::Foundation::CloseHandle;
+
+    let pids = {
+        let mut list = get_all_process_children(pid);
+        list.push(pid);
+        list
+    };
+
+    for p in pids {
+        unsafe {
+            let handle = OpenProcess(PROCESS_TERMINATE, 0, p);
+            if handle != 0 {
+                TerminateProcess(handle, 1);
+                CloseHandle(handle);
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(windows)]
 fn resume_process_tree(pid: u32) -> Result<(), String> {
     use windows_sys::Win32::System::Threading::*;
@@ -323,10 +510,498 @@ fn resume_process_tree(pid: u32) -> Result<(), String> {
     Ok(())
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeProcessInfo {
+    pub pid: u32,
+    #[serde(rename = "parentPid")]
+    pub parent_pid: u32,
+    #[serde(rename = "exePath")]
+    pub exe_path: Option<String>,
+    // node.exe 位于 vX.Y.Z 目录时的版本号
+    pub version: Option<String>,
+    #[serde(rename = "workingSet")]
+    pub working_set: Option<u64>,
+    // 内核态 + 用户态 CPU 时间
+    #[serde(rename = "cpuTimeMs")]
+    pub cpu_time_ms: Option<u64>,
+    #[serde(rename = "startTime")]
+    pub start_time: Option<String>,
+    #[serde(rename = "commandLine")]
+    pub command_line: Option<String>,
+}
+
+// 读取其他进程的命令行需要访问其内存，这里借助 CIM 查询；失败（例如被策略禁用）时返回空表
+#[cfg(windows)]
+fn query_node_command_lines() -> HashMap<u32, String> {
+    let script = "Get-CimInstance Win32_Process | Where-Object Name -eq 'node.exe' | ForEach-Object { [string]$_.ProcessId + [char]9 + $_.CommandLine }";
+    let output = match create_silent_command("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .output()
+    {
+        Ok(o) if o.status.success() => o,
+        _ => return HashMap::new(),
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (pid, cmd) = line.split_once('\t')?;
+            let cmd = cmd.trim();
+            Some((pid.trim().parse().ok()?, cmd.to_string())).filter(|_| !cmd.is_empty())
+        })
+        .collect()
+}
+
+// 枚举所有 node.exe 进程及其内存和 CPU 时间。只申请 PROCESS_QUERY_LIMITED_INFORMATION，
+// 普通权限即可读取大部分进程；其他用户或提权进程读取不到的字段为空
+#[cfg(windows)]
+fn list_node_process_stats(include_command_line: bool) -> Vec<NodeProcessInfo> {
+    use windows_sys::Win32::Foundation::{CloseHandle, FILETIME};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::*;
+    use windows_sys::Win32::System::ProcessStatus::{K32GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows_sys::Win32::System::Threading::{
+        GetProcessTimes, OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_VM_READ,
+    };
+
+    let mut found = Vec::new();
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == -1 {
+            return Vec::new();
+        }
+        let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+        if Process32FirstW(snapshot, &mut entry) != 0 {
+            loop {
+                let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+                if String::from_utf16_lossy(&entry.szExeFile[..len]).eq_ignore_ascii_case("node.exe") {
+                    found.push((entry.th32ProcessID, entry.th32ParentProcessID));
+                }
+                if Process32NextW(snapshot, &mut entry) == 0 {
+                    break;
+                }
+            }
+        }
+        CloseHandle(snapshot);
+    }
+
+    let filetime_u64 = |t: &FILETIME| ((t.dwHighDateTime as u64) << 32) | t.dwLowDateTime as u64;
+    let command_lines = if include_command_line { query_node_command_lines() } else { HashMap::new() };
+
+    found
+        .into_iter()
+        .map(|(pid, parent_pid)| {
+            let mut info = NodeProcessInfo {
+                pid,
+                parent_pid,
+                exe_path: None,
+                version: None,
+                working_set: None,
+                cpu_time_ms: None,
+                start_time: None,
+                command_line: command_lines.get(&pid).cloned(),
+            };
+            unsafe {
+                // 部分系统上读取内存信息仍需要 PROCESS_VM_READ，申请失败时退回只读基本信息
+                let mut handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_VM_READ, 0, pid);
+                if handle == 0 {
+                    handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+                }
+                if handle == 0 {
+                    return info;
+                }
+
+                let mut buf = [0u16; 1024];
+                let mut size = buf.len() as u32;
+                if QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, buf.as_mut_ptr(), &mut size) != 0 {
+                    let path = PathBuf::from(String::from_utf16_lossy(&buf[..size as usize]));
+                    info.version = path.parent().and_then(version_from_link_target);
+                    info.exe_path = Some(path.to_string_lossy().to_string());
+                }
+
+                let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+                counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+                if K32GetProcessMemoryInfo(handle, &mut counters, counters.cb) != 0 {
+                    info.working_set = Some(counters.WorkingSetSize as u64);
+                }
+
+                let mut times: [FILETIME; 4] = std::mem::zeroed();
+                let [creation, exit, kernel, user] = &mut times;
+                if GetProcessTimes(handle, creation, exit, kernel, user) != 0 {
+                    // FILETIME 以 100 纳秒为单位，起点为 1601-01-01
+                    info.cpu_time_ms = Some((filetime_u64(kernel) + filetime_u64(user)) / 10_000);
+                    let unix_secs = (filetime_u64(creation) / 10_000_000).checked_sub(11_644_473_600);
+                    info.start_time = unix_secs.map(|secs| {
+                        let dt: DateTime<Local> = (UNIX_EPOCH + std::time::Duration::from_secs(secs)).into();
+                        dt.format("%Y-%m-%d %H:%M:%S").to_string()
+                    });
+                }
+                CloseHandle(handle);
+            }
+            info
+        })
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn list_node_process_stats(_include_command_line: bool) -> Vec<NodeProcessInfo> {
+    Vec::new()
+}
+
+// 按需刷新的 node 进程资源占用；命令行查询较慢，频繁刷新时可传 false 跳过
+#[tauri::command]
+async fn monitor_node_processes(include_command_line: Option<bool>) -> Result<Vec<NodeProcessInfo>, String> {
+    let include_command_line = include_command_line.unwrap_or(true);
+    tauri::async_runtime::spawn_blocking(move || list_node_process_stats(include_command_line))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// --- 多语言 ---
+
+const SUPPORTED_LOCALES: [&str; 2] = ["zh-CN", "en"];
+
+// 当前后端消息语言，启动时从配置加载，set_locale 可在运行时修改
+static CURRENT_LOCALE: Mutex<String> = Mutex::new(String::new());
+
+// (消息码, 中文, English)，消息码同时随事件发送，前端可自行本地化
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("tray.noActiveVersion", "未激活版本", "No active version"),
+    ("tray.switchVersion", "切换版本", "Switch version"),
+    ("tray.previousVersion", "上一个版本", "Previous version"),
+    ("tray.recentVersions", "最近使用", "Recently used"),
+    ("tray.useLatestInstalled", "使用最新已安装版本", "Use latest installed version"),
+    ("tray.show", "显示窗口", "Show window"),
+    ("tray.hide", "隐藏窗口", "Hide window"),
+    ("tray.quit", "退出", "Quit"),
+    ("tray.downloading", "正在下载: {}", "Downloading: {}"),
+    ("tray.downloadingMany", "正在下载 {} 个版本", "Downloading {} versions"),
+    ("dialog.close.title", "退出确认", "Confirm exit"),
+    (
+        "dialog.close.message",
+        "您想将应用最小化到托盘还是直接退出？\n\n[是]：直接退出并记住选择\n[否]：最小化到托盘并记住选择\n[取消]：仅本次最小化到托盘，下次仍然询问",
+        "Minimize to the tray or quit?\n\n[Yes]: quit and remember this choice\n[No]: minimize to the tray and remember this choice\n[Cancel]: minimize to the tray this time only and ask again next time",
+    ),
+    ("install.done", "安装完成", "Installation complete"),
+    ("install.error", "错误: {}", "Error: {}"),
+    ("install.paused", "已暂停", "Paused"),
+    ("install.resumed", "正在安装...", "Installing..."),
+    ("install.cancelled", "已取消", "Cancelled"),
+    ("install.usingCache", "使用本地缓存的安装包", "Using cached archive"),
+    ("install.downloading", "正在下载 Node.js 完整包", "Downloading Node.js package"),
+    ("install.extracting", "正在解压并配置环境...", "Extracting and configuring..."),
+    ("install.verifying", "正在校验安装...", "Verifying installation..."),
+    ("install.package", "正在安装 {}...", "Installing {}..."),
+    ("npm.logTruncated", "... 输出过多，后续内容已省略", "... too much output, the rest has been omitted"),
+    ("prefix.configured", "已配置的共享目录", "Configured shared prefix"),
+    ("prefix.existing", "npm 当前的 prefix", "Existing npm prefix"),
+    ("prefix.default", "默认位置", "Default"),
+    ("prefix.nvmHome", "NVM_HOME 目录内", "Inside NVM_HOME"),
+    ("nvm.fetchingRelease", "正在获取最新版本信息...", "Fetching latest release..."),
+    ("nvm.selectingProxy", "正在重试加速代理 {} ...", "Trying download proxy {} ..."),
+    ("nvm.downloading", "正在下载... {}", "Downloading... {}"),
+    ("nvm.extracting", "正在解压文件...", "Extracting files..."),
+    ("nvm.writingConfig", "正在创建配置文件...", "Creating settings file..."),
+    ("nvm.settingEnv", "正在配置环境变量...", "Configuring environment variables..."),
+    ("nvm.cancelled", "已取消安装", "Installation cancelled"),
+    ("error.unsupportedLocale", "不支持的语言: {}", "Unsupported language: {}"),
+    ("error.invalidTheme", "无效的主题: {}", "Invalid theme: {}"),
+    ("error.nvmHomeMissing", "未找到 NVM_HOME 环境变量", "NVM_HOME environment variable not found"),
+    ("error.settingsMissing", "nvm settings.txt 不存在", "nvm settings.txt does not exist"),
+    ("error.exeDirUnavailable", "无法获取程序所在目录", "Cannot determine the application directory"),
+    ("error.createPortableDir", "创建便携数据目录失败: {}", "Failed to create the portable data directory: {}"),
+    ("error.saveAppState", "保存应用状态失败: {}", "Failed to save application state: {}"),
+    ("error.backupCache", "备份缓存文件失败: {}", "Failed to back up the cache file: {}"),
+    ("error.rebuildCache", "重建缓存文件失败: {}", "Failed to rebuild the cache file: {}"),
+    ("error.userProfileMissing", "无法获取 USERPROFILE 环境变量", "USERPROFILE environment variable not found"),
+    ("error.serialize", "序列化失败: {}", "Serialization failed: {}"),
+    ("error.unsupportedEnvScript", "不支持的脚本格式: {}（可选 cmd、powershell）", "Unsupported script format: {} (expected cmd or powershell)"),
+    ("error.startClip", "无法启动 clip: {}", "Failed to start clip: {}"),
+    ("error.writeClipboard", "写入剪贴板失败: {}", "Failed to write to the clipboard: {}"),
+    ("error.parse", "解析失败: {}", "Failed to parse: {}"),
+    ("error.nvmPathMissing", "NVM 路径不存在: {}", "NVM path does not exist: {}"),
+    ("error.writeConfig", "写入配置失败: {}", "Failed to write settings: {}"),
+    ("error.batchRunning", "已有批量安装正在进行", "A batch install is already running"),
+    ("error.noBatch", "当前没有批量安装", "No batch install is running"),
+    ("error.writeFile", "写入文件失败: {}", "Failed to write file: {}"),
+    ("error.unsupportedScript", "不支持的脚本格式: {}", "Unsupported script format: {}"),
+    ("error.readFile", "读取文件失败: {}", "Failed to read file: {}"),
+    ("error.openFile", "打开文件失败: {}", "Failed to open file: {}"),
+    ("error.windowsOnly", "仅支持 Windows 系统", "Only supported on Windows"),
+    ("error.createNpmrc", "创建 .npmrc 失败: {}", "Failed to create .npmrc: {}"),
+    ("error.unknownConfigFile", "未知的配置文件: {}", "Unknown config file: {}"),
+    ("error.createBackupDir", "创建备份目录失败: {}", "Failed to create the backup directory: {}"),
+    ("error.backupFile", "备份 {} 失败: {}", "Failed to back up {}: {}"),
+    ("error.invalidResetScope", "无效的重置范围: {}", "Invalid reset scope: {}"),
+    ("error.delete", "删除 {} 失败: {}", "Failed to delete {}: {}"),
+    ("error.readNvmrc", "读取 .nvmrc 失败: {}", "Failed to read .nvmrc: {}"),
+    ("error.readNodeVersion", "读取 .node-version 失败: {}", "Failed to read .node-version: {}"),
+    ("error.nvmRootMissing", "NVM 根目录不存在", "NVM root directory does not exist"),
+    ("error.notFound", "找不到 {}", "Cannot find {}"),
+    ("error.runNode", "无法运行 node.exe: {}", "Failed to run node.exe: {}"),
+    ("error.nodeExitCode", "node.exe 退出码 {}: {}", "node.exe exited with code {}: {}"),
+    ("error.versionMismatch", "版本不符: 期望 {}，实际 {}", "Version mismatch: expected {}, got {}"),
+    ("error.readVersionsDir", "读取版本目录失败: {}", "Failed to read the versions directory: {}"),
+    ("error.invalidVersion", "无效的版本号: {}", "Invalid version: {}"),
+    ("error.noActiveVersion", "当前没有正在使用的 Node.js 版本", "No Node.js version is currently in use"),
+    ("error.corepackUnavailable", "Node.js v{} 不包含 corepack（需要 14.19 / 16.9 及以上版本）", "Node.js v{} does not include corepack (requires 14.19 / 16.9 or later)"),
+    ("error.runCorepack", "无法运行 corepack: {}", "Failed to run corepack: {}"),
+    ("error.corepackFailed", "corepack {} 失败: {}", "corepack {} failed: {}"),
+    ("error.pathMissing", "路径不存在", "Path does not exist"),
+    ("error.runNpm", "无法运行 npm 命令: {}", "Failed to run npm: {}"),
+    ("error.noInstalledVersions", "没有已安装的 Node.js 版本", "No Node.js versions are installed"),
+    ("error.versionNotFound", "版本不存在: {}", "Version not found: {}"),
+    ("error.versionNotFoundCheck", "版本不存在: {}，请检查版本号是否正确", "Version not found: {}. Please check the version number"),
+    ("error.archUnavailableOnMirror", "当前镜像没有提供 {} 的 {} 安装包", "The current mirror does not provide the {} package for {}"),
+    ("error.emptyVersionList", "镜像版本列表为空", "The mirror's version list is empty"),
+    ("error.emptyMirror", "镜像地址不能为空", "Mirror URL cannot be empty"),
+    ("error.createJunction", "创建目录联接失败: {}", "Failed to create directory junction: {}"),
+    ("error.dirMissing", "目录不存在: {}", "Directory does not exist: {}"),
+    ("error.installDirNotAbsolute", "安装位置必须是绝对路径: {}", "Install location must be an absolute path: {}"),
+    ("error.installDirInsideNvm", "安装位置不能位于 NVM 目录内", "Install location cannot be inside the NVM directory"),
+    ("error.versionExists", "版本 {} 已存在", "Version {} already exists"),
+    ("error.targetDirNotEmpty", "目标目录不为空: {}", "Target directory is not empty: {}"),
+    ("error.createDir", "创建目录失败: {}", "Failed to create directory: {}"),
+    ("error.junctionMismatch", "目录联接未指向目标目录，目标磁盘可能不支持联接", "The junction does not point to the target directory; the target drive may not support junctions"),
+    ("error.versionDownloading", "版本 {} 正在下载中", "Version {} is already downloading"),
+    ("error.versionInstalled", "版本 {} 已安装", "Version {} is already installed"),
+    ("error.taskNotFound", "未找到任务: {}", "Task not found: {}"),
+    ("error.copyCachedArchive", "复制缓存安装包失败: {}", "Failed to copy the cached archive: {}"),
+    ("error.downloadSizeHttp", "获取下载大小失败: HTTP {}", "Failed to get download size: HTTP {}"),
+    ("error.unsupportedArch", "不支持的架构: {}，可选值为 x64、x86、arm64", "Unsupported architecture: {} (expected x64, x86 or arm64)"),
+    ("error.deleteWhileDownloading", "版本 {} 正在下载中，无法删除", "Version {} is downloading and cannot be deleted"),
+    ("error.cleanupIncomplete", "清理未完成的安装失败 ({}): {}", "Failed to clean up incomplete install ({}): {}"),
+    ("error.readArchive", "读取压缩包失败: {}", "Failed to read archive: {}"),
+    ("error.writePath", "无法写入 {}: {}", "Cannot write {}: {}"),
+    ("error.missingAfterExtract", "解压后缺少 {}", "Missing after extraction: {}"),
+    ("error.downloadRetried", "下载失败: {}，已重试 {} 次", "Download failed: {} (retried {} times)"),
+    ("error.downloadCancelled", "下载已取消", "Download cancelled"),
+    ("error.downloadStalled", "连接停滞: {} 秒内未收到数据，已重试 {} 次", "Connection stalled: no data for {} seconds (retried {} times)"),
+    ("error.downloadHttp", "下载失败: HTTP {}", "Download failed: HTTP {}"),
+    ("error.refuseDelete", "拒绝删除: {}", "Refusing to delete: {}"),
+    ("error.refuseDeleteNonCruft", "拒绝删除非 npm 临时目录: {}", "Refusing to delete a directory that is not an npm temporary directory: {}"),
+    ("error.runNpmHint", "无法运行 npm 命令: {}. 请确保已执行 `nvm use` 并正确安装 Node.js。", "Failed to run npm: {}. Make sure you have run `nvm use` and Node.js is installed correctly."),
+    ("error.npmFailed", "npm 命令执行失败: {}", "npm command failed: {}"),
+    ("error.runCommand", "无法运行 {} 命令: {}", "Failed to run {}: {}"),
+    ("error.commandFailed", "{} 命令执行失败: {}", "{} command failed: {}"),
+    ("error.unknownScope", "未知的范围: {}", "Unknown scope: {}"),
+    ("error.invalidPeriod", "无效的时间范围: {}", "Invalid period: {}"),
+    ("error.invalidDate", "无效的日期: {}", "Invalid date: {}"),
+    ("error.emptyPeriod", "时间范围为空: {}", "Empty period: {}"),
+    ("error.downloadTrendHttp", "获取 {} 下载趋势失败: HTTP {}", "Failed to fetch download trend for {}: HTTP {}"),
+    ("error.queryPackageHttp", "查询 {} 失败: HTTP {}", "Failed to query {}: HTTP {}"),
+    ("error.noLatestTag", "{} 没有 latest 标签", "{} has no latest tag"),
+    ("error.packageVersionMissing", "{} 没有版本 {}", "{} has no version {}"),
+    ("error.distTagMissing", "包 {} 没有 dist-tag \"{}\"，可用的标签: {}", "Package {} has no dist-tag \"{}\". Available tags: {}"),
+    ("error.packageInstalling", "包 {} 正在安装中", "Package {} is already being installed"),
+    ("error.yarnGlobalDir", "无法获取 yarn 全局目录", "Cannot determine the yarn global directory"),
+    ("error.runYarn", "无法运行 yarn 命令: {}", "Failed to run yarn: {}"),
+    ("error.runPnpm", "无法运行 pnpm 命令: {}", "Failed to run pnpm: {}"),
+    ("error.advisories", "查询安全公告失败: {}", "Failed to query security advisories: {}"),
+    ("error.invalidCloseAction", "无效的关闭行为: {}", "Invalid close action: {}"),
+    ("error.mirrorScheme", "镜像地址必须以 http:// 或 https:// 开头: {}", "Mirror URL must start with http:// or https://: {}"),
+    ("error.invalidMirror", "无效的镜像地址: {}", "Invalid mirror URL: {}"),
+    ("error.unsupportedPackageManager", "不支持的包管理器: {}", "Unsupported package manager: {}"),
+    ("error.sizeCancelled", "统计已取消", "Size calculation cancelled"),
+    ("error.storageScanCancelled", "存储扫描已取消", "Storage scan cancelled"),
+    ("error.invalidNvmInstall", "{} 不是有效的 nvm 安装（缺少 nvm.exe 或 settings.txt）", "{} is not a valid nvm installation (missing nvm.exe or settings.txt)"),
+    ("error.settingsNoSymlink", "该安装的 settings.txt 中没有 path（符号链接路径）", "This installation's settings.txt has no path (symlink path)"),
+    ("error.githubRequest", "请求 GitHub API 失败: {}", "GitHub API request failed: {}"),
+    ("error.githubParse", "解析 GitHub 响应失败: {}", "Failed to parse the GitHub response: {}"),
+    ("error.nvmInstalling", "NVM 正在安装中", "NVM is already being installed"),
+    ("error.installCancelled", "安装已取消", "Installation cancelled"),
+    ("error.createSettings", "创建配置文件失败: {}", "Failed to create the settings file: {}"),
+    ("error.fetchRelease", "获取版本失败: {}", "Failed to fetch release: {}"),
+    ("error.nvmAssetMissing", "未找到 nvm-noinstall.zip 下载文件", "nvm-noinstall.zip download not found"),
+    ("error.proxyStatus", "代理 {} 返回错误码: {}", "Proxy {} returned status {}"),
+    ("error.proxyConnect", "代理 {} 连接失败: {}", "Failed to connect to proxy {}: {}"),
+    ("error.proxyDownload", "代理 {} 下载失败: {}", "Download through proxy {} failed: {}"),
+    ("error.proxyInvalidFile", "代理 {} 返回的文件无效: {}", "Proxy {} returned an invalid file: {}"),
+    ("error.allProxiesFailed", "所有加速代理均失效，最后一次错误: {}", "All download proxies failed. Last error: {}"),
+    ("error.openZip", "打开 zip 文件失败: {}", "Failed to open zip file: {}"),
+    ("error.readZip", "读取 zip 文件失败: {}", "Failed to read zip file: {}"),
+    ("error.extract", "解压失败: {}", "Extraction failed: {}"),
+    ("error.createFile", "创建文件失败: {}", "Failed to create file: {}"),
+    ("error.write", "写入失败: {}", "Write failed: {}"),
+    ("error.createTempFile", "创建临时文件失败: {}", "Failed to create temporary file: {}"),
+    ("error.download", "下载错误: {}", "Download error: {}"),
+    ("error.sizeMismatch", "文件大小不符 (期望 {} 字节, 实际 {} 字节)", "File size mismatch (expected {} bytes, got {} bytes)"),
+    ("error.invalidZip", "不是有效的 zip 文件: {}", "Not a valid zip file: {}"),
+    ("error.nvmExeMissing", "压缩包中未找到 nvm.exe", "nvm.exe not found in the archive"),
+    ("error.openRegistry", "打开注册表失败: {}", "Failed to open the registry: {}"),
+    ("error.setNvmHome", "设置 NVM_HOME 失败: {}", "Failed to set NVM_HOME: {}"),
+    ("error.setNvmSymlink", "设置 NVM_SYMLINK 失败: {}", "Failed to set NVM_SYMLINK: {}"),
+    ("error.setPath", "设置 PATH 失败: {}", "Failed to set PATH: {}"),
+    ("error.refuseDeleteRoot", "拒绝删除根目录: {}", "Refusing to delete a root directory: {}"),
+    ("error.read", "读取 {} 失败: {}", "Failed to read {}: {}"),
+    ("error.uninstallConfirm", "卸载 nvm 需要确认", "Uninstalling nvm requires confirmation"),
+    ("error.uninstallBusy", "有正在进行的安装任务，请先取消后再卸载", "Installs are in progress; cancel them before uninstalling"),
+    ("error.nvmHomeUnknown", "无法确定 NVM_HOME", "Cannot determine NVM_HOME"),
+    ("error.backupEnv", "备份环境变量失败: {}", "Failed to back up environment variables: {}"),
+    ("error.fixPrefix", "修复 npm prefix 失败: {}", "Failed to fix npm prefix: {}"),
+    ("error.writeNpmrc", "写入 .npmrc 失败: {}", "Failed to write .npmrc: {}"),
+    ("error.npmCacheDir", "无法获取 npm 缓存目录", "Cannot determine the npm cache directory"),
+    ("error.cacheDirNotAbsolute", "缓存目录必须是绝对路径", "Cache directory must be an absolute path"),
+    ("error.cacheDirNotWritable", "缓存目录不可写: {}", "Cache directory is not writable: {}"),
+    ("error.migrateCache", "迁移 npm 缓存失败: {}", "Failed to migrate the npm cache: {}"),
+    ("error.npmGlobalDir", "无法获取 npm 全局目录", "Cannot determine the npm global directory"),
+    ("error.migrationCancelled", "迁移已取消", "Migration cancelled"),
+    ("error.copyGlobalPackages", "复制全局包失败，已回滚: {}", "Failed to copy global packages, rolled back: {}"),
+    ("error.copyCancelled", "复制已取消", "Copy cancelled"),
+    ("error.invalidSymlink", "符号链接路径无效", "Invalid symlink path"),
+    ("error.createWatcher", "创建目录监听失败: {}", "Failed to create directory watcher: {}"),
+    ("error.watchDir", "监听目录失败: {}", "Failed to watch directory: {}"),
+    ("error.invalidAutoSwitchMode", "无效的自动切换模式: {}", "Invalid auto-switch mode: {}"),
+    ("error.copyVerify", "复制校验失败 (文件 {}/{}, 大小 {}/{})，已回滚", "Copy verification failed (files {}/{}, size {}/{}), rolled back"),
+    ("error.requestTimeout", "连接超时", "Connection timed out"),
+    ("error.connectionRefused", "连接被拒绝", "Connection refused"),
+    ("error.connect", "无法建立连接: {}", "Cannot establish a connection: {}"),
+    ("error.request", "请求失败: {}", "Request failed: {}"),
+    ("error.httpStatus", "HTTP 错误: {}", "HTTP error: {}"),
+    ("error.batchCancelled", "批量安装已取消", "Batch install cancelled"),
+    ("error.movePrefixEntry", "迁移 {} 失败，已回滚: {}", "Failed to move {}, rolled back: {}"),
+    ("import.nvmPathEmpty", "NVM 路径为空", "NVM path is empty"),
+    ("import.symlinkEmpty", "符号链接路径为空", "Symlink path is empty"),
+    ("import.symlinkInsideNvm", "符号链接路径不能位于 NVM 目录内: {}", "Symlink path cannot be inside the NVM directory: {}"),
+    ("import.symlinkIsDir", "符号链接路径已是普通目录，切换版本时 nvm 会失败: {}", "Symlink path is a regular directory; nvm will fail to switch versions: {}"),
+    ("import.prefixInsideSymlink", "全局包路径不能位于符号链接目录内，切换版本后会丢失: {}", "Global package path cannot be inside the symlink directory; it would be lost when switching versions: {}"),
+    ("import.prefixMissing", "全局包路径不存在，将在首次安装全局包时创建: {}", "Global package path does not exist; it will be created on the first global install: {}"),
+    ("import.nodeMirror", "Node 镜像: {}", "Node mirror: {}"),
+    ("import.npmMirror", "npm 镜像: {}", "npm mirror: {}"),
+    ("import.unsupportedArch", "不支持的架构: {}", "Unsupported architecture: {}"),
+    ("import.unknownCloseAction", "未知的关闭行为 {}，将使用默认值", "Unknown close action {}; the default will be used"),
+    ("import.unsupportedLocale", "不支持的语言 {}，将使用默认值", "Unsupported language {}; the default will be used"),
+    ("import.unrecognizedVersion", "无法识别的版本号，安装时将跳过: {}", "Unrecognized version, it will be skipped during install: {}"),
+    ("health.envNotLoaded", "已写入注册表但当前进程未生效，请重启应用: {}", "Set in the registry but not loaded by this process; restart the app: {}"),
+    ("health.envNotPersisted", "仅存在于当前进程，未写入注册表: {}", "Only set in this process, not written to the registry: {}"),
+    ("health.envMissing", "未设置 {} 环境变量", "{} environment variable is not set"),
+    ("health.symlinkBroken", "符号链接指向的版本不存在或不完整: {}", "The symlink points to a missing or incomplete version: {}"),
+    ("health.noActiveVersion", "当前没有激活的 Node.js 版本", "No Node.js version is active"),
+    ("health.npmUnavailable", "无法运行 npm", "Cannot run npm"),
+    ("health.npmPrefixMismatch", "npm prefix 为 {}，预期为 {}", "npm prefix is {}, expected {}"),
+    ("health.registryUnreachable", "无法连接 {}: {}", "Cannot connect to {}: {}"),
+    ("health.pathOk", "PATH 配置正常", "PATH is configured correctly"),
+    ("health.permissionsOk", "所有位置均可写入", "All locations are writable"),
+    ("health.diskSpaceLow", "剩余空间仅 {} MB", "Only {} MB free"),
+    ("health.diskSpace", "剩余空间 {} MB", "{} MB free"),
+    ("health.noIncompleteInstalls", "没有未完成的安装", "No incomplete installs"),
+    ("health.cacheCorrupt", "cache.json 已损坏，缓存已失效", "cache.json is corrupt; the cache is not being used"),
+    ("health.cacheInvalidEntries", "cache.json 中有无法解析的条目: {}", "cache.json has entries that cannot be parsed: {}"),
+    ("health.npmCruft", "{} 个 npm 临时目录，共 {} MB", "{} npm temporary directories, {} MB in total"),
+    ("health.versionsRun", "所有已安装版本均可正常运行", "All installed versions run correctly"),
+    ("health.versionCheckTimeout", "检查已安装版本超时", "Timed out checking installed versions"),
+    ("health.gitMissing", "未检测到 Git，从 Git 地址安装 npm 包会失败", "Git was not found; installing npm packages from Git URLs will fail"),
+    ("path.symlinkMissing", "PATH 中未找到 NVM_SYMLINK 目录，nvm use 切换的版本不会生效", "The NVM_SYMLINK directory is not in PATH; versions switched with nvm use will not take effect"),
+    ("path.nodeBeforeSymlink", "{} 位于 NVM_SYMLINK 之前，会覆盖 nvm 切换的 Node.js 版本", "{} comes before NVM_SYMLINK and overrides the Node.js version selected by nvm"),
+    ("path.duplicate", "{} 在 PATH 中重复出现", "{} appears more than once in PATH"),
+    (
+        "path.syncRisk",
+        "{} 位于 {} 同步目录中，同步会锁定文件并破坏符号链接和 node_modules，建议选择其他位置",
+        "{} is inside a {} synced folder; syncing locks files and breaks symlinks and node_modules. Choose another location",
+    ),
+];
+
+fn current_locale() -> String {
+    let locale = CURRENT_LOCALE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    if locale.is_empty() { default_locale() } else { locale }
+}
+
+fn set_current_locale(locale: &str) {
+    *CURRENT_LOCALE.lock().unwrap_or_else(|e| e.into_inner()) = locale.to_string();
+}
+
+// 按当前语言取消息文本，未知的消息码原样返回
+fn tr(code: &str) -> String {
+    let english = current_locale() == "en";
+    MESSAGES
+        .iter()
+        .find(|(c, _, _)| *c == code)
+        .map(|(_, zh, en)| if english { en.to_string() } else { zh.to_string() })
+        .unwrap_or_else(|| code.to_string())
+}
+
+// 带一个参数的消息，替换文本中的 {}
+fn tr_with(code: &str, arg: &str) -> String {
+    tr(code).replacen("{}", arg, 1)
+}
+
+// 带多个参数的消息，按顺序替换文本中的 {}；参数本身含有 {} 时不会被再次替换
+fn tr_args(code: &str, args: &[&dyn std::fmt::Display]) -> String {
+    let text = tr(code);
+    let mut parts = text.split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for (i, part) in parts.enumerate() {
+        match args.get(i) {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str("{}"),
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+#[tauri::command]
+async fn set_locale<R: Runtime>(app: AppHandle<R>, locale: String) -> Result<bool, String> {
+    if !SUPPORTED_LOCALES.contains(&locale.as_str()) {
+        return Err(tr_args("error.unsupportedLocale", &[&locale]));
+    }
+    update_settings_file(&get_settings_path()?, &[("locale", Some(locale.clone()))])?;
+    set_current_locale(&locale);
+    let _ = refresh_tray(app).await;
+    Ok(true)
+}
+
+// --- 主题 ---
+
+const THEMES: [&str; 3] = ["light", "dark", "system"];
+const THEME_POLL_SECS: u64 = 2;
+
+#[tauri::command]
+async fn get_theme() -> Result<String, String> {
+    Ok(load_app_state().theme.unwrap_or_else(|| "system".to_string()))
+}
+
+#[tauri::command]
+async fn set_theme(theme: String) -> Result<String, String> {
+    if !THEMES.contains(&theme.as_str()) {
+        return Err(tr_args("error.invalidTheme", &[&theme]));
+    }
+    update_app_state(|state| state.theme = Some(theme.clone()))?;
+    Ok(theme)
+}
+
+// Windows 应用的明暗模式，读取不到（例如旧版本系统）时视为浅色
+fn read_system_theme() -> String {
+    let light = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize")
+        .and_then(|key| key.get_value::<u32, _>("AppsUseLightTheme"))
+        .unwrap_or(1);
+    if light == 0 { "dark".to_string() } else { "light".to_string() }
+}
+
+#[tauri::command]
+async fn get_system_theme() -> Result<String, String> {
+    Ok(read_system_theme())
+}
+
+// 轮询系统主题，变化时发送 theme:changed，前端在主题为 system 时据此切换
+fn start_theme_watcher<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut last = read_system_theme();
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(THEME_POLL_SECS)).await;
+            let current = read_system_theme();
+            if current != last {
+                let _ = app.emit("theme:changed", serde_json::json!({ "systemTheme": current }));
+                last = current;
+            }
+        }
+    });
+}
+
 // --- 辅助函数 ---
 
 fn get_settings_path() -> Result<PathBuf, String> {
-    let nvm_home = env::var("NVM_HOME").map_err(|_| "未找到 NVM_HOME 环境变量".to_string())?;
+    let nvm_home = env::var("NVM_HOME").map_err(|_| tr("error.nvmHomeMissing"))?;
     Ok(PathBuf::from(nvm_home).join("settings.txt"))
 }
 
@@ -340,6 +1015,15 @@ fn parse_nvm_settings(content: &str) -> NvmConfig {
         last_updated: None,
         close_action: "ask".to_string(),
         global_prefix: None,
+        auto_switch_mode: default_auto_switch_mode(),
+        keep_archives: false,
+        locale: default_locale(),
+        hidden_mirrors: Vec::new(),
+        custom_mirrors_only: false,
+        download_stall_timeout: default_stall_timeout(),
+        download_retries: default_download_retries(),
+        download_retry_backoff: default_retry_backoff(),
+        package_manager: default_package_manager(),
     };
 
     for line in content.lines() {
@@ -359,6 +1043,17 @@ fn parse_nvm_settings(content: &str) -> NvmConfig {
                 "arch" => config.arch = value,
                 "close_action" => config.close_action = value,
                 "global_prefix" => config.global_prefix = if value.is_empty() { None } else { Some(value) },
+                "auto_switch_mode" => config.auto_switch_mode = value,
+                "keep_archives" => config.keep_archives = value == "true",
+                "locale" => config.locale = value,
+                "hidden_mirrors" => {
+                    config.hidden_mirrors = value.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect()
+                }
+                "custom_mirrors_only" => config.custom_mirrors_only = value == "true",
+                "download_stall_timeout" => config.download_stall_timeout = value.parse().unwrap_or_else(|_| default_stall_timeout()),
+                "download_retries" => config.download_retries = value.parse().unwrap_or_else(|_| default_download_retries()),
+                "download_retry_backoff" => config.download_retry_backoff = value.parse().unwrap_or_else(|_| default_retry_backoff()),
+                "package_manager" => config.package_manager = value,
                 _ => {}
             }
         }
@@ -369,7 +1064,7 @@ fn parse_nvm_settings(content: &str) -> NvmConfig {
 async fn internal_get_config() -> Result<NvmConfig, String> {
     let path = get_settings_path()?;
     if !path.exists() {
-        return Err("nvm settings.txt 不存在".to_string());
+        return Err(tr("error.settingsMissing"));
     }
 
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
@@ -386,29 +1081,48 @@ async fn internal_get_config() -> Result<NvmConfig, String> {
     Ok(config)
 }
 
+// NVM_SYMLINK 可能是符号链接，也可能是目录联接（nvm 在没有创建符号链接权限时使用）。
+// read_link 读取不到时再用 canonicalize 解析重解析点的最终目标
 fn get_current_node_version(symlink_path: &str) -> Option<String> {
     let path = Path::new(symlink_path);
-    if let Ok(target) = fs::read_link(path) {
-        if let Some(name) = target.file_name() {
-            let name_str = name.to_string_lossy();
-            if let Some(version) = name_str.strip_prefix('v') {
-                return Some(version.to_string());
-            }
-        }
+    if let Some(version) = fs::read_link(path).ok().and_then(|t| version_from_link_target(&t)) {
+        return Some(version);
+    }
+    fs::canonicalize(path).ok().and_then(|t| version_from_link_target(&t))
+}
+
+// 从链接目标中取出 vX.Y.Z 目录名，兼容 \\?\ 与 \??\ 前缀以及末尾的分隔符
+fn version_from_link_target(target: &Path) -> Option<String> {
+    let target = target.to_string_lossy();
+    let name = target.trim_end_matches(['\\', '/']).rsplit(['\\', '/']).next()?;
+    let version = name.strip_prefix('v')?;
+    if version.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(version.to_string())
+    } else {
+        None
     }
-    None
 }
 
 fn get_dir_size(path: &Path) -> u64 {
+    get_dir_size_with_cancel(path, &AtomicBool::new(false))
+}
+
+// 可取消的目录大小统计，cancel 被置位后尽快返回（此时结果不完整，调用方需自行检查标志）
+fn get_dir_size_with_cancel(path: &Path, cancel: &AtomicBool) -> u64 {
+    if cancel.load(Ordering::Relaxed) {
+        return 0;
+    }
     fs::read_dir(path)
         .map(|entries| {
             entries
                 .flatten()
+                .take_while(|_| !cancel.load(Ordering::Relaxed))
                 .map(|entry| {
+                    // entry.metadata 不跟随重解析点，符号链接/目录联接不会被进入，避免重复统计或成环
                     let metadata = entry.metadata().ok();
                     if let Some(md) = metadata {
                         if md.is_dir() {
-                            get_dir_size(&entry.path())
+                            get_dir_size_with_cancel(&entry.path(), cancel)
                         } else {
                             md.len()
                         }
@@ -421,12 +1135,95 @@ fn get_dir_size(path: &Path) -> u64 {
         .unwrap_or(0)
 }
 
-fn get_cache_path() -> Result<PathBuf, String> {
+// 便携模式：可执行文件旁存在 portable.flag，或以 --portable 参数启动
+fn is_portable_mode() -> bool {
+    if env::args().any(|a| a == "--portable") {
+        return true;
+    }
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("portable.flag").exists()))
+        .unwrap_or(false)
+}
+
+// 应用自身状态（缓存等）的存放目录
+// 便携模式下为可执行文件旁的 data 目录，否则与 settings.txt 同目录 (NVM_HOME)
+// nvm 配置本身始终从 NVM_HOME/settings.txt 读取
+fn get_app_data_dir() -> Result<PathBuf, String> {
+    if is_portable_mode() {
+        let exe = env::current_exe().map_err(|e| e.to_string())?;
+        let dir = exe.parent().ok_or_else(|| tr("error.exeDirUnavailable"))?.join("data");
+        fs::create_dir_all(&dir).map_err(|e| tr_args("error.createPortableDir", &[&e]))?;
+        return Ok(dir);
+    }
     let settings_path = get_settings_path()?;
-    Ok(settings_path.parent().unwrap().join("cache.json"))
+    Ok(settings_path.parent().unwrap().to_path_buf())
+}
+
+fn get_cache_path() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join("cache.json"))
+}
+
+// 应用自身的持久化状态（项目列表等），保存在应用数据目录的 app_state.json
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct AppState {
+    #[serde(default)]
+    pub projects: Vec<ProjectEntry>,
+    // 最近切换过的版本（不带 v 前缀），第一个为当前版本，第二个为上一个版本
+    #[serde(rename = "switchHistory", default)]
+    pub switch_history: Vec<String>,
+    // 最近几次由应用修改用户环境变量的记录，最新的在前
+    #[serde(rename = "envChanges", default)]
+    pub env_changes: Vec<EnvChange>,
+    // 界面主题："light" / "dark" / "system"，未设置时跟随系统
+    #[serde(default)]
+    pub theme: Option<String>,
+}
+
+const SWITCH_HISTORY_LIMIT: usize = 6;
+
+fn record_switch_history(previous: Option<String>, version: &str) -> Result<AppState, String> {
+    let version = version.trim_start_matches('v').to_string();
+    update_app_state(|state| {
+        state.switch_history.retain(|v| *v != version && Some(v) != previous.as_ref());
+        if let Some(prev) = previous.filter(|p| *p != version) {
+            state.switch_history.insert(0, prev);
+        }
+        state.switch_history.insert(0, version);
+        state.switch_history.truncate(SWITCH_HISTORY_LIMIT);
+    })
+}
+
+static APP_STATE_LOCK: Mutex<()> = Mutex::new(());
+
+fn get_app_state_path() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join("app_state.json"))
+}
+
+fn load_app_state() -> AppState {
+    get_app_state_path()
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// 加锁读取-修改-写回，避免并发修改丢失
+fn update_app_state<F: FnOnce(&mut AppState)>(f: F) -> Result<AppState, String> {
+    let _guard = APP_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = load_app_state();
+    f(&mut state);
+    let content = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+    fs::write(get_app_state_path()?, content).map_err(|e| tr_args("error.saveAppState", &[&e]))?;
+    Ok(state)
 }
 
 async fn get_from_cache(key: &str) -> Option<serde_json::Value> {
+    // 24 小时 = 86400 秒
+    get_from_cache_with_ttl(key, 86400).await
+}
+
+async fn get_from_cache_with_ttl(key: &str, ttl_secs: u64) -> Option<serde_json::Value> {
     let cache_path = get_cache_path().ok()?;
     if !cache_path.exists() {
         return None;
@@ -437,14 +1234,33 @@ async fn get_from_cache(key: &str) -> Option<serde_json::Value> {
 
     if let Some(entry) = cache.get(key) {
         let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
-        // 24 小时 = 86400 秒
-        if now - entry.timestamp < 86400 {
+        if now.saturating_sub(entry.timestamp) < ttl_secs {
             return Some(entry.data.clone());
         }
     }
     None
 }
 
+// 删除指定的缓存条目，例如镜像变化后旧镜像的版本列表
+async fn remove_from_cache(keys: &[&str]) {
+    let Ok(cache_path) = get_cache_path() else { return };
+    let Some(mut cache) = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<HashMap<String, CacheEntry>>(&content).ok())
+    else {
+        return;
+    };
+    let mut removed = false;
+    for key in keys {
+        removed |= cache.remove(*key).is_some();
+    }
+    if removed {
+        if let Ok(content) = serde_json::to_string_pretty(&cache) {
+            let _ = fs::write(cache_path, content);
+        }
+    }
+}
+
 async fn save_to_cache(key: &str, data: serde_json::Value) {
     if let Ok(cache_path) = get_cache_path() {
         let mut cache: HashMap<String, CacheEntry> = fs::read_to_string(&cache_path)
@@ -464,6 +1280,79 @@ async fn save_to_cache(key: &str, data: serde_json::Value) {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheHealth {
+    pub path: String,
+    pub exists: bool,
+    // 整个文件能否被 get_from_cache 读取；为 false 时缓存实际上已失效
+    pub valid: bool,
+    #[serde(rename = "entryCount")]
+    pub entry_count: usize,
+    #[serde(rename = "invalidEntries")]
+    pub invalid_entries: Vec<String>,
+    // 文件本身不是合法的 JSON 对象
+    pub corrupt: bool,
+}
+
+// 逐条解析 cache.json：返回可用的条目和无法解析的键；整个文件不是 JSON 对象时返回 None
+fn parse_cache_entries(content: &str) -> Option<(HashMap<String, CacheEntry>, Vec<String>)> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    let object = value.as_object()?;
+    let mut entries = HashMap::new();
+    let mut invalid = Vec::new();
+    for (key, entry) in object {
+        match serde_json::from_value::<CacheEntry>(entry.clone()) {
+            Ok(entry) => {
+                entries.insert(key.clone(), entry);
+            }
+            Err(_) => invalid.push(key.clone()),
+        }
+    }
+    invalid.sort();
+    Some((entries, invalid))
+}
+
+#[tauri::command]
+async fn check_cache_health() -> Result<CacheHealth, String> {
+    let cache_path = get_cache_path()?;
+    let path = cache_path.to_string_lossy().to_string();
+    if !cache_path.exists() {
+        return Ok(CacheHealth { path, exists: false, valid: true, entry_count: 0, invalid_entries: Vec::new(), corrupt: false });
+    }
+    let content = fs::read_to_string(&cache_path).unwrap_or_default();
+    Ok(match parse_cache_entries(&content) {
+        Some((entries, invalid_entries)) => CacheHealth {
+            path,
+            exists: true,
+            valid: invalid_entries.is_empty(),
+            entry_count: entries.len(),
+            invalid_entries,
+            corrupt: false,
+        },
+        None => CacheHealth { path, exists: true, valid: false, entry_count: 0, invalid_entries: Vec::new(), corrupt: true },
+    })
+}
+
+// 先把原文件备份为 cache.json.bak，再保留可解析的条目重写；文件整体损坏时重建为空缓存
+#[tauri::command]
+async fn repair_cache() -> Result<CacheHealth, String> {
+    let cache_path = get_cache_path()?;
+    if cache_path.exists() {
+        let content = fs::read_to_string(&cache_path).unwrap_or_default();
+        fs::copy(&cache_path, cache_path.with_extension("json.bak")).map_err(|e| tr_args("error.backupCache", &[&e]))?;
+        let entries = parse_cache_entries(&content).map(|(entries, _)| entries).unwrap_or_default();
+        let content = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+        fs::write(&cache_path, content).map_err(|e| tr_args("error.rebuildCache", &[&e]))?;
+    }
+    check_cache_health().await
+}
+
+fn get_user_npmrc_path() -> Result<PathBuf, String> {
+    let user_profile = env::var("USERPROFILE")
+        .map_err(|_| tr("error.userProfileMissing"))?;
+    Ok(PathBuf::from(&user_profile).join(".npmrc"))
+}
+
 fn create_silent_command(cmd: &str) -> Command {
     let mut command = Command::new(cmd);
     #[cfg(windows)]
@@ -522,56 +1411,519 @@ async fn export_config() -> Result<String, String> {
     };
     
     serde_json::to_string_pretty(&export_data)
-        .map_err(|e| format!("序列化失败: {}", e))
+        .map_err(|e| tr_args("error.serialize", &[&e]))
 }
 
+// 纯文本的环境摘要，方便粘贴到聊天或问题反馈中。
+// 标签使用固定的英文，格式保持稳定，不随界面语言变化
 #[tauri::command]
-async fn import_config(json_data: String) -> Result<bool, String> {
-    let export_data: ExportData = serde_json::from_str(&json_data)
-        .map_err(|e| format!("解析失败: {}", e))?;
-    
-    // 验证路径是否存在
-    let nvm_path = Path::new(&export_data.config.nvm_path);
-    if !nvm_path.exists() {
-        return Err(format!("NVM 路径不存在: {}", export_data.config.nvm_path));
-    }
-    
-    // 保存配置
-    let settings_path = get_settings_path()?;
-    let mut content = String::new();
-    
-    content.push_str(&format!("root: {}\n", export_data.config.nvm_path));
-    content.push_str(&format!("path: {}\n", export_data.config.nvm_symlink));
-    
-    if !export_data.config.node_mirror.is_empty() {
-        content.push_str(&format!("node_mirror: {}\n", export_data.config.node_mirror));
-    }
-    if !export_data.config.npm_mirror.is_empty() {
-        content.push_str(&format!("npm_mirror: {}\n", export_data.config.npm_mirror));
-    }
-    if !export_data.config.arch.is_empty() {
-        content.push_str(&format!("arch: {}\n", export_data.config.arch));
-    }
-    if !export_data.config.close_action.is_empty() {
-        content.push_str(&format!("close_action: {}\n", export_data.config.close_action));
+async fn get_summary_text() -> Result<String, String> {
+    let config = internal_get_config().await?;
+    let nvm = check_nvm_installation().await?;
+    let versions = get_installed_versions().await.unwrap_or_default();
+    let mirror = get_current_mirror().await?;
+
+    let active = get_current_node_version(&config.nvm_symlink);
+    let mirror_name = mirror["preset"]["name"]
+        .as_str()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "Custom".to_string());
+
+    let mut lines = vec![
+        format!("nvm-windows GUI {}", env!("CARGO_PKG_VERSION")),
+        format!("nvm: {}", nvm.version.as_deref().unwrap_or("not installed")),
+        format!("Active: {}", active.map(|v| format!("v{}", v)).unwrap_or_else(|| "none".to_string())),
+        format!("Arch: {}", to_dist_arch(&config.arch)),
+        format!("Node mirror: {} ({})", mirror_name, mirror["nodeUrl"].as_str().unwrap_or("")),
+        format!("npm mirror: {}", if config.npm_mirror.is_empty() { "https://registry.npmjs.org/" } else { &config.npm_mirror }),
+        format!("Installed ({}):", versions.len()),
+    ];
+    for v in &versions {
+        let marker = if v.is_active { "*" } else { " " };
+        let arch = v.arch.as_deref().map(|a| format!(" [{}]", a)).unwrap_or_default();
+        lines.push(format!("  {} v{}{}", marker, v.version, arch));
     }
-    if let Some(ref prefix) = export_data.config.global_prefix {
-        content.push_str(&format!("global_prefix: {}\n", prefix));
+    Ok(lines.join("\r\n"))
+}
+
+// 生成设置 nvm 环境变量的脚本（cmd 或 PowerShell），用于 CI 或批量部署时复现配置。
+// PATH 中已存在的目录不会重复添加，脚本可以多次执行
+fn build_env_script(format: &str, nvm_home: &str, nvm_symlink: &str, global_prefix: Option<&str>) -> Result<String, String> {
+    // 最终顺序为 NVM_HOME;NVM_SYMLINK;全局目录;原有 PATH，逐个前置时需要倒序
+    let mut path_dirs: Vec<&str> = vec![nvm_home, nvm_symlink];
+    path_dirs.extend(global_prefix);
+    path_dirs.reverse();
+    let header = [
+        format!("nvm-windows GUI {} environment script", env!("CARGO_PKG_VERSION")),
+        format!("Generated: {}", Local::now().format("%Y-%m-%d %H:%M:%S")),
+        "Sets NVM_HOME / NVM_SYMLINK for the current user and prepends them to the user PATH.".to_string(),
+    ];
+
+    let mut lines: Vec<String> = Vec::new();
+    match format {
+        "cmd" => {
+            lines.push("@echo off".to_string());
+            lines.extend(header.iter().map(|h| format!("rem {}", h)));
+            lines.push("setlocal".to_string());
+            // 只读取用户级 PATH 的原始值（保留 %USERPROFILE% 等未展开的引用），避免把系统 PATH 合并写进用户变量
+            lines.push("set \"USER_PATH=\"".to_string());
+            lines.push("for /f \"skip=2 tokens=2,*\" %%A in ('reg query HKCU\\Environment /v Path 2^>nul') do set \"USER_PATH=%%B\"".to_string());
+            for dir in &path_dirs {
+                lines.push(format!(
+                    "echo ;%USER_PATH%; | find /i \";{dir};\" >nul || set \"USER_PATH={dir};%USER_PATH%\"",
+                    dir = dir
+                ));
+            }
+            // setx 会把超过 1024 个字符的值截断，PATH 改用 reg add 按 REG_EXPAND_SZ 写入；
+            // 末尾的反斜杠会转义结束引号，需要再补一个
+            lines.push("if \"%USER_PATH:~-1%\"==\"\\\" set \"USER_PATH=%USER_PATH%\\\"".to_string());
+            lines.push("reg add HKCU\\Environment /v Path /t REG_EXPAND_SZ /d \"%USER_PATH%\" /f >nul".to_string());
+            // reg add 不会广播环境变量变更，setx 放在最后，由它通知其他程序重新读取
+            for (name, value) in [("NVM_HOME", nvm_home), ("NVM_SYMLINK", nvm_symlink)] {
+                lines.push(format!("setx {} \"{}\" >nul", name, value));
+            }
+            lines.push("endlocal".to_string());
+            lines.push("echo Done. Open a new terminal to pick up the changes.".to_string());
+        }
+        "powershell" => {
+            let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+            lines.extend(header.iter().map(|h| format!("# {}", h)));
+            lines.push("$ErrorActionPreference = 'Stop'".to_string());
+            // GetEnvironmentVariable 返回展开后的值，直接读取注册表原始值，写回时保持 ExpandString 类型
+            lines.push("$userPath = (Get-Item 'HKCU:\\Environment').GetValue('Path', '', 'DoNotExpandEnvironmentNames')".to_string());
+            lines.push("$entries = @(\"$userPath\" -split ';' | Where-Object { $_ })".to_string());
+            lines.push(format!(
+                "foreach ($dir in @({})) {{",
+                path_dirs.iter().map(|d| quote(d)).collect::<Vec<_>>().join(", ")
+            ));
+            lines.push("    if (-not ($entries | Where-Object { $_.TrimEnd('\\') -ieq $dir.TrimEnd('\\') })) { $entries = @($dir) + $entries }".to_string());
+            lines.push("    if (-not (($env:Path -split ';') -contains $dir)) { $env:Path = \"$dir;$env:Path\" }".to_string());
+            lines.push("}".to_string());
+            lines.push("Set-ItemProperty -Path 'HKCU:\\Environment' -Name 'Path' -Value ($entries -join ';') -Type ExpandString".to_string());
+            // SetEnvironmentVariable 会广播环境变量变更，放在 PATH 写入之后
+            for (name, value) in [("NVM_HOME", nvm_home), ("NVM_SYMLINK", nvm_symlink)] {
+                lines.push(format!("[Environment]::SetEnvironmentVariable('{}', {}, 'User')", name, quote(value)));
+                lines.push(format!("$env:{} = {}", name, quote(value)));
+            }
+            lines.push("Write-Host 'Done. Open a new terminal to pick up the changes.'".to_string());
+        }
+        other => return Err(tr_args("error.unsupportedEnvScript", &[&other])),
     }
-    
-    fs::write(&settings_path, content)
-        .map_err(|e| format!("写入配置失败: {}", e))?;
-    
-    Ok(true)
+    lines.push(String::new());
+    Ok(lines.join("\r\n"))
 }
 
 #[tauri::command]
-async fn save_config_to_file(file_path: String) -> Result<bool, String> {
+async fn export_env_script(format: String) -> Result<String, String> {
     let config = internal_get_config().await?;
-    
-    // 获取已安装版本列表
-    let nvm_path = Path::new(&config.nvm_path);
-    let mut installed_versions = Vec::new();
+    // NVM_HOME 是 settings.txt 所在目录，读取不到时退回 nvm 根目录
+    let nvm_home = get_settings_path()
+        .ok()
+        .and_then(|p| p.parent().map(|d| d.to_string_lossy().to_string()))
+        .unwrap_or_else(|| config.nvm_path.clone());
+    build_env_script(&format.to_lowercase(), &nvm_home, &config.nvm_symlink, config.global_prefix.as_deref())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppInfo {
+    #[serde(rename = "appVersion")]
+    pub app_version: String,
+    #[serde(rename = "tauriVersion")]
+    pub tauri_version: String,
+    #[serde(rename = "webviewVersion")]
+    pub webview_version: Option<String>,
+    #[serde(rename = "exePath")]
+    pub exe_path: Option<String>,
+    #[serde(rename = "settingsPath")]
+    pub settings_path: Option<String>,
+    #[serde(rename = "dataDir")]
+    pub data_dir: Option<String>,
+    #[serde(rename = "cachePath")]
+    pub cache_path: Option<String>,
+    #[serde(rename = "archiveCacheDir")]
+    pub archive_cache_dir: Option<String>,
+    #[serde(rename = "logDir")]
+    pub log_dir: Option<String>,
+    pub portable: bool,
+    #[serde(rename = "osBuild")]
+    pub os_build: Option<String>,
+}
+
+// 形如 "Windows 10 Pro 22H2 (19045.4170)"，读取失败时为 None
+fn get_os_build() -> Option<String> {
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion")
+        .ok()?;
+    let product: String = key.get_value("ProductName").ok()?;
+    let build: String = key.get_value("CurrentBuild").ok()?;
+    let display: Option<String> = key.get_value("DisplayVersion").ok();
+    let ubr: Option<u32> = key.get_value("UBR").ok();
+    let mut text = product;
+    if let Some(display) = display {
+        text.push(' ');
+        text.push_str(&display);
+    }
+    match ubr {
+        Some(ubr) => text.push_str(&format!(" ({}.{})", build, ubr)),
+        None => text.push_str(&format!(" ({})", build)),
+    }
+    Some(text)
+}
+
+// 关于页面与问题反馈使用的应用自身信息，只包含路径和版本，不包含 PATH 等环境变量
+#[tauri::command]
+async fn get_app_info<R: Runtime>(app: AppHandle<R>) -> Result<AppInfo, String> {
+    let path_string = |p: PathBuf| p.to_string_lossy().to_string();
+    Ok(AppInfo {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        webview_version: tauri::webview_version().ok(),
+        exe_path: env::current_exe().ok().map(path_string),
+        settings_path: get_settings_path().ok().map(path_string),
+        data_dir: get_app_data_dir().ok().map(path_string),
+        cache_path: get_cache_path().ok().map(path_string),
+        archive_cache_dir: get_archive_cache_dir().ok().map(path_string),
+        log_dir: app.path().app_log_dir().ok().map(path_string),
+        portable: is_portable_mode(),
+        os_build: get_os_build(),
+    })
+}
+
+// 通过系统自带的 clip.exe 写入剪贴板，输入使用带 BOM 的 UTF-16 以保留中文路径
+#[tauri::command]
+async fn copy_to_clipboard(text: String) -> Result<bool, String> {
+    let mut child = create_silent_command("clip")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| tr_args("error.startClip", &[&e]))?;
+    let mut bytes = vec![0xFF, 0xFE];
+    for unit in text.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_le_bytes());
+    }
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&bytes).map_err(|e| tr_args("error.writeClipboard", &[&e]))?;
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    Ok(status.success())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportValidation {
+    pub valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// child 与 parent 相同或位于其下（Windows 路径不区分大小写）
+fn is_same_or_nested_path(child: &str, parent: &str) -> bool {
+    let normalize = |p: &str| p.trim().replace('/', "\\").trim_end_matches('\\').to_lowercase();
+    let (child, parent) = (normalize(child), normalize(parent));
+    !parent.is_empty() && (child == parent || child.starts_with(&format!("{}\\", parent)))
+}
+
+// 只做检查不写入任何文件，errors 非空时不应导入
+fn validate_export_data(data: &ExportData) -> ImportValidation {
+    let config = &data.config;
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if config.nvm_path.trim().is_empty() {
+        errors.push(tr("import.nvmPathEmpty"));
+    } else if !Path::new(&config.nvm_path).is_dir() {
+        errors.push(tr_args("error.nvmPathMissing", &[&config.nvm_path]));
+    }
+    if config.nvm_symlink.trim().is_empty() {
+        errors.push(tr("import.symlinkEmpty"));
+    } else if is_same_or_nested_path(&config.nvm_symlink, &config.nvm_path) {
+        errors.push(tr_args("import.symlinkInsideNvm", &[&config.nvm_symlink]));
+    } else if let Ok(meta) = fs::symlink_metadata(&config.nvm_symlink) {
+        if !meta.file_type().is_symlink() {
+            warnings.push(tr_args("import.symlinkIsDir", &[&config.nvm_symlink]));
+        }
+    }
+    if let Some(ref prefix) = config.global_prefix {
+        if is_same_or_nested_path(prefix, &config.nvm_symlink) {
+            errors.push(tr_args("import.prefixInsideSymlink", &[prefix]));
+        } else if !Path::new(prefix).exists() {
+            warnings.push(tr_args("import.prefixMissing", &[prefix]));
+        }
+    }
+
+    for (code, url) in [("import.nodeMirror", &config.node_mirror), ("import.npmMirror", &config.npm_mirror)] {
+        if let Err(e) = normalize_mirror_url(url) {
+            errors.push(tr_args(code, &[&e]));
+        }
+    }
+    if !config.arch.is_empty() && !["32", "64", "arm64"].contains(&config.arch.as_str()) {
+        errors.push(tr_args("import.unsupportedArch", &[&config.arch]));
+    }
+    if !CLOSE_ACTIONS.contains(&config.close_action.as_str()) {
+        warnings.push(tr_args("import.unknownCloseAction", &[&config.close_action]));
+    }
+    if !SUPPORTED_LOCALES.contains(&config.locale.as_str()) {
+        warnings.push(tr_args("import.unsupportedLocale", &[&config.locale]));
+    }
+
+    for path in [Some(&config.nvm_path), Some(&config.nvm_symlink), config.global_prefix.as_ref()].into_iter().flatten() {
+        if let Some(warning) = check_path_sync_risk(path.clone()).warning {
+            warnings.push(warning);
+        }
+    }
+    for version in &data.installed_versions {
+        if version.trim_start_matches('v').split('.').count() != 3 {
+            warnings.push(tr_args("import.unrecognizedVersion", &[version]));
+        }
+    }
+
+    ImportValidation { valid: errors.is_empty(), errors, warnings }
+}
+
+#[tauri::command]
+async fn validate_import(json_data: String) -> Result<ImportValidation, String> {
+    Ok(match serde_json::from_str::<ExportData>(&json_data) {
+        Ok(data) => validate_export_data(&data),
+        Err(e) => ImportValidation {
+            valid: false,
+            errors: vec![tr_args("error.parse", &[&e])],
+            warnings: Vec::new(),
+        },
+    })
+}
+
+// require_valid 为 true 时先执行 validate_import 的全部检查，有错误则不写入
+#[tauri::command]
+async fn import_config<R: Runtime>(app: AppHandle<R>, json_data: String, require_valid: Option<bool>) -> Result<bool, String> {
+    let export_data: ExportData = serde_json::from_str(&json_data)
+        .map_err(|e| tr_args("error.parse", &[&e]))?;
+
+    if require_valid.unwrap_or(false) {
+        let report = validate_export_data(&export_data);
+        if !report.valid {
+            return Err(report.errors.join("\n"));
+        }
+    }
+    
+    // 验证路径是否存在
+    let nvm_path = Path::new(&export_data.config.nvm_path);
+    if !nvm_path.exists() {
+        return Err(tr_args("error.nvmPathMissing", &[&export_data.config.nvm_path]));
+    }
+    
+    // 按键更新 settings.txt：保留导出文件中没有的键，并与其他写入共用 SETTINGS_LOCK
+    let old_mirror = internal_get_config().await.ok().map(|c| c.node_mirror);
+    update_settings_file(&get_settings_path()?, &config_to_settings(&export_data.config))?;
+    set_current_locale(&export_data.config.locale);
+    if old_mirror.as_ref() != Some(&export_data.config.node_mirror) {
+        remove_from_cache(&AVAILABLE_VERSIONS_CACHE_KEYS).await;
+    }
+
+    // 导入的配置可能使用不同的安装目录，重新监听
+    let _ = start_version_watcher(&app, &export_data.config.nvm_path);
+    
+    Ok(true)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportInstallReport {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<serde_json::Value>,
+}
+
+// 应用导出文件中的配置，并依次安装其中列出但本机尚未安装的版本
+// 每个版本开始和结束时发送 import:install:progress，下载过程仍通过 install:progress 汇报
+#[tauri::command]
+async fn install_from_export(window: WebviewWindow, json_data: String) -> Result<ImportInstallReport, String> {
+    let export_data: ExportData = serde_json::from_str(&json_data)
+        .map_err(|e| tr_args("error.parse", &[&e]))?;
+    import_config(window.app_handle().clone(), json_data, Some(true)).await?;
+
+    let installed = list_installed_version_names(Path::new(&export_data.config.nvm_path));
+    let missing: Vec<String> = export_data
+        .installed_versions
+        .iter()
+        .filter(|v| !installed.iter().any(|i| i == v.trim_start_matches('v')))
+        .cloned()
+        .collect();
+
+    let mut report = ImportInstallReport { succeeded: Vec::new(), failed: Vec::new() };
+    let total = missing.len();
+
+    // 登记为当前批次，同一时间只允许一个批次
+    let batch_id = format!("batch-{}", Local::now().timestamp_millis());
+    let paused = Arc::new(AtomicBool::new(false));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let batch_state = window.app_handle().state::<BatchState>();
+        let mut current = batch_state.current.lock().unwrap();
+        if current.is_some() {
+            return Err(tr("error.batchRunning"));
+        }
+        *current = Some(BatchInfo {
+            id: batch_id.clone(),
+            versions: missing.iter().map(|v| if v.starts_with('v') { v.clone() } else { format!("v{}", v) }).collect(),
+            paused: paused.clone(),
+            cancelled: cancelled.clone(),
+        });
+    }
+
+    for (index, version) in missing.into_iter().enumerate() {
+        // 整批暂停时尚未开始的版本等待恢复，取消后不再开始
+        while paused.load(Ordering::SeqCst) && !cancelled.load(Ordering::SeqCst) {
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        }
+        if cancelled.load(Ordering::SeqCst) {
+            let _ = window.emit("batch:progress", serde_json::json!({
+                "batchId": batch_id,
+                "version": version,
+                "index": index,
+                "total": total,
+                "status": "skipped"
+            }));
+            report.failed.push(serde_json::json!({ "version": version, "error": tr("error.batchCancelled") }));
+            continue;
+        }
+
+        for event in ["import:install:progress", "batch:progress"] {
+            let _ = window.emit(event, serde_json::json!({
+                "batchId": batch_id,
+                "version": version,
+                "index": index,
+                "total": total,
+                "status": "installing"
+            }));
+        }
+
+        let result = match start_install_task(&window, version.clone(), None, false, None).await {
+            Ok(handle) => {
+                // 在启动任务的同时整批被暂停时，新任务也以暂停状态开始
+                if paused.load(Ordering::SeqCst) {
+                    let id = if version.starts_with('v') { version.clone() } else { format!("v{}", version) };
+                    let state = window.app_handle().state::<DownloadState>();
+                    let tasks = state.tasks.lock().unwrap();
+                    if let Some(task) = tasks.get(&id) {
+                        task.pause_flag.store(true, Ordering::SeqCst);
+                    }
+                }
+                handle.await.map_err(|e| e.to_string()).and_then(|r| r)
+            }
+            Err(e) => Err(e),
+        };
+
+        for event in ["import:install:progress", "batch:progress"] {
+            let _ = window.emit(event, serde_json::json!({
+                "batchId": batch_id,
+                "version": version,
+                "index": index,
+                "total": total,
+                "status": if result.is_ok() { "done" } else { "failed" },
+                "error": result.as_ref().err()
+            }));
+        }
+        match result {
+            Ok(()) => report.succeeded.push(version),
+            Err(e) => report.failed.push(serde_json::json!({ "version": version, "error": e })),
+        }
+    }
+
+    *window.app_handle().state::<BatchState>().current.lock().unwrap() = None;
+    Ok(report)
+}
+
+// 对当前批次中正在运行的任务执行 f，返回批次 id 和受影响的任务 id
+fn for_each_batch_task<F: Fn(&TaskInfo)>(
+    batch_state: &BatchState,
+    download_state: &DownloadState,
+    f: F,
+) -> Result<(String, Vec<String>), String> {
+    let current = batch_state.current.lock().unwrap();
+    let batch = current.as_ref().ok_or_else(|| tr("error.noBatch"))?;
+    let tasks = download_state.tasks.lock().unwrap();
+    let mut affected = Vec::new();
+    for id in &batch.versions {
+        if let Some(task) = tasks.get(id) {
+            f(task);
+            affected.push(id.clone());
+        }
+    }
+    Ok((batch.id.clone(), affected))
+}
+
+#[tauri::command]
+async fn pause_batch(
+    window: WebviewWindow,
+    batch_state: tauri::State<'_, BatchState>,
+    download_state: tauri::State<'_, DownloadState>,
+) -> Result<bool, String> {
+    if let Some(batch) = batch_state.current.lock().unwrap().as_ref() {
+        batch.paused.store(true, Ordering::SeqCst);
+    }
+    let (batch_id, affected) = for_each_batch_task(&batch_state, &download_state, |task| {
+        task.pause_flag.store(true, Ordering::SeqCst);
+        #[cfg(windows)]
+        if let Some(p) = *task.pid.lock().unwrap() {
+            let _ = suspend_process_tree(p);
+        }
+    })?;
+    let _ = window.emit("batch:progress", serde_json::json!({
+        "batchId": batch_id,
+        "status": "paused",
+        "versions": affected
+    }));
+    Ok(true)
+}
+
+#[tauri::command]
+async fn resume_batch(
+    window: WebviewWindow,
+    batch_state: tauri::State<'_, BatchState>,
+    download_state: tauri::State<'_, DownloadState>,
+) -> Result<bool, String> {
+    if let Some(batch) = batch_state.current.lock().unwrap().as_ref() {
+        batch.paused.store(false, Ordering::SeqCst);
+    }
+    let (batch_id, affected) = for_each_batch_task(&batch_state, &download_state, |task| {
+        task.pause_flag.store(false, Ordering::SeqCst);
+        #[cfg(windows)]
+        if let Some(p) = *task.pid.lock().unwrap() {
+            let _ = resume_process_tree(p);
+        }
+    })?;
+    let _ = window.emit("batch:progress", serde_json::json!({
+        "batchId": batch_id,
+        "status": "resumed",
+        "versions": affected
+    }));
+    Ok(true)
+}
+
+// 取消正在运行的任务（按单独取消的流程清理），排队中的版本不再开始
+#[tauri::command]
+async fn cancel_batch(
+    window: WebviewWindow,
+    batch_state: tauri::State<'_, BatchState>,
+    download_state: tauri::State<'_, DownloadState>,
+) -> Result<Vec<String>, String> {
+    if let Some(batch) = batch_state.current.lock().unwrap().as_ref() {
+        batch.cancelled.store(true, Ordering::SeqCst);
+    }
+    let (batch_id, affected) = for_each_batch_task(&batch_state, &download_state, |task| {
+        let _ = task.cancel_tx.send(());
+    })?;
+    let _ = window.emit("batch:progress", serde_json::json!({
+        "batchId": batch_id,
+        "status": "cancelled",
+        "versions": affected
+    }));
+    Ok(affected)
+}
+
+#[tauri::command]
+async fn save_config_to_file(file_path: String) -> Result<bool, String> {
+    let config = internal_get_config().await?;
+    
+    // 获取已安装版本列表
+    let nvm_path = Path::new(&config.nvm_path);
+    let mut installed_versions = Vec::new();
     
     if nvm_path.exists() {
         if let Ok(entries) = fs::read_dir(nvm_path) {
@@ -598,21 +1950,205 @@ async fn save_config_to_file(file_path: String) -> Result<bool, String> {
     };
     
     let json = serde_json::to_string_pretty(&export_data)
-        .map_err(|e| format!("序列化失败: {}", e))?;
+        .map_err(|e| tr_args("error.serialize", &[&e]))?;
     
     fs::write(&file_path, json)
-        .map_err(|e| format!("写入文件失败: {}", e))?;
+        .map_err(|e| tr_args("error.writeFile", &[&e]))?;
     
     Ok(true)
 }
 
+// 导出可直接执行的安装脚本，format 为 cmd / powershell / bash
+#[tauri::command]
+async fn export_install_script(format: String, include_global_packages: bool) -> Result<String, String> {
+    let (comment, header, npm) = match format.as_str() {
+        "cmd" => ("REM", "@echo off", "call npm"),
+        "powershell" => ("#", "$ErrorActionPreference = \"Continue\"", "npm"),
+        "bash" => ("#", "#!/usr/bin/env bash", "npm"),
+        _ => return Err(tr_args("error.unsupportedScript", &[&format])),
+    };
+
+    let config = internal_get_config().await?;
+    let mut versions = list_installed_version_names(Path::new(&config.nvm_path));
+    versions.sort_by(|a, b| compare_semver(a, b));
+    let active = get_current_node_version(&config.nvm_symlink);
+
+    let mut lines = vec![
+        header.to_string(),
+        format!("{} Generated by nvm-windows GUI {}", comment, env!("CARGO_PKG_VERSION")),
+        format!("{} Exported at {}", comment, Local::now().format("%Y-%m-%d %H:%M:%S")),
+        String::new(),
+    ];
+    for version in &versions {
+        lines.push(format!("nvm install {}", version));
+    }
+    if let Some(ref active) = active {
+        lines.push(format!("nvm use {}", active));
+    }
+
+    if include_global_packages {
+        // npm / corepack 随 Node.js 一起安装，无需重复安装
+        let packages: Vec<Package> = get_global_packages(None)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.name != "npm" && p.name != "corepack")
+            .collect();
+        if !packages.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("{} Global packages", comment));
+            for package in packages {
+                lines.push(format!("{} install -g {}@{}", npm, package.name, package.version));
+            }
+        }
+    }
+
+    let mut script = lines.join("\n");
+    script.push('\n');
+    Ok(script)
+}
+
 #[tauri::command]
 async fn load_config_from_file(file_path: String) -> Result<String, String> {
     let content = fs::read_to_string(&file_path)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
+        .map_err(|e| tr_args("error.readFile", &[&e]))?;
     Ok(content)
 }
 
+// 使用系统默认程序打开文件，没有关联程序时退回记事本
+#[cfg(windows)]
+fn shell_open_file(path: &Path) -> Result<(), String> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::Shell::ShellExecuteW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let operation: Vec<u16> = OsStr::new("open").encode_wide().chain(Some(0)).collect();
+    let file: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let result = unsafe {
+        ShellExecuteW(0, operation.as_ptr(), file.as_ptr(), std::ptr::null(), std::ptr::null(), SW_SHOWNORMAL)
+    };
+    // ShellExecuteW 返回值大于 32 表示成功
+    if result > 32 {
+        return Ok(());
+    }
+    Command::new("notepad.exe")
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| tr_args("error.openFile", &[&e]))
+}
+
+#[cfg(not(windows))]
+fn shell_open_file(_path: &Path) -> Result<(), String> {
+    Err(tr("error.windowsOnly"))
+}
+
+// which: "settings" 打开 nvm 的 settings.txt，"npmrc" 打开用户 .npmrc（不存在时创建空文件）
+// 编辑器关闭无法感知，编辑完成后由前端调用 reload_config 重新读取
+#[tauri::command]
+async fn open_config_file(which: String) -> Result<String, String> {
+    let path = match which.as_str() {
+        "settings" => {
+            let path = get_settings_path()?;
+            if !path.exists() {
+                return Err(tr("error.settingsMissing"));
+            }
+            path
+        }
+        "npmrc" => {
+            let path = get_user_npmrc_path()?;
+            if !path.exists() {
+                fs::write(&path, "").map_err(|e| tr_args("error.createNpmrc", &[&e]))?;
+            }
+            path
+        }
+        _ => return Err(tr_args("error.unknownConfigFile", &[&which])),
+    };
+    shell_open_file(&path)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn reload_config<R: Runtime>(app: AppHandle<R>) -> Result<NvmConfig, String> {
+    let config = internal_get_config().await?;
+    // 手动编辑可能修改了路径等信息，同步刷新托盘菜单
+    let _ = refresh_tray(app).await;
+    Ok(config)
+}
+
+// 重置前备份 settings.txt 及应用状态文件到应用数据目录下的 backups/reset-时间戳，返回备份目录
+fn backup_app_files() -> Result<PathBuf, String> {
+    backup_app_files_to(&get_app_data_dir()?.join("backups"), "reset")
+}
+
+// 在 root 下创建 前缀-时间戳 目录并复制 settings.txt、cache.json 和 app_state.json
+fn backup_app_files_to(root: &Path, prefix: &str) -> Result<PathBuf, String> {
+    let backup_dir = root.join(format!("{}-{}", prefix, Local::now().format("%Y%m%d-%H%M%S")));
+    copy_backup_files(&backup_dir, &[get_settings_path()?, get_cache_path()?, get_app_state_path()?])?;
+    Ok(backup_dir)
+}
+
+fn copy_backup_files(backup_dir: &Path, files: &[PathBuf]) -> Result<(), String> {
+    fs::create_dir_all(backup_dir).map_err(|e| tr_args("error.createBackupDir", &[&e]))?;
+    for file in files.iter().filter(|f| f.exists()) {
+        if let Some(name) = file.file_name() {
+            fs::copy(file, backup_dir.join(name)).map_err(|e| tr_args("error.backupFile", &[&file.display(), &e]))?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetSummary {
+    #[serde(rename = "backupPath")]
+    pub backup_path: String,
+    pub reset: Vec<String>,
+}
+
+// scope: "app" 重置应用自身状态（缓存、项目列表），"nvm" 将 settings.txt 中的镜像/架构/关闭行为等恢复默认，"all" 两者都做
+// root / path 保持不变，已安装的 Node 版本不会被删除
+#[tauri::command]
+async fn reset_app_settings<R: Runtime>(app: AppHandle<R>, scope: String) -> Result<ResetSummary, String> {
+    let (reset_app, reset_nvm) = match scope.as_str() {
+        "app" => (true, false),
+        "nvm" => (false, true),
+        "all" => (true, true),
+        _ => return Err(tr_args("error.invalidResetScope", &[&scope])),
+    };
+
+    let backup_dir = backup_app_files()?;
+    let mut reset = Vec::new();
+
+    if reset_app {
+        for path in [get_cache_path()?, get_app_state_path()?] {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| tr_args("error.delete", &[&path.display(), &e]))?;
+                reset.push(path.file_name().unwrap_or_default().to_string_lossy().to_string());
+            }
+        }
+    }
+
+    if reset_nvm {
+        let defaults = parse_nvm_settings("");
+        let updates = [
+            ("node_mirror", Some(defaults.node_mirror)),
+            ("npm_mirror", None),
+            ("arch", Some(defaults.arch)),
+            ("close_action", Some(defaults.close_action)),
+            ("auto_switch_mode", Some(defaults.auto_switch_mode)),
+        ];
+        update_settings_file(&get_settings_path()?, &updates)?;
+        reset.extend(updates.iter().map(|(key, _)| format!("settings.txt: {}", key)));
+    }
+
+    let _ = refresh_tray(app).await;
+    Ok(ResetSummary {
+        backup_path: backup_dir.to_string_lossy().to_string(),
+        reset,
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NvmrcInfo {
     pub version: String,
@@ -628,7 +2164,7 @@ async fn read_nvmrc(dir_path: String) -> Result<Option<NvmrcInfo>, String> {
     let nvmrc_path = dir.join(".nvmrc");
     if nvmrc_path.exists() {
         let content = fs::read_to_string(&nvmrc_path)
-            .map_err(|e| format!("读取 .nvmrc 失败: {}", e))?;
+            .map_err(|e| tr_args("error.readNvmrc", &[&e]))?;
         let version = content.trim().to_string();
         if !version.is_empty() {
             return Ok(Some(NvmrcInfo {
@@ -643,7 +2179,7 @@ async fn read_nvmrc(dir_path: String) -> Result<Option<NvmrcInfo>, String> {
     let node_version_path = dir.join(".node-version");
     if node_version_path.exists() {
         let content = fs::read_to_string(&node_version_path)
-            .map_err(|e| format!("读取 .node-version 失败: {}", e))?;
+            .map_err(|e| tr_args("error.readNodeVersion", &[&e]))?;
         let version = content.trim().to_string();
         if !version.is_empty() {
             return Ok(Some(NvmrcInfo {
@@ -662,7 +2198,7 @@ async fn get_installed_versions() -> Result<Vec<NodeVersion>, String> {
     let config = internal_get_config().await?;
     let nvm_path = Path::new(&config.nvm_path);
     if !nvm_path.exists() {
-        return Err("NVM 根目录不存在".to_string());
+        return Err(tr("error.nvmRootMissing"));
     }
 
     let mut versions = Vec::new();
@@ -671,7 +2207,7 @@ async fn get_installed_versions() -> Result<Vec<NodeVersion>, String> {
     if let Ok(entries) = fs::read_dir(nvm_path) {
         for entry in entries.flatten() {
             let path = entry.path();
-            if path.is_dir() {
+            if path.is_dir() && !is_unsafe_reparse_point(&path, nvm_path) {
                 let name = entry.file_name().into_string().unwrap_or_default();
                 if name.starts_with('v') && name.split('.').count() >= 3 {
                     // Check if node.exe exists to confirm it's not a partial download
@@ -681,22 +2217,8 @@ async fn get_installed_versions() -> Result<Vec<NodeVersion>, String> {
                     }
 
                     let version = name[1..].to_string();
-                    let metadata = entry.metadata().ok();
-                    let installed_date = metadata
-                        .and_then(|m| m.created().ok())
-                        .map(|t| {
-                            let dt: DateTime<Local> = t.into();
-                            dt.format("%Y-%m-%d %H:%M:%S").to_string()
-                        })
-                        .unwrap_or_else(|| "未知".to_string());
-
-                    versions.push(NodeVersion {
-                        version: version.clone(),
-                        path: path.to_string_lossy().to_string(),
-                        is_active: current_node.as_ref() == Some(&version),
-                        installed_date,
-                        size: get_dir_size(&path), 
-                    });
+                    let is_active = current_node.as_ref() == Some(&version);
+                    versions.push(build_node_version(&path, version, is_active));
                 }
             }
         }
@@ -705,68 +2227,521 @@ async fn get_installed_versions() -> Result<Vec<NodeVersion>, String> {
     Ok(versions)
 }
 
-#[tauri::command]
-async fn get_version_size(path: String) -> Result<u64, String> {
-    let p = Path::new(&path);
-    if p.exists() {
-        Ok(get_dir_size(p))
-    } else {
-        Err("路径不存在".to_string())
+// 读取单个版本目录的完整信息（大小统计在慢速磁盘上较耗时）
+fn build_node_version(path: &Path, version: String, is_active: bool) -> NodeVersion {
+    let installed_date = fs::metadata(path)
+        .ok()
+        .and_then(|m| m.created().ok())
+        .map(|t| {
+            let dt: DateTime<Local> = t.into();
+            dt.format("%Y-%m-%d %H:%M:%S").to_string()
+        })
+        .unwrap_or_else(|| "未知".to_string());
+
+    NodeVersion {
+        version,
+        path: path.to_string_lossy().to_string(),
+        is_active,
+        is_default: is_active,
+        installed_date,
+        size: get_dir_size(path),
+        arch: detect_version_arch(path),
     }
 }
 
-#[tauri::command]
-async fn get_active_version() -> Result<Option<String>, String> {
-    let config = internal_get_config().await?;
-    Ok(get_current_node_version(&config.nvm_symlink))
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionRunCheck {
+    pub runs: bool,
+    #[serde(rename = "reportedVersion")]
+    pub reported_version: Option<String>,
+    pub error: Option<String>,
 }
 
-// 自动应用 npm registry 配置
-async fn apply_npm_registry() -> Result<(), String> {
-    let config = internal_get_config().await?;
-    
-    // 使用 get_registry_for_npm 获取正确的 registry URL
-    let registry_url = get_registry_for_npm(&config.npm_mirror)
-        .unwrap_or_else(|| "https://registry.npmjs.org/".to_string());
-    
-    // 执行 npm config set registry
-    let output = create_silent_command("npm.cmd")
-        .args(["config", "set", "registry", &registry_url])
-        .output();
-    
-    if let Err(e) = output {
-        // 忽略错误，因为可能在没有 node 时执行
-        eprintln!("Warning: Failed to set npm registry: {}", e);
+// 通过绝对路径直接运行 node.exe -v（不切换版本），能发现被杀毒软件隔离或解压不完整的安装
+fn check_node_runs(node_exe: &Path, expected: &str) -> VersionRunCheck {
+    let expected = if expected.starts_with('v') { expected.to_string() } else { format!("v{}", expected) };
+    let fail = |reported: Option<String>, error: String| VersionRunCheck { runs: false, reported_version: reported, error: Some(error) };
+    if !node_exe.is_file() {
+        return fail(None, tr_args("error.notFound", &[&node_exe.display()]));
     }
-    
-    Ok(())
+    let output = match create_silent_command(&node_exe.to_string_lossy()).arg("-v").output() {
+        Ok(o) => o,
+        Err(e) => return fail(None, tr_args("error.runNode", &[&e])),
+    };
+    if !output.status.success() {
+        let code = output.status.code().map(|c| c.to_string()).unwrap_or_else(|| "未知".to_string());
+        return fail(None, tr_args("error.nodeExitCode", &[&code, &String::from_utf8_lossy(&output.stderr).trim()]));
+    }
+    let reported = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if reported != expected {
+        return fail(Some(reported.clone()), tr_args("error.versionMismatch", &[&expected, &reported]));
+    }
+    VersionRunCheck { runs: true, reported_version: Some(reported), error: None }
 }
 
 #[tauri::command]
-async fn switch_version(version: String) -> Result<bool, String> {
-    let output = create_silent_command("nvm")
-        .args(["use", &version])
-        .output()
-        .map_err(|e| e.to_string())?;
+async fn test_version_runs(version: String) -> Result<VersionRunCheck, String> {
+    let config = internal_get_config().await?;
+    let dir_name = if version.starts_with('v') { version.clone() } else { format!("v{}", version) };
+    let node_exe = PathBuf::from(&config.nvm_path).join(&dir_name).join("node.exe");
+    tauri::async_runtime::spawn_blocking(move || check_node_runs(&node_exe, &version))
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    if output.status.success() {
-        // 切换成功后自动应用 npm registry
-        let _ = apply_npm_registry().await;
-        Ok(true)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionBinary {
+    pub name: String,
+    pub kind: String, // "exe" / "cmd" / "ps1"
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionBinaries {
+    pub version: String,
+    pub binaries: Vec<VersionBinary>,
+    // Node 14.19 / 16.9 起自带 corepack，可用于管理 yarn / pnpm
+    #[serde(rename = "hasCorepack")]
+    pub has_corepack: bool,
+}
+
+// 列出版本根目录下可执行的文件（node.exe、npm.cmd、npx.ps1 等），展示该版本提供哪些命令
+#[tauri::command]
+async fn list_version_binaries(version: String) -> Result<VersionBinaries, String> {
+    let config = internal_get_config().await?;
+    let dir_name = if version.starts_with('v') { version.clone() } else { format!("v{}", version) };
+    let dir = PathBuf::from(&config.nvm_path).join(&dir_name);
+    let entries = fs::read_dir(&dir).map_err(|e| tr_args("error.readVersionsDir", &[&e]))?;
+
+    let mut binaries: Vec<VersionBinary> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let kind = path.extension()?.to_string_lossy().to_ascii_lowercase();
+            if !["exe", "cmd", "ps1"].contains(&kind.as_str()) || !path.is_file() {
+                return None;
+            }
+            Some(VersionBinary {
+                name: entry.file_name().to_string_lossy().to_string(),
+                kind,
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            })
+        })
+        .collect();
+    binaries.sort_by_key(|b| b.name.to_ascii_lowercase());
+
+    let has_corepack = dir.join("node_modules").join("corepack").is_dir()
+        || binaries.iter().any(|b| b.name.to_ascii_lowercase().starts_with("corepack."));
+    Ok(VersionBinaries { version: dir_name, binaries, has_corepack })
+}
+
+// 常见特性的最低 Node 版本。同一特性可能被回移到旧的主版本线，因此按主版本分别给出门槛：
+// 同主版本时需不低于该门槛，更高的主版本一律支持
+const NODE_FEATURES: &[(&str, &[&str])] = &[
+    ("hasCorepack", &["14.19.0", "16.9.0"]),
+    ("esmStable", &["12.22.0", "14.17.0", "15.3.0"]),
+    ("hasGlobalFetch", &["18.0.0"]),
+    ("hasTestRunner", &["16.17.0", "18.0.0"]),
+    ("hasWatchMode", &["16.19.0", "18.11.0"]),
+    ("hasPermissionModel", &["20.0.0"]),
+    ("hasRequireEsm", &["20.19.0", "22.12.0"]),
+    ("hasTypeStripping", &["22.18.0", "23.6.0"]),
+];
+
+// 以 npm 版本判断的特性
+const NPM_FEATURES: &[(&str, &str)] = &[("supportsWorkspaces", "7.0.0"), ("supportsOverrides", "8.3.0")];
+
+fn meets_feature_threshold(version: &str, thresholds: &[&str]) -> bool {
+    let major = |v: &str| v.trim_start_matches('v').split('.').next().and_then(|m| m.parse::<u64>().ok()).unwrap_or(0);
+    let ge = |t: &str| compare_semver(version, t) != std::cmp::Ordering::Less;
+    thresholds.iter().any(|t| major(t) == major(version) && ge(t))
+        || thresholds.iter().max_by(|a, b| compare_semver(a, b)).map(|t| major(version) > major(t)).unwrap_or(false)
+}
+
+// 已安装时读取版本目录中 npm 的 package.json，否则使用 index.json 中记录的自带 npm 版本
+async fn bundled_npm_version(version: &str) -> Option<String> {
+    if let Ok(config) = internal_get_config().await {
+        let manifest = PathBuf::from(&config.nvm_path)
+            .join(format!("v{}", version))
+            .join("node_modules")
+            .join("npm")
+            .join("package.json");
+        if let Some(v) = fs::read_to_string(manifest)
+            .ok()
+            .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+            .and_then(|m| m["version"].as_str().map(|s| s.to_string()))
+        {
+            return Some(v);
+        }
+    }
+    get_version_meta(version.to_string()).await.ok().and_then(|meta| meta.npm)
+}
+
+// 根据版本号和自带的 npm 判断常见特性是否可用，npm 版本未知时相关特性为 false
+#[tauri::command]
+async fn version_capabilities(version: String) -> Result<serde_json::Value, String> {
+    let version = version.trim().trim_start_matches('v').to_string();
+    if version.split('.').next().and_then(|m| m.parse::<u64>().ok()).is_none() {
+        return Err(tr_args("error.invalidVersion", &[&version]));
+    }
+    let npm_version = bundled_npm_version(&version).await;
+
+    let mut result = serde_json::Map::new();
+    result.insert("version".to_string(), serde_json::json!(version));
+    result.insert("npmVersion".to_string(), serde_json::json!(npm_version));
+    for (feature, thresholds) in NODE_FEATURES {
+        result.insert(feature.to_string(), serde_json::json!(meets_feature_threshold(&version, thresholds)));
+    }
+    for (feature, min_npm) in NPM_FEATURES {
+        let supported = npm_version.as_deref().map(|npm| compare_semver(npm, min_npm) != std::cmp::Ordering::Less).unwrap_or(false);
+        result.insert(feature.to_string(), serde_json::json!(supported));
+    }
+    Ok(serde_json::Value::Object(result))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CorepackStatus {
+    #[serde(rename = "nodeVersion")]
+    pub node_version: Option<String>,
+    // 当前版本是否自带 corepack
+    pub available: bool,
+    // yarn / pnpm 的 shim 是否由 corepack 生成
+    #[serde(rename = "yarnEnabled")]
+    pub yarn_enabled: bool,
+    #[serde(rename = "pnpmEnabled")]
+    pub pnpm_enabled: bool,
+    // corepack 默认提供的版本（来自其 config.json）
+    #[serde(rename = "yarnVersion")]
+    pub yarn_version: Option<String>,
+    #[serde(rename = "pnpmVersion")]
+    pub pnpm_version: Option<String>,
+}
+
+// corepack 生成的 shim 中会引用 corepack 自身的路径
+fn is_corepack_shim(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains("corepack"))
+        .unwrap_or(false)
+}
+
+// 读取 corepack 的 config.json 中各包管理器的默认版本，去掉 "+sha..." 校验部分
+fn corepack_default_version(node_dir: &Path, manager: &str) -> Option<String> {
+    let content = fs::read_to_string(node_dir.join("node_modules").join("corepack").join("config.json")).ok()?;
+    let config: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let value = config["definitions"][manager]["default"].as_str()?;
+    Some(value.split('+').next().unwrap_or(value).to_string())
+}
+
+fn inspect_corepack(node_dir: &Path, node_version: Option<String>) -> CorepackStatus {
+    let available = node_dir.join("corepack.cmd").is_file();
+    CorepackStatus {
+        node_version,
+        available,
+        yarn_enabled: available && is_corepack_shim(&node_dir.join("yarn.cmd")),
+        pnpm_enabled: available && is_corepack_shim(&node_dir.join("pnpm.cmd")),
+        yarn_version: corepack_default_version(node_dir, "yarn"),
+        pnpm_version: corepack_default_version(node_dir, "pnpm"),
+    }
+}
+
+#[tauri::command]
+async fn corepack_status() -> Result<CorepackStatus, String> {
+    let config = internal_get_config().await?;
+    let active = get_current_node_version(&config.nvm_symlink);
+    Ok(inspect_corepack(Path::new(&config.nvm_symlink), active))
+}
+
+// 对当前使用的版本执行 corepack enable / disable，shim 写入该版本目录（即 NVM_SYMLINK）
+async fn run_corepack(action: &str) -> Result<CorepackStatus, String> {
+    let config = internal_get_config().await?;
+    let node_dir = PathBuf::from(&config.nvm_symlink);
+    let active = get_current_node_version(&config.nvm_symlink).ok_or_else(|| tr("error.noActiveVersion"))?;
+    let corepack = node_dir.join("corepack.cmd");
+    if !corepack.is_file() {
+        return Err(tr_args("error.corepackUnavailable", &[&active]));
+    }
+
+    let output = create_silent_command(&corepack.to_string_lossy())
+        .arg(action)
+        .current_dir(&node_dir)
+        .output()
+        .map_err(|e| tr_args("error.runCorepack", &[&e]))?;
+    if !output.status.success() {
+        return Err(tr_args("error.corepackFailed", &[&action, &String::from_utf8_lossy(&output.stderr).trim()]));
+    }
+    Ok(inspect_corepack(&node_dir, Some(active)))
+}
+
+#[tauri::command]
+async fn corepack_enable() -> Result<CorepackStatus, String> {
+    run_corepack("enable").await
+}
+
+#[tauri::command]
+async fn corepack_disable() -> Result<CorepackStatus, String> {
+    run_corepack("disable").await
+}
+
+// 流式扫描：先为每个版本发送 version:found（仅名称/路径/是否激活），
+// 再逐个统计大小和日期后发送 version:detail，最终返回完整列表
+#[tauri::command]
+async fn stream_installed_versions(window: WebviewWindow) -> Result<Vec<NodeVersion>, String> {
+    let config = internal_get_config().await?;
+    let nvm_path = PathBuf::from(&config.nvm_path);
+    if !nvm_path.exists() {
+        return Err(tr("error.nvmRootMissing"));
+    }
+
+    let current_node = get_current_node_version(&config.nvm_symlink);
+    let mut names = list_installed_version_names(&nvm_path);
+    names.sort_by(|a, b| compare_semver(b, a));
+
+    for version in &names {
+        let _ = window.emit("version:found", serde_json::json!({
+            "version": version,
+            "path": nvm_path.join(format!("v{}", version)).to_string_lossy(),
+            "isActive": current_node.as_ref() == Some(version),
+            "isDefault": current_node.as_ref() == Some(version)
+        }));
+    }
+
+    let mut versions = Vec::new();
+    for version in names {
+        let path = nvm_path.join(format!("v{}", version));
+        let is_active = current_node.as_ref() == Some(&version);
+        let item = tauri::async_runtime::spawn_blocking(move || build_node_version(&path, version, is_active))
+            .await
+            .map_err(|e| e.to_string())?;
+        let _ = window.emit("version:detail", &item);
+        versions.push(item);
+    }
+    Ok(versions)
+}
+
+#[tauri::command]
+async fn get_version_size(path: String) -> Result<u64, String> {
+    let p = Path::new(&path);
+    if p.exists() {
+        Ok(get_dir_size(p))
+    } else {
+        Err(tr("error.pathMissing"))
+    }
+}
+
+#[tauri::command]
+async fn get_active_version() -> Result<Option<String>, String> {
+    let config = internal_get_config().await?;
+    Ok(get_current_node_version(&config.nvm_symlink))
+}
+
+// 默认版本：新打开的终端所使用的版本。
+// nvm-windows 没有 Unix nvm 的 alias default，所有终端共用 NVM_SYMLINK 指向的版本，
+// 因此设置默认版本就是切换符号链接，与 switch_version 效果相同，且重启后依然保留。
+// 单独提供这组接口是为了与“当前版本”在概念上区分，日后支持按终端切换时只需修改这里。
+#[tauri::command]
+async fn get_default_version() -> Result<Option<String>, String> {
+    let config = internal_get_config().await?;
+    Ok(get_current_node_version(&config.nvm_symlink))
+}
+
+#[tauri::command]
+async fn set_default_version<R: Runtime>(app: AppHandle<R>, version: String) -> Result<bool, String> {
+    switch_version(app, version).await
+}
+
+// 自动应用 npm registry 配置
+async fn apply_npm_registry() -> Result<(), String> {
+    let config = internal_get_config().await?;
+    
+    // 使用 get_registry_for_npm 获取正确的 registry URL
+    let registry_url = get_registry_for_npm(&config.npm_mirror)
+        .unwrap_or_else(|| "https://registry.npmjs.org/".to_string());
+    
+    // 执行 npm config set registry
+    let output = create_silent_command("npm.cmd")
+        .args(["config", "set", "registry", &registry_url])
+        .output();
+    
+    if let Err(e) = output {
+        // 忽略错误，因为可能在没有 node 时执行
+        eprintln!("Warning: Failed to set npm registry: {}", e);
+    }
+    
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegistryConsistency {
+    pub configured: String,
+    pub live: Option<String>,
+    pub mismatch: bool,
+}
+
+// .npmrc、环境变量或其他工具都可能改掉 npm 的 registry，这里对比应用配置与 npm 实际使用的值
+#[tauri::command]
+async fn check_registry_consistency() -> Result<RegistryConsistency, String> {
+    let config = internal_get_config().await?;
+    let configured = get_registry_for_npm(&config.npm_mirror)
+        .unwrap_or_else(|| "https://registry.npmjs.org/".to_string());
+
+    let output = create_silent_command("npm.cmd")
+        .args(["config", "get", "registry"])
+        .output()
+        .map_err(|e| tr_args("error.runNpm", &[&e]))?;
+    let live = if output.status.success() {
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if value.is_empty() || value == "undefined" { None } else { Some(value) }
+    } else {
+        None
+    };
+
+    let normalize = |url: &str| url.trim().trim_end_matches('/').to_lowercase();
+    let mismatch = live.as_deref().map(|l| normalize(l) != normalize(&configured)).unwrap_or(true);
+    Ok(RegistryConsistency { configured, live, mismatch })
+}
+
+#[tauri::command]
+async fn reconcile_registry() -> Result<RegistryConsistency, String> {
+    apply_npm_registry().await?;
+    check_registry_consistency().await
+}
+
+#[tauri::command]
+async fn switch_version<R: Runtime>(app: AppHandle<R>, version: String) -> Result<bool, String> {
+    let config = internal_get_config().await.ok();
+    let previous = config.as_ref().and_then(|c| get_current_node_version(&c.nvm_symlink));
+    // latest-installed 表示本机已安装的最新版本（不同于可下载的 latest）
+    let version = if version == LATEST_INSTALLED {
+        let installed = config
+            .as_ref()
+            .map(|c| list_installed_version_names(Path::new(&c.nvm_path)))
+            .unwrap_or_default();
+        resolve_installed_version(&version, &installed).ok_or_else(|| tr("error.noInstalledVersions"))?
+    } else {
+        version
+    };
+    let output = create_silent_command("nvm")
+        .args(["use", &version])
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        // 托盘和界面发起的切换都记录到历史，供托盘的“上一个版本”使用
+        let _ = record_switch_history(previous, &version);
+        // 切换成功后自动应用 npm registry
+        let _ = apply_npm_registry().await;
+        // prefix 指向旧位置时全局包会装到别处，提醒前端修复
+        if let Ok(status) = verify_npm_prefix().await {
+            if !status.ok {
+                let _ = app.emit("npm:prefix-mismatch", &status);
+            }
+        }
+        Ok(true)
     } else {
         Err(String::from_utf8_lossy(&output.stderr).to_string())
     }
 }
 
+// 代理列表 (加速 GitHub 下载)
+const GITHUB_PROXY_PREFIXES: [&str; 4] = [
+    "https://ghp.ci/",
+    "https://gh-proxy.com/",
+    "https://mirror.ghproxy.com/",
+    "https://ghproxy.net/",
+];
+
+// Node.js 仓库中各版本对应的更新日志文件名
+fn changelog_file_for(version: &str) -> Option<String> {
+    let mut parts = version.trim_start_matches('v').split('.');
+    let major: u64 = parts.next()?.parse().ok()?;
+    let minor: u64 = parts.next()?.parse().ok()?;
+    Some(match (major, minor) {
+        (0, 10) => "CHANGELOG_V010.md".to_string(),
+        (0, 12) => "CHANGELOG_V012.md".to_string(),
+        (0, _) => "CHANGELOG_ARCHIVE.md".to_string(),
+        (1..=3, _) => "CHANGELOG_IOJS.md".to_string(),
+        _ => format!("CHANGELOG_V{}.md", major),
+    })
+}
+
+// 截取指定版本的条目：从 <a id="x.y.z"></a> 锚点（或 "Version x.y.z" 标题）到下一个锚点为止
+fn extract_changelog_entry(content: &str, version: &str) -> Option<String> {
+    let version = version.trim_start_matches('v');
+    let anchor = format!("<a id=\"{}\"></a>", version);
+    let start = content.find(&anchor).map(|i| i + anchor.len()).or_else(|| {
+        let heading = format!("Version {}", version);
+        content.lines().find(|l| l.starts_with("## ") && l.contains(&heading)).and_then(|l| content.find(l))
+    })?;
+    let rest = &content[start..];
+    let end = rest.find("<a id=\"").unwrap_or(rest.len());
+    let entry = rest[..end].trim();
+    if entry.is_empty() { None } else { Some(entry.to_string()) }
+}
+
+// 返回 None 表示暂无该版本的更新日志（网络不可用或文件中找不到条目）
+#[tauri::command]
+async fn get_version_changelog(version: String) -> Result<Option<String>, String> {
+    let version = if version.starts_with('v') { version } else { format!("v{}", version) };
+    let cache_key = format!("changelog_{}", version);
+    // 已发布版本的更新日志不会再变化，缓存 30 天
+    if let Some(cached) = get_from_cache_with_ttl(&cache_key, 30 * 86400).await {
+        if let Some(entry) = cached.as_str() {
+            return Ok(Some(entry.to_string()));
+        }
+    }
+
+    let file = match changelog_file_for(&version) {
+        Some(f) => f,
+        None => return Ok(None),
+    };
+    let raw_url = format!("https://raw.githubusercontent.com/nodejs/node/main/doc/changelogs/{}", file);
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // 先直连，失败再依次尝试加速代理
+    let urls = std::iter::once(raw_url.clone()).chain(GITHUB_PROXY_PREFIXES.iter().map(|p| format!("{}{}", p, raw_url)));
+    for url in urls {
+        let content = match client.get(&url).header("User-Agent", "Mozilla/5.0").send().await {
+            Ok(res) if res.status().is_success() => match res.text().await {
+                Ok(text) => text,
+                Err(_) => continue,
+            },
+            _ => continue,
+        };
+        // 拿到了文件但没有对应条目时无需再试其他代理
+        let entry = extract_changelog_entry(&content, &version);
+        if let Some(ref entry) = entry {
+            save_to_cache(&cache_key, serde_json::json!(entry)).await;
+        }
+        return Ok(entry);
+    }
+    Ok(None)
+}
+
 #[tauri::command]
-async fn get_available_versions() -> Result<Vec<AvailableVersion>, String> {
-    let cache_key = "node_available_versions";
+async fn get_available_versions(include_prerelease: Option<bool>) -> Result<Vec<AvailableVersion>, String> {
+    fetch_available_versions(include_prerelease.unwrap_or(false)).await
+}
+
+// 可用版本列表的缓存键：含预发布版本的完整列表和只含正式版的列表
+const AVAILABLE_VERSIONS_CACHE_KEYS: [&str; 2] = ["node_available_versions_all", "node_available_versions"];
+
+// 默认过滤掉 rc / nightly 等预发布版本，让版本列表保持整洁
+async fn fetch_available_versions(include_prerelease: bool) -> Result<Vec<AvailableVersion>, String> {
+    let cache_key = if include_prerelease { "node_available_versions_all" } else { "node_available_versions" };
     if let Some(cached) = get_from_cache(cache_key).await {
         if let Ok(versions) = serde_json::from_value::<Vec<AvailableVersion>>(cached) {
             return Ok(versions);
         }
     }
+    download_available_versions(include_prerelease).await
+}
 
+// 跳过缓存，从当前镜像重新下载 index.json 并更新缓存
+async fn download_available_versions(include_prerelease: bool) -> Result<Vec<AvailableVersion>, String> {
+    let cache_key = if include_prerelease { "node_available_versions_all" } else { "node_available_versions" };
     let config = internal_get_config().await?;
     let url = format!("{}index.json", config.node_mirror);
     let client = reqwest::Client::new();
@@ -776,10 +2751,13 @@ async fn get_available_versions() -> Result<Vec<AvailableVersion>, String> {
         .send()
         .await
         .map_err(|e| e.to_string())?;
-    let versions = response
+    let mut versions = response
         .json::<Vec<AvailableVersion>>()
         .await
         .map_err(|e| e.to_string())?;
+    if !include_prerelease {
+        versions.retain(|v| !is_prerelease_version(&v.version));
+    }
 
     if let Ok(json_val) = serde_json::to_value(&versions) {
         save_to_cache(cache_key, json_val).await;
@@ -788,80 +2766,437 @@ async fn get_available_versions() -> Result<Vec<AvailableVersion>, String> {
     Ok(versions)
 }
 
+// 查询单个版本的信息（npm 版本、发布日期、LTS 代号、安装包列表），供安装确认对话框使用。
+// 优先从已缓存的版本列表中查找，缓存为空时才下载完整的 index.json（随后会被缓存）
 #[tauri::command]
-async fn install_version(
-    window: WebviewWindow,
-    state: tauri::State<'_, DownloadState>,
-    version: String,
-) -> Result<bool, String> {
+async fn get_version_meta(version: String) -> Result<AvailableVersion, String> {
     let version = if version.starts_with('v') { version } else { format!("v{}", version) };
-    
-    // 检查是否已经在下载
-    {
-        let tasks = state.tasks.lock().unwrap();
-        if tasks.contains_key(&version) {
-            return Err(format!("版本 {} 正在下载中", version));
+    for cache_key in AVAILABLE_VERSIONS_CACHE_KEYS {
+        if let Some(cached) = get_from_cache(cache_key).await {
+            if let Ok(versions) = serde_json::from_value::<Vec<AvailableVersion>>(cached) {
+                if let Some(entry) = versions.into_iter().find(|v| v.version == version) {
+                    return Ok(entry);
+                }
+            }
         }
     }
+    fetch_available_versions(true)
+        .await?
+        .into_iter()
+        .find(|v| v.version == version)
+        .ok_or_else(|| tr_args("error.versionNotFound", &[&version]))
+}
 
-    let (cancel_tx, _) = broadcast::channel(1);
-    let pause_flag = Arc::new(AtomicBool::new(false));
-    
-    {
-        let mut tasks = state.tasks.lock().unwrap();
-        tasks.insert(version.clone(), TaskInfo {
-            cancel_tx: cancel_tx.clone(),
-            pause_flag: pause_flag.clone(),
-            pid: Arc::new(Mutex::new(None)),
-        });
+// 下载前确认版本和对应架构的 zip 包在镜像的 index.json 中存在
+// 版本列表获取失败（例如离线）时不拦截，由下载本身报告错误。
+// 缓存的列表可能早于新版本发布，未找到时先从镜像重新获取一次再判断
+async fn ensure_version_on_mirror(version: &str, arch: &str) -> Result<(), String> {
+    let file_key = format!("win-{}-zip", arch);
+    let has_package = |versions: &[AvailableVersion]| -> Result<(), String> {
+        let entry = versions
+            .iter()
+            .find(|v| v.version == version)
+            .ok_or_else(|| tr_args("error.versionNotFoundCheck", &[&version]))?;
+        if !entry.files.iter().any(|f| f == &file_key) {
+            return Err(tr_args("error.archUnavailableOnMirror", &[&version, &arch]));
+        }
+        Ok(())
+    };
+    let cached = match fetch_available_versions(true).await {
+        Ok(v) => v,
+        Err(_) => return Ok(()),
+    };
+    if has_package(&cached).is_ok() {
+        return Ok(());
     }
+    match download_available_versions(true).await {
+        Ok(fresh) => has_package(&fresh),
+        Err(_) => has_package(&cached),
+    }
+}
 
-    let app_handle = window.app_handle().clone();
-    let version_clone = version.clone();
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchSupport {
+    pub arch: String,
+    pub supported: bool,
+    #[serde(rename = "supportedArches")]
+    pub supported_arches: Vec<String>,
+    #[serde(rename = "probedVersion")]
+    pub probed_version: String,
+}
 
-    tauri::async_runtime::spawn(async move {
-        let result = perform_download(window.clone(), version_clone.clone(), pause_flag, cancel_tx.subscribe()).await;
-        
-        // 清理任务
-        {
-            let state = app_handle.state::<DownloadState>();
-            let mut tasks = state.tasks.lock().unwrap();
-            tasks.remove(&version_clone);
-        }
+// 保存架构前检查当前镜像能否提供该架构的安装包：
+// 以最新的正式版为样本，从 files 推断镜像支持的架构，并对所选架构实际请求一次安装包
+#[tauri::command]
+async fn validate_arch_on_mirror(arch: String) -> Result<ArchSupport, String> {
+    let config = internal_get_config().await?;
+    let versions = fetch_available_versions(false).await?;
+    let latest = versions.first().ok_or_else(|| tr("error.emptyVersionList"))?;
 
-        match result {
-            Ok(_) => {
-                let _ = window.emit("install:progress", serde_json::json!({ 
-                    "version": version_clone, 
-                    "progress": 100, 
-                    "status": "安装完成",
-                    "finished": true 
-                }));
-            }
-            Err(e) => {
-                let _ = window.emit("install:progress", serde_json::json!({ 
-                    "version": version_clone, 
-                    "progress": 0, 
-                    "status": format!("错误: {}", e),
-                    "error": e 
-                }));
+    let supported_arches: Vec<String> = ["x64", "x86", "arm64"]
+        .iter()
+        .filter(|a| latest.files.iter().any(|f| *f == format!("win-{}-zip", a)))
+        .map(|a| a.to_string())
+        .collect();
+
+    let dist_arch = parse_dist_arch(&arch)?;
+    let mut supported = supported_arches.iter().any(|a| a == dist_arch);
+    if supported {
+        // index.json 与实际文件不同步的镜像并不少见，HEAD 明确返回失败时以其为准
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let url = build_download_url(&config.node_mirror, &latest.version, dist_arch);
+        if let Ok(res) = client.head(&url).header("User-Agent", "Mozilla/5.0").send().await {
+            if res.status().is_client_error() {
+                supported = false;
             }
         }
-    });
+    }
 
-    Ok(true)
+    Ok(ArchSupport {
+        arch,
+        supported,
+        supported_arches,
+        probed_version: latest.version.clone(),
+    })
 }
 
+// 安装只会解压到 NVM 目录，不会修改符号链接，当前使用的版本保持不变；
+// 传入 activate = true 时在安装成功后再切换到该版本
 #[tauri::command]
-async fn pause_download(window: WebviewWindow, state: tauri::State<'_, DownloadState>, version: String) -> Result<bool, String> {
-    let tasks = state.tasks.lock().unwrap();
-    // 找到匹配的任务标识
-    let matched_id = if tasks.contains_key(&version) {
-        Some(version.clone())
-    } else if !version.starts_with('v') {
-        let v_prefixed = format!("v{}", version);
-        if tasks.contains_key(&v_prefixed) {
-            Some(v_prefixed)
+async fn install_version(
+    window: WebviewWindow,
+    version: String,
+    arch: Option<String>,
+    activate: Option<bool>,
+    mirror_override: Option<String>,
+) -> Result<bool, String> {
+    let mirror = match mirror_override {
+        Some(ref value) if !value.trim().is_empty() => Some(resolve_mirror_override(value)?),
+        _ => None,
+    };
+    start_install_task(&window, version, arch, activate.unwrap_or(false), mirror).await?;
+    Ok(true)
+}
+
+// 单次安装临时使用的镜像：可以是内置镜像 id，也可以是完整的镜像地址，
+// 只用于本次下载，不修改配置中的 node_mirror
+fn resolve_mirror_override(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if let Some(preset) = get_all_mirror_presets().into_iter().find(|p| p.id == value) {
+        return Ok(preset.node_url);
+    }
+    let url = normalize_mirror_url(value)?;
+    if url.is_empty() {
+        return Err(tr("error.emptyMirror"));
+    }
+    Ok(url)
+}
+
+// 创建目录联接（mklink /J），不需要管理员权限或开发者模式
+fn create_junction(link: &Path, target: &Path) -> Result<(), String> {
+    let output = create_silent_command("cmd")
+        .args(["/C", "mklink", "/J"])
+        .arg(link)
+        .arg(target)
+        .output()
+        .map_err(|e| tr_args("error.createJunction", &[&e]))?;
+    if !output.status.success() {
+        // mklink 把错误写到 stderr，为空时再退回 stdout
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let message = if stderr.is_empty() { String::from_utf8_lossy(&output.stdout).trim().to_string() } else { stderr };
+        return Err(tr_args("error.createJunction", &[&message]));
+    }
+    Ok(())
+}
+
+// 版本目录是目录联接时返回其真实位置
+fn resolve_version_junction(path: &Path) -> Option<PathBuf> {
+    let meta = fs::symlink_metadata(path).ok()?;
+    if meta.file_type().is_symlink() {
+        fs::canonicalize(path).ok()
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReparsePoint {
+    pub path: String,
+    pub target: Option<String>,
+    // 目标位于 nvm 根目录内，遍历时会重复统计甚至成环
+    #[serde(rename = "isLoop")]
+    pub is_loop: bool,
+    #[serde(rename = "isDangling")]
+    pub is_dangling: bool,
+}
+
+// 不是符号链接/目录联接时返回 None；root 需为 canonicalize 之后的路径
+fn inspect_reparse_point(path: &Path, root: &Path) -> Option<ReparsePoint> {
+    let meta = fs::symlink_metadata(path).ok()?;
+    if !meta.file_type().is_symlink() {
+        return None;
+    }
+    let target = fs::read_link(path).ok().map(|t| t.to_string_lossy().to_string());
+    let resolved = fs::canonicalize(path).ok();
+    Some(ReparsePoint {
+        path: path.to_string_lossy().to_string(),
+        target,
+        is_loop: resolved
+            .as_ref()
+            .map(|r| is_same_or_nested_path(&r.to_string_lossy(), &root.to_string_lossy()))
+            .unwrap_or(false),
+        is_dangling: resolved.is_none(),
+    })
+}
+
+// 版本扫描和大小统计用：指回 nvm 根目录或目标不存在的重解析点应跳过，
+// 指向其他磁盘的目录联接（install_version_at 创建的）是正常的版本
+fn is_unsafe_reparse_point(path: &Path, root: &Path) -> bool {
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    inspect_reparse_point(path, &root).map(|p| p.is_loop || p.is_dangling).unwrap_or(false)
+}
+
+// 递归查找，不进入重解析点内部
+fn collect_reparse_points(dir: &Path, root: &Path, found: &mut Vec<ReparsePoint>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Some(point) = inspect_reparse_point(&path, root) {
+                found.push(point);
+            } else if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                collect_reparse_points(&path, root, found);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+async fn scan_reparse_points(path: String) -> Result<Vec<ReparsePoint>, String> {
+    let root = fs::canonicalize(&path).map_err(|e| tr_args("error.dirMissing", &[&e]))?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut found = Vec::new();
+        collect_reparse_points(&root, &root, &mut found);
+        found
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// 把版本安装到其他磁盘：真实文件放在 target_dir\vX.Y.Z，再在 NVM 目录下创建同名目录联接，
+// nvm 仍按原路径识别该版本。注意：
+// - 目录联接只支持本机的 NTFS 卷，不能指向网络共享；
+// - 目标磁盘不可用（例如移动硬盘未连接）时该版本会显示为损坏；
+// - 卸载时会一并删除联接指向的真实目录
+#[tauri::command]
+async fn install_version_at(
+    window: WebviewWindow,
+    version: String,
+    target_dir: String,
+    arch: Option<String>,
+) -> Result<bool, String> {
+    let version = if version.starts_with('v') { version } else { format!("v{}", version) };
+    let config = internal_get_config().await?;
+    let target_root = PathBuf::from(target_dir.trim());
+    if !target_root.is_absolute() {
+        return Err(tr_args("error.installDirNotAbsolute", &[&target_dir]));
+    }
+    if is_same_or_nested_path(&target_root.to_string_lossy(), &config.nvm_path) {
+        return Err(tr("error.installDirInsideNvm"));
+    }
+
+    let link = PathBuf::from(&config.nvm_path).join(&version);
+    if fs::symlink_metadata(&link).is_ok() {
+        return Err(tr_args("error.versionExists", &[&version]));
+    }
+    let real_dir = target_root.join(&version);
+    if real_dir.exists() && fs::read_dir(&real_dir).map(|mut d| d.next().is_some()).unwrap_or(false) {
+        return Err(tr_args("error.targetDirNotEmpty", &[&real_dir.display()]));
+    }
+    fs::create_dir_all(&real_dir).map_err(|e| tr_args("error.createDir", &[&e]))?;
+
+    // 先确认联接可用，再开始下载，下载和解压都会经由联接写入真实目录
+    let remove_link = |link: &Path, real_dir: &Path| {
+        let _ = fs::remove_dir(link);
+        let _ = cleanup_if_empty(real_dir);
+    };
+    if let Err(e) = create_junction(&link, &real_dir) {
+        let _ = cleanup_if_empty(&real_dir);
+        return Err(e);
+    }
+    let same_target = match (fs::canonicalize(&link), fs::canonicalize(&real_dir)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    };
+    if !same_target {
+        remove_link(&link, &real_dir);
+        return Err(tr("error.junctionMismatch"));
+    }
+
+    let handle = match start_install_task(&window, version.clone(), arch, false, None).await {
+        Ok(h) => h,
+        Err(e) => {
+            remove_link(&link, &real_dir);
+            return Err(e);
+        }
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let result = handle.await.unwrap_or_else(|e| Err(e.to_string()));
+        if result.is_err() {
+            // 失败时 perform_download 已清空版本目录，这里移除联接和空目录
+            if fs::read_dir(&real_dir).map(|mut d| d.next().is_none()).unwrap_or(true) {
+                remove_link(&link, &real_dir);
+            }
+            return;
+        }
+        let verified = link.join("node.exe").exists() && real_dir.join("node.exe").exists();
+        let _ = window.emit("install:junction", serde_json::json!({
+            "version": version,
+            "linkPath": link.to_string_lossy(),
+            "targetPath": real_dir.to_string_lossy(),
+            "verified": verified
+        }));
+    });
+
+    Ok(true)
+}
+
+// 安装前检查：架构是否有效、是否已安装（同时清理上次未完成的残留）以及镜像上是否有对应安装包
+async fn check_install_preconditions(version: &str, arch: Option<&str>, mirror_override: Option<&str>) -> Result<(), String> {
+    if let Some(value) = arch {
+        parse_dist_arch(value)?;
+    }
+    let config = internal_get_config().await?;
+    if prepare_install_dir(&PathBuf::from(&config.nvm_path).join(version))? == InstallDirState::Complete {
+        return Err(tr_args("error.versionInstalled", &[&version]));
+    }
+    let dist_arch = to_dist_arch(arch.unwrap_or(&config.arch));
+    if find_cached_archive(version, dist_arch).is_none() {
+        match mirror_override {
+            // 临时镜像的版本列表不在缓存中，直接确认安装包存在
+            Some(mirror) => {
+                let client = reqwest::Client::builder()
+                    .timeout(std::time::Duration::from_secs(5))
+                    .build()
+                    .map_err(|e| e.to_string())?;
+                head_download_size(&client, &build_download_url(mirror, version, dist_arch), version).await?;
+            }
+            None => ensure_version_on_mirror(version, dist_arch).await?,
+        }
+    }
+    Ok(())
+}
+
+// 校验并登记下载任务后在后台执行安装，返回的句柄可用于等待安装结果
+async fn start_install_task(
+    window: &WebviewWindow,
+    version: String,
+    arch: Option<String>,
+    activate: bool,
+    mirror_override: Option<String>,
+) -> Result<tauri::async_runtime::JoinHandle<Result<(), String>>, String> {
+    let version = if version.starts_with('v') { version } else { format!("v{}", version) };
+    let state = window.app_handle().state::<DownloadState>();
+
+    // 检查和登记在同一次加锁中完成：先占住任务位置再做后续检查，
+    // 避免重复点击或批量安装与手动安装同时通过检查，向同一个目录写入
+    let (cancel_tx, _) = broadcast::channel(1);
+    let cancel_rx = cancel_tx.subscribe();
+    let (done_tx, done_rx) = watch::channel(false);
+    let pause_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut tasks = state.tasks.lock().unwrap();
+        if tasks.contains_key(&version) {
+            return Err(tr_args("error.versionDownloading", &[&version]));
+        }
+        tasks.insert(version.clone(), TaskInfo {
+            cancel_tx: cancel_tx.clone(),
+            pause_flag: pause_flag.clone(),
+            pid: Arc::new(Mutex::new(None)),
+            done_rx,
+            progress: Some(Arc::new(AtomicU32::new(0))),
+        });
+    }
+
+    // 检查未通过时释放占用的任务位置
+    if let Err(e) = check_install_preconditions(&version, arch.as_deref(), mirror_override.as_deref()).await {
+        state.tasks.lock().unwrap().remove(&version);
+        let _ = done_tx.send(true);
+        return Err(e);
+    }
+
+    let window = window.clone();
+    let app_handle = window.app_handle().clone();
+    let version_clone = version.clone();
+    let _ = refresh_tray(app_handle.clone()).await;
+
+    Ok(tauri::async_runtime::spawn(async move {
+        let result = perform_download(window.clone(), version_clone.clone(), arch, mirror_override, pause_flag, cancel_rx).await;
+        
+        // 清理任务
+        {
+            let state = app_handle.state::<DownloadState>();
+            let mut tasks = state.tasks.lock().unwrap();
+            tasks.remove(&version_clone);
+        }
+        let _ = done_tx.send(true);
+        let _ = refresh_tray(app_handle.clone()).await;
+
+        match result {
+            Ok(ref check) => {
+                let mut activate_error = None;
+                if activate {
+                    if let Err(e) = switch_version(app_handle.clone(), version_clone.trim_start_matches('v').to_string()).await {
+                        activate_error = Some(e);
+                    }
+                }
+                // 完成事件中带上当前使用的版本，便于前端确认安装没有改变它
+                let active_version = match internal_get_config().await {
+                    Ok(config) => get_current_node_version(&config.nvm_symlink),
+                    Err(_) => None,
+                };
+                let _ = window.emit("install:progress", serde_json::json!({ 
+                    "version": version_clone, 
+                    "progress": 100, 
+                    "phase": "done",
+                    "phaseProgress": 100,
+                    "status": tr("install.done"),
+                    "code": "install.done",
+                    "finished": true,
+                    "activated": activate && activate_error.is_none(),
+                    "activeVersion": active_version,
+                    "activateError": activate_error,
+                    "verified": check.runs,
+                    "warning": check.error
+                }));
+            }
+            Err(ref e) => {
+                let _ = window.emit("install:progress", serde_json::json!({ 
+                    "version": version_clone, 
+                    "progress": 0, 
+                    "status": tr_with("install.error", e),
+                    "code": "install.error",
+                    "error": e 
+                }));
+            }
+        }
+        result.map(|_| ())
+    }))
+}
+
+#[tauri::command]
+async fn pause_download(window: WebviewWindow, state: tauri::State<'_, DownloadState>, version: String) -> Result<bool, String> {
+    let tasks = state.tasks.lock().unwrap();
+    // 找到匹配的任务标识
+    let matched_id = if tasks.contains_key(&version) {
+        Some(version.clone())
+    } else if !version.starts_with('v') {
+        let v_prefixed = format!("v{}", version);
+        if tasks.contains_key(&v_prefixed) {
+            Some(v_prefixed)
         } else {
             None
         }
@@ -881,14 +3216,15 @@ async fn pause_download(window: WebviewWindow, state: tauri::State<'_, DownloadS
             // 务必使用 Map 中的 id 发送事件，确保前端能匹配
             let _ = window.emit("install:progress", serde_json::json!({
                 "version": id,
-                "status": "已暂停",
+                "status": tr("install.paused"),
+                "code": "install.paused",
                 "isPaused": true
             }));
         }
         
         Ok(true)
     } else {
-        Err(format!("未找到任务: {}", version))
+        Err(tr_args("error.taskNotFound", &[&version]))
     }
 }
 
@@ -919,49 +3255,148 @@ async fn resume_download(window: WebviewWindow, state: tauri::State<'_, Download
 
             let _ = window.emit("install:progress", serde_json::json!({
                 "version": id,
-                "status": "正在安装...",
+                "status": tr("install.resumed"),
+                "code": "install.resumed",
                 "isPaused": false
             }));
         }
         
         Ok(true)
     } else {
-        Err(format!("未找到任务: {}", version))
+        Err(tr_args("error.taskNotFound", &[&version]))
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CancelResult {
+    pub stopped: bool,
+    #[serde(rename = "partRemoved")]
+    pub part_removed: bool,
+    pub state: String, // "cancelled" 或 "timeout"
+}
+
+// 发送取消信号后等待任务确认结束并清理完 .part 文件，超时则返回 timeout
 #[tauri::command]
-async fn cancel_download(state: tauri::State<'_, DownloadState>, version: String) -> Result<bool, String> {
-    let tasks = state.tasks.lock().unwrap();
-    let task = tasks.get(&version).or_else(|| {
-        if !version.starts_with('v') {
-            tasks.get(&format!("v{}", version))
+async fn cancel_download(state: tauri::State<'_, DownloadState>, version: String) -> Result<CancelResult, String> {
+    let (id, mut done_rx) = {
+        let tasks = state.tasks.lock().unwrap();
+        let id = if tasks.contains_key(&version) {
+            version.clone()
+        } else if !version.starts_with('v') && tasks.contains_key(&format!("v{}", version)) {
+            format!("v{}", version)
         } else {
-            None
+            return Err(tr_args("error.taskNotFound", &[&version]));
+        };
+        let task = tasks.get(&id).unwrap();
+        let _ = task.cancel_tx.send(());
+        (id, task.done_rx.clone())
+    };
+
+    // wait_for 在发送端被丢弃时返回 Err，同样说明任务已经结束
+    let stopped = tokio::time::timeout(std::time::Duration::from_secs(10), done_rx.wait_for(|done| *done))
+        .await
+        .is_ok();
+
+    let part_removed = match internal_get_config().await {
+        Ok(config) => !PathBuf::from(&config.nvm_path).join(&id).join("node.zip.part").exists(),
+        Err(_) => stopped,
+    };
+
+    Ok(CancelResult {
+        stopped,
+        part_removed,
+        state: if stopped { "cancelled".to_string() } else { "timeout".to_string() },
+    })
+}
+
+// 一次取消全部 Node 下载和全局包安装任务，各任务按单独取消时的流程清理 .part 文件和子进程。
+// 超时仍未结束的任务直接结束其进程树，返回发出取消信号的任务 id
+#[tauri::command]
+async fn cancel_all_tasks(window: WebviewWindow, state: tauri::State<'_, DownloadState>) -> Result<Vec<String>, String> {
+    let pending: Vec<(String, watch::Receiver<bool>, Arc<Mutex<Option<u32>>>)> = {
+        let tasks = state.tasks.lock().unwrap();
+        tasks
+            .iter()
+            .map(|(id, task)| {
+                let _ = task.cancel_tx.send(());
+                (id.clone(), task.done_rx.clone(), task.pid.clone())
+            })
+            .collect()
+    };
+
+    let waits = pending.into_iter().map(|(id, mut done_rx, pid)| async move {
+        let stopped = tokio::time::timeout(std::time::Duration::from_secs(10), done_rx.wait_for(|done| *done))
+            .await
+            .is_ok();
+        if !stopped {
+            if let Some(p) = *pid.lock().unwrap() {
+                #[cfg(windows)]
+                let _ = terminate_process_tree(p);
+                #[cfg(not(windows))]
+                let _ = p;
+            }
         }
+        (id, stopped)
     });
+    let results = join_all(waits).await;
 
-    if let Some(task) = task {
-        let _ = task.cancel_tx.send(());
-        Ok(true)
-    } else {
-        Err(format!("未找到任务: {}", version))
+    let cancelled: Vec<String> = results.iter().map(|(id, _)| id.clone()).collect();
+    let timed_out: Vec<&String> = results.iter().filter(|(_, stopped)| !stopped).map(|(id, _)| id).collect();
+    let _ = window.emit("tasks:cancelled", serde_json::json!({
+        "cancelled": cancelled,
+        "timedOut": timed_out
+    }));
+    Ok(cancelled)
+}
+
+// 安装各阶段在总进度中所占的区间：下载 0-90、解压 90-98、校验 98-100
+fn overall_install_progress(phase: &str, phase_progress: u32) -> u32 {
+    let (start, end) = match phase {
+        "download" => (0, 90),
+        "extract" => (90, 98),
+        "verify" => (98, 100),
+        _ => (100, 100),
+    };
+    start + (end - start) * phase_progress.min(100) / 100
+}
+
+const TRAY_PROGRESS_REFRESH_MS: u64 = 2000;
+static LAST_TRAY_PROGRESS_REFRESH: AtomicU64 = AtomicU64::new(0);
+
+// 记录安装任务的总进度，并节流刷新托盘菜单：下载时进度事件非常频繁，重建菜单的开销较大
+fn report_task_progress<R: Runtime>(app: &AppHandle<R>, version: &str, progress: u32) {
+    if let Some(current) = app.state::<DownloadState>().tasks.lock().unwrap().get(version).and_then(|t| t.progress.as_ref()) {
+        if current.swap(progress, Ordering::Relaxed) == progress {
+            return;
+        }
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+    let last = LAST_TRAY_PROGRESS_REFRESH.load(Ordering::Relaxed);
+    if now.saturating_sub(last) < TRAY_PROGRESS_REFRESH_MS
+        || LAST_TRAY_PROGRESS_REFRESH.compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed).is_err()
+    {
+        return;
     }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = refresh_tray(app).await;
+    });
 }
 
+// arch 为本次下载使用的架构，未指定时使用 config.arch，且不会修改配置
 async fn perform_download(
     window: WebviewWindow,
     version: String,
+    arch: Option<String>,
+    mirror_override: Option<String>,
     pause_flag: Arc<AtomicBool>,
     mut cancel_rx: broadcast::Receiver<()>,
-) -> Result<(), String> {
+) -> Result<VersionRunCheck, String> {
     let config = internal_get_config().await?;
-    let arch = if config.arch == "64" { "x64" } else { "x86" };
+    let arch = to_dist_arch(arch.as_deref().unwrap_or(&config.arch));
     
-    // 构造下载 URL
-    // 例如: https://npmmirror.com/mirrors/node/v20.0.0/node-v20.0.0-win-x64.zip
-    let base_mirror = config.node_mirror.trim_end_matches('/');
-    let url = format!("{}/{}/node-{}-win-{}.zip", base_mirror, version, version, arch);
+    let mirror = mirror_override.as_deref().unwrap_or(&config.node_mirror);
+    let url = build_download_url(mirror, &version, arch);
     
     // 目标路径
     let nvm_path = PathBuf::from(&config.nvm_path);
@@ -971,17 +3406,32 @@ async fn perform_download(
     let zip_path = install_dir.join("node.zip");
     let part_path = install_dir.join("node.zip.part");
 
-    // 开始下载 node.zip
-    let download_result = download_file_with_resume(
-        &window, 
-        &version, 
-        &url, 
-        &part_path, 
-        &zip_path, 
-        pause_flag, 
-        &mut cancel_rx,
-        "正在下载 Node.js 完整包"
-    ).await;
+    // 本地缓存中有可用的安装包时直接使用，不再下载
+    let cached_archive = find_cached_archive(&version, arch);
+    let download_result = if let Some(ref cached) = cached_archive {
+        report_task_progress(window.app_handle(), &version, overall_install_progress("download", 100));
+        let _ = window.emit("install:progress", serde_json::json!({ 
+            "version": version, 
+            "progress": overall_install_progress("download", 100), 
+            "phase": "download",
+            "phaseProgress": 100,
+            "status": tr("install.usingCache"),
+            "code": "install.usingCache"
+        }));
+        fs::copy(cached, &zip_path).map(|_| ()).map_err(|e| tr_args("error.copyCachedArchive", &[&e]))
+    } else {
+        // 开始下载 node.zip
+        download_file_with_resume(
+            &window, 
+            &version, 
+            &url, 
+            &part_path, 
+            &zip_path, 
+            pause_flag, 
+            &mut cancel_rx,
+            &tr("install.downloading")
+        ).await
+    };
 
     if let Err(e) = download_result {
         // 如果失败或取消，清理空目录
@@ -989,92 +3439,723 @@ async fn perform_download(
         return Err(e);
     }
 
-    // 解压 Zip
-    let _ = window.emit("install:progress", serde_json::json!({ 
-        "version": version, 
-        "progress": 99, 
-        "status": "正在解压并配置环境..."
-    }));
-
+    // 解压 Zip 是同步 IO（写入被占用时还会等待重试），放到阻塞线程中执行；
+    // 按已处理的条目数报告阶段内进度，只在百分比变化时发送
     let root_folder = format!("node-{}-win-{}", version, arch);
-    let extract_result = extract_and_flatten_zip(&zip_path, &install_dir, &root_folder);
+    let (extract_window, extract_version) = (window.clone(), version.clone());
+    let (extract_zip, extract_dir) = (zip_path.clone(), install_dir.clone());
+    let extract_result = tauri::async_runtime::spawn_blocking(move || {
+        let mut last_extract_progress = u32::MAX;
+        let mut on_extract = |phase_progress: u32| {
+            if phase_progress == last_extract_progress {
+                return;
+            }
+            last_extract_progress = phase_progress;
+            report_task_progress(extract_window.app_handle(), &extract_version, overall_install_progress("extract", phase_progress));
+            let _ = extract_window.emit("install:progress", serde_json::json!({ 
+                "version": extract_version, 
+                "progress": overall_install_progress("extract", phase_progress), 
+                "phase": "extract",
+                "phaseProgress": phase_progress,
+                "status": tr("install.extracting"),
+                "code": "install.extracting"
+            }));
+        };
+        extract_and_flatten_zip(&extract_zip, &extract_dir, &root_folder, &mut on_extract)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
     
     // 如果解压失败，清理
-    if let Err(e) = extract_result {
-        let _ = cleanup_if_empty(&install_dir);
-        return Err(e);
+    let failed_files = match extract_result {
+        Ok(failed) => failed,
+        Err(e) => {
+            let _ = cleanup_if_empty(&install_dir);
+            return Err(e);
+        }
+    };
+    // 非关键文件写入失败时版本仍可使用，提醒前端这是不完整的安装
+    if !failed_files.is_empty() {
+        let _ = window.emit("install:partial", serde_json::json!({
+            "version": version,
+            "failedFiles": failed_files
+        }));
+    }
+
+    // 开启 keep_archives 时把解压成功的新下载移入缓存，否则删除
+    if config.keep_archives && cached_archive.is_none() {
+        store_archive_in_cache(&zip_path, &version, arch);
+    } else {
+        let _ = fs::remove_file(zip_path);
     }
 
-    // 删除清理 zip 文件
-    let _ = fs::remove_file(zip_path);
+    // 记录本次安装使用的架构，供版本列表展示
+    write_version_meta(&install_dir, &VersionMeta {
+        arch: Some(arch.to_string()),
+        installed_at: Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+    });
+
+    // 文件齐全但无法运行时（杀毒软件隔离、解压不完整）在完成事件中提醒，而不是等到切换时才发现；
+    // 安装本身已经完成（跨架构安装在本机也无法运行），因此不作为失败处理
+    let _ = window.emit("install:progress", serde_json::json!({
+        "version": version,
+        "progress": overall_install_progress("verify", 0),
+        "phase": "verify",
+        "phaseProgress": 0,
+        "status": tr("install.verifying"),
+        "code": "install.verifying"
+    }));
+    let node_exe = install_dir.join("node.exe");
+    let expected = version.clone();
+    let check = tauri::async_runtime::spawn_blocking(move || check_node_runs(&node_exe, &expected))
+        .await
+        .map_err(|e| e.to_string())?;
     
     // 安装完成后自动应用 npm registry 配置
     let _ = apply_npm_registry().await;
     
-    Ok(())
+    Ok(check)
 }
 
-fn cleanup_if_empty(path: &Path) -> std::io::Result<()> {
-    if path.exists() && path.is_dir() {
-        let entries = fs::read_dir(path)?;
-        if entries.count() == 0 {
-            fs::remove_dir(path)?;
-        }
-    }
-    Ok(())
+// 构造下载 URL：内置镜像按各自的地址模板，自定义镜像使用官方目录结构
+// 例如: https://npmmirror.com/mirrors/node/v20.0.0/node-v20.0.0-win-x64.zip
+fn build_download_url(node_mirror: &str, version: &str, arch: &str) -> String {
+    download_url_template(node_mirror)
+        .replace("{version}", version)
+        .replace("{arch}", arch)
 }
-// TODO: 下载 npm (这是后续优化点，目前先完成核心逻辑)
-//为了保证兼容性，我们在下载完 node.zip 后，已经包含了 npm。
 
-fn extract_and_flatten_zip(zip_path: &Path, extract_to: &Path, _root_folder_name: &str) -> Result<(), String> {
-    let file = File::open(zip_path).map_err(|e| e.to_string())?;
-    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+fn download_url_template(node_mirror: &str) -> String {
+    let base_mirror = node_mirror.trim_end_matches('/');
+    get_all_mirror_presets()
+        .into_iter()
+        .find(|p| p.node_url.trim_end_matches('/').eq_ignore_ascii_case(base_mirror))
+        .map(|p| p.download_template)
+        .unwrap_or_else(|| format!("{}/{{version}}/node-{{version}}-win-{{arch}}.zip", base_mirror))
+}
 
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
-        let outpath = match file.enclosed_name() {
-            Some(path) => {
-                let components: Vec<_> = path.components().collect();
-                if components.len() <= 1 {
-                    continue;
-                }
-                let relative_path: PathBuf = components.iter().skip(1).collect();
-                extract_to.join(relative_path)
-            }
-            None => continue,
-        };
+// 镜像未返回 content-length 时按主版本号给出的大致体积
+fn estimate_download_size(version: &str) -> u64 {
+    let major: u64 = version.trim_start_matches('v').split('.').next().and_then(|m| m.parse().ok()).unwrap_or(0);
+    let mb = match major {
+        0..=8 => 15,
+        9..=12 => 22,
+        13..=16 => 26,
+        17..=20 => 30,
+        _ => 32,
+    };
+    mb * 1024 * 1024
+}
 
-        if file.name().ends_with('/') {
-            fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
-        } else {
-            if let Some(p) = outpath.parent() {
-                if !p.exists() {
-                    fs::create_dir_all(p).map_err(|e| e.to_string())?;
-                }
-            }
-            let mut outfile = fs::File::create(&outpath).map_err(|e| e.to_string())?;
-            std::io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
-        }
+// HEAD 请求读取安装包的实际大小；超时、网络问题或镜像未返回 content-length 时为 None，
+// 版本不存在或 HTTP 错误时返回 Err
+async fn head_download_size(client: &reqwest::Client, url: &str, version: &str) -> Result<Option<u64>, String> {
+    let response = match client.head(url).header("User-Agent", "Mozilla/5.0").send().await {
+        Ok(response) => response,
+        Err(_) => return Ok(None),
+    };
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(tr_args("error.versionNotFound", &[&version]));
     }
-    Ok(())
+    if !response.status().is_success() {
+        return Err(tr_args("error.downloadSizeHttp", &[&response.status()]));
+    }
+    // HEAD 响应没有 body，content_length() 会返回 0，需要直接读取响应头
+    Ok(response
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|len| *len > 0))
 }
 
-async fn download_file_with_resume(
-    window: &WebviewWindow,
-    version: &str,
-    url: &str,
-    part_path: &PathBuf,
-    target_path: &PathBuf,
-    pause_flag: Arc<AtomicBool>,
-    cancel_rx: &mut broadcast::Receiver<()>,
-    base_status: &str,
-) -> Result<(), String> {
-    let client = reqwest::Client::builder()
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+#[tauri::command]
+async fn get_download_size(version: String) -> Result<u64, String> {
+    let version = if version.starts_with('v') { version } else { format!("v{}", version) };
+    let config = internal_get_config().await?;
+    let url = build_download_url(&config.node_mirror, &version, to_dist_arch(&config.arch));
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
         .build()
         .map_err(|e| e.to_string())?;
+    // 超时或网络问题时给出估算值，不阻塞安装确认
+    let size = head_download_size(&client, &url, &version).await?;
+    Ok(size.unwrap_or_else(|| estimate_download_size(&version)))
+}
 
-    
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionDownloadSize {
+    pub version: String,
+    pub size: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchSizeEstimate {
+    // 只累加成功获取到大小的版本
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+    pub versions: Vec<VersionDownloadSize>,
+    // 无法获取大小的版本，界面可以按估算值提示
+    #[serde(rename = "unknownSize")]
+    pub unknown_size: Vec<String>,
+    #[serde(rename = "freeSpace")]
+    pub free_space: Option<u64>,
+    // 压缩包与解压后的文件会同时存在，按下载总量的 4 倍估算所需空间
+    #[serde(rename = "enoughSpace")]
+    pub enough_space: Option<bool>,
+}
+
+// 批量安装前并发查询每个版本安装包的大小
+#[tauri::command]
+async fn estimate_batch_size(versions: Vec<String>) -> Result<BatchSizeEstimate, String> {
+    let config = internal_get_config().await?;
+    let arch = to_dist_arch(&config.arch);
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let futures: Vec<_> = versions
+        .into_iter()
+        .map(|version| {
+            let version = if version.starts_with('v') { version } else { format!("v{}", version) };
+            let url = build_download_url(&config.node_mirror, &version, arch);
+            let client = client.clone();
+            async move {
+                let (size, error) = match head_download_size(&client, &url, &version).await {
+                    Ok(size) => (size, None),
+                    Err(e) => (None, Some(e)),
+                };
+                VersionDownloadSize { version, size, error }
+            }
+        })
+        .collect();
+    let versions = join_all(futures).await;
+
+    let total_bytes = versions.iter().filter_map(|v| v.size).sum::<u64>();
+    let unknown_size = versions.iter().filter(|v| v.size.is_none()).map(|v| v.version.clone()).collect();
+    let free_space = get_disk_free_space(Path::new(&config.nvm_path));
+    Ok(BatchSizeEstimate {
+        total_bytes,
+        versions,
+        unknown_size,
+        free_space,
+        enough_space: free_space.map(|free| free >= total_bytes.saturating_mul(4)),
+    })
+}
+
+// 将配置中的架构值（"64" / "32" / "arm64"）转换为 Node.js 发行包中的名称
+fn to_dist_arch(arch: &str) -> &str {
+    match arch {
+        "64" | "x64" => "x64",
+        "32" | "x86" => "x86",
+        "arm64" => "arm64",
+        _ => "x64",
+    }
+}
+
+// 校验调用方传入的架构（配置值或发行包名称均可），未知值直接报错，而不是像 to_dist_arch 一样按 x64 处理
+fn parse_dist_arch(arch: &str) -> Result<&'static str, String> {
+    match arch {
+        "64" | "x64" => Ok("x64"),
+        "32" | "x86" => Ok("x86"),
+        "arm64" => Ok("arm64"),
+        _ => Err(tr_args("error.unsupportedArch", &[&arch])),
+    }
+}
+
+const VERSION_META_FILE: &str = ".nvm-gui.json";
+
+// 安装时写入版本目录的元数据，nvm 本身会忽略该文件
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct VersionMeta {
+    pub arch: Option<String>,
+    #[serde(rename = "installedAt")]
+    pub installed_at: Option<String>,
+}
+
+fn read_version_meta(version_dir: &Path) -> Option<VersionMeta> {
+    let content = fs::read_to_string(version_dir.join(VERSION_META_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_version_meta(version_dir: &Path, meta: &VersionMeta) {
+    if let Ok(content) = serde_json::to_string_pretty(meta) {
+        let _ = fs::write(version_dir.join(VERSION_META_FILE), content);
+    }
+}
+
+// 从 PE 文件头读取目标机器类型
+fn parse_pe_machine(header: &[u8]) -> Option<&'static str> {
+    if header.len() < 0x40 || &header[0..2] != b"MZ" {
+        return None;
+    }
+    let pe_offset = u32::from_le_bytes(header[0x3C..0x40].try_into().ok()?) as usize;
+    let signature = header.get(pe_offset..pe_offset + 4)?;
+    if signature != b"PE\0\0" {
+        return None;
+    }
+    let machine = header.get(pe_offset + 4..pe_offset + 6)?;
+    match u16::from_le_bytes([machine[0], machine[1]]) {
+        0x8664 => Some("x64"),
+        0x014C => Some("x86"),
+        0xAA64 => Some("arm64"),
+        _ => None,
+    }
+}
+
+fn read_node_exe_arch(version_dir: &Path) -> Option<&'static str> {
+    use std::io::Read;
+    let mut file = File::open(version_dir.join("node.exe")).ok()?;
+    let mut header = vec![0u8; 4096];
+    let n = file.read(&mut header).ok()?;
+    header.truncate(n);
+    parse_pe_machine(&header)
+}
+
+// 获取已安装版本的架构：优先读取元数据，否则解析 node.exe 并写回元数据缓存（同一安装不会变化）
+fn detect_version_arch(version_dir: &Path) -> Option<String> {
+    let mut meta = read_version_meta(version_dir).unwrap_or_default();
+    if let Some(arch) = meta.arch {
+        return Some(arch);
+    }
+    let arch = read_node_exe_arch(version_dir)?.to_string();
+    meta.arch = Some(arch.clone());
+    write_version_meta(version_dir, &meta);
+    Some(arch)
+}
+
+// --- 安装包缓存 ---
+
+// 缓存总大小上限，超出后按最近使用时间淘汰
+const ARCHIVE_CACHE_MAX_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+fn get_archive_cache_dir() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join("archives"))
+}
+
+fn archive_file_name(version: &str, arch: &str) -> String {
+    format!("node-{}-win-{}.zip", version, arch)
+}
+
+// 能正常读取 zip 目录的缓存才视为有效，损坏的缓存直接删除
+fn find_cached_archive(version: &str, arch: &str) -> Option<PathBuf> {
+    let path = get_archive_cache_dir().ok()?.join(archive_file_name(version, arch));
+    if !path.exists() {
+        return None;
+    }
+    let valid = File::open(&path).ok().map(|f| zip::ZipArchive::new(f).is_ok()).unwrap_or(false);
+    if !valid {
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+    // 更新修改时间作为最近使用时间
+    if let Ok(file) = fs::OpenOptions::new().write(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+    Some(path)
+}
+
+fn store_archive_in_cache(zip_path: &Path, version: &str, arch: &str) {
+    let cache_dir = match get_archive_cache_dir() {
+        Ok(dir) => dir,
+        Err(_) => {
+            let _ = fs::remove_file(zip_path);
+            return;
+        }
+    };
+    let _ = fs::create_dir_all(&cache_dir);
+    let target = cache_dir.join(archive_file_name(version, arch));
+    // 跨盘时 rename 会失败，退回复制后删除
+    if fs::rename(zip_path, &target).is_err() {
+        let _ = fs::copy(zip_path, &target);
+        let _ = fs::remove_file(zip_path);
+    }
+    if let Ok(file) = fs::OpenOptions::new().write(true).open(&target) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+    evict_archive_cache(ARCHIVE_CACHE_MAX_BYTES);
+}
+
+fn list_cached_archive_files() -> Vec<(PathBuf, u64, SystemTime)> {
+    let mut files = Vec::new();
+    if let Ok(entries) = get_archive_cache_dir().and_then(|dir| fs::read_dir(dir).map_err(|e| e.to_string())) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map(|e| e == "zip").unwrap_or(false) {
+                if let Ok(meta) = entry.metadata() {
+                    files.push((path, meta.len(), meta.modified().unwrap_or(UNIX_EPOCH)));
+                }
+            }
+        }
+    }
+    files
+}
+
+fn evict_archive_cache(max_bytes: u64) {
+    let mut files = list_cached_archive_files();
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    // 最久未使用的排在前面
+    files.sort_by_key(|(_, _, used)| *used);
+    for (path, size, _) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedArchive {
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub size: u64,
+    #[serde(rename = "lastUsed")]
+    pub last_used: String,
+}
+
+#[tauri::command]
+async fn get_cached_archives() -> Result<Vec<CachedArchive>, String> {
+    let mut files = list_cached_archive_files();
+    files.sort_by(|a, b| b.2.cmp(&a.2));
+    Ok(files
+        .into_iter()
+        .map(|(path, size, used)| CachedArchive {
+            file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            size,
+            last_used: DateTime::<Local>::from(used).format("%Y-%m-%d %H:%M:%S").to_string(),
+        })
+        .collect())
+}
+
+// 返回释放的字节数
+#[tauri::command]
+async fn clear_archive_cache() -> Result<u64, String> {
+    let mut freed = 0;
+    for (path, size, _) in list_cached_archive_files() {
+        fs::remove_file(&path).map_err(|e| tr_args("error.delete", &[&path.display(), &e]))?;
+        freed += size;
+    }
+    Ok(freed)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DownloadCacheItem {
+    pub kind: String, // "part" 未完成的下载，"archive" 缓存的安装包
+    pub path: String,
+    pub version: String,
+    pub size: u64,
+    #[serde(rename = "ageSecs")]
+    pub age_secs: u64,
+    // 正在进行中的下载，不允许删除
+    pub active: bool,
+}
+
+fn file_age_secs(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn collect_download_cache_items(nvm_path: &Path, active_tasks: &[String]) -> Vec<DownloadCacheItem> {
+    let mut items = Vec::new();
+    if let Ok(entries) = fs::read_dir(nvm_path) {
+        for entry in entries.flatten() {
+            let version = entry.file_name().to_string_lossy().to_string();
+            let part = entry.path().join("node.zip.part");
+            if let Ok(meta) = fs::metadata(&part) {
+                items.push(DownloadCacheItem {
+                    kind: "part".to_string(),
+                    path: part.to_string_lossy().to_string(),
+                    active: active_tasks.contains(&version),
+                    version,
+                    size: meta.len(),
+                    age_secs: file_age_secs(&meta),
+                });
+            }
+        }
+    }
+    for (path, size, _) in list_cached_archive_files() {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        // node-v20.11.1-win-x64.zip -> v20.11.1
+        let version = name.strip_prefix("node-").and_then(|n| n.split("-win-").next()).unwrap_or(&name).to_string();
+        let age_secs = fs::metadata(&path).map(|m| file_age_secs(&m)).unwrap_or(0);
+        items.push(DownloadCacheItem {
+            kind: "archive".to_string(),
+            path: path.to_string_lossy().to_string(),
+            version,
+            size,
+            age_secs,
+            active: false,
+        });
+    }
+    items
+}
+
+#[tauri::command]
+async fn get_download_cache_info(state: tauri::State<'_, DownloadState>) -> Result<Vec<DownloadCacheItem>, String> {
+    let config = internal_get_config().await?;
+    let active: Vec<String> = state.tasks.lock().unwrap().keys().cloned().collect();
+    Ok(collect_download_cache_items(Path::new(&config.nvm_path), &active))
+}
+
+// 只删除 get_download_cache_info 列出的非活动条目，返回释放的字节数
+#[tauri::command]
+async fn clean_download_cache(state: tauri::State<'_, DownloadState>, items: Vec<String>) -> Result<u64, String> {
+    let config = internal_get_config().await?;
+    let active: Vec<String> = state.tasks.lock().unwrap().keys().cloned().collect();
+    let known = collect_download_cache_items(Path::new(&config.nvm_path), &active);
+
+    let mut freed = 0;
+    for path in items {
+        let item = match known.iter().find(|i| i.path == path) {
+            Some(i) => i,
+            None => continue,
+        };
+        if item.active {
+            return Err(tr_args("error.deleteWhileDownloading", &[&item.version]));
+        }
+        fs::remove_file(&item.path).map_err(|e| tr_args("error.delete", &[&item.path, &e]))?;
+        freed += item.size;
+        if item.kind == "part" {
+            if let Some(dir) = Path::new(&item.path).parent() {
+                let _ = cleanup_if_empty(dir);
+            }
+        }
+    }
+    Ok(freed)
+}
+
+#[derive(Debug, PartialEq)]
+enum InstallDirState {
+    Missing,
+    Complete,
+    // 上次安装中断留下的目录，已清理；kept_part 表示保留了可续传的 node.zip.part
+    Cleaned { kept_part: bool },
+}
+
+// 重新安装前检查版本目录：有 node.exe 且没有未完成的下载视为已安装；
+// 否则删除残留的解压内容，只保留 node.zip.part 用于续传（目录本身保留，可能是目录联接）
+fn prepare_install_dir(dir: &Path) -> Result<InstallDirState, String> {
+    if !dir.exists() {
+        return Ok(InstallDirState::Missing);
+    }
+    let part = dir.join("node.zip.part");
+    if dir.join("node.exe").exists() && !part.exists() {
+        return Ok(InstallDirState::Complete);
+    }
+    let entries = fs::read_dir(dir).map_err(|e| tr_args("error.readVersionsDir", &[&e]))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == part {
+            continue;
+        }
+        let result = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        result.map_err(|e| tr_args("error.cleanupIncomplete", &[&path.display(), &e]))?;
+    }
+    Ok(InstallDirState::Cleaned { kept_part: part.exists() })
+}
+
+fn cleanup_if_empty(path: &Path) -> std::io::Result<()> {
+    if path.exists() && path.is_dir() {
+        let entries = fs::read_dir(path)?;
+        if entries.count() == 0 {
+            fs::remove_dir(path)?;
+        }
+    }
+    Ok(())
+}
+// TODO: 下载 npm (这是后续优化点，目前先完成核心逻辑)
+//为了保证兼容性，我们在下载完 node.zip 后，已经包含了 npm。
+
+// 缺少这些文件时 node / npm 无法使用，写入失败必须中止安装
+fn is_critical_node_file(relative: &Path) -> bool {
+    let rel = relative.to_string_lossy().replace('\\', "/").to_lowercase();
+    matches!(rel.as_str(), "node.exe" | "npm.cmd" | "npx.cmd" | "node_modules/npm/bin/npm-cli.js")
+}
+
+// 杀毒软件扫描新文件时会短暂占用（共享冲突 32 / 锁冲突 33 / 拒绝访问 5），稍等后重试。
+// 条目直接流式写入文件；重试时重新打开条目和目标文件，从头写入
+fn write_zip_entry_with_retry<R: std::io::Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    index: usize,
+    outpath: &Path,
+) -> Result<(), String> {
+    let mut last_error = String::new();
+    for attempt in 0..3 {
+        let mut entry = archive.by_index(index).map_err(|e| tr_args("error.readArchive", &[&e]))?;
+        match File::create(outpath).and_then(|mut out| std::io::copy(&mut entry, &mut out)) {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                last_error = e.to_string();
+                if !matches!(e.raw_os_error(), Some(5) | Some(32) | Some(33)) {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(300 * (attempt + 1)));
+            }
+        }
+    }
+    Err(last_error)
+}
+
+// 单个文件写入失败不会中止解压，返回未能解压的文件（相对路径）；
+// 只有关键文件失败或解压后 node.exe / npm 缺失时才返回错误
+// on_progress 的参数为已处理条目的百分比
+fn extract_and_flatten_zip(
+    zip_path: &Path,
+    extract_to: &Path,
+    _root_folder_name: &str,
+    on_progress: &mut dyn FnMut(u32),
+) -> Result<Vec<String>, String> {
+    let file = File::open(zip_path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut failed = Vec::new();
+    let total = archive.len().max(1);
+
+    for i in 0..archive.len() {
+        on_progress((i * 100 / total) as u32);
+        let file = archive.by_index(i).map_err(|e| e.to_string())?;
+        let is_dir = file.name().ends_with('/');
+        let relative_path: PathBuf = match file.enclosed_name() {
+            Some(path) => {
+                let components: Vec<_> = path.components().collect();
+                if components.len() <= 1 {
+                    continue;
+                }
+                components.iter().skip(1).collect()
+            }
+            None => continue,
+        };
+        drop(file);
+        let outpath = extract_to.join(&relative_path);
+
+        let result = if is_dir {
+            fs::create_dir_all(&outpath).map_err(|e| e.to_string())
+        } else {
+            outpath
+                .parent()
+                .map(|p| fs::create_dir_all(p).map_err(|e| e.to_string()))
+                .unwrap_or(Ok(()))
+                .and_then(|_| write_zip_entry_with_retry(&mut archive, i, &outpath))
+        };
+        if let Err(e) = result {
+            if is_critical_node_file(&relative_path) {
+                return Err(tr_args("error.writePath", &[&relative_path.display(), &e]));
+            }
+            failed.push(relative_path.to_string_lossy().to_string());
+        }
+    }
+
+    on_progress(100);
+
+    let missing: Vec<&str> = ["node.exe", "npm.cmd"].into_iter().filter(|f| !extract_to.join(f).exists()).collect();
+    if !missing.is_empty() {
+        return Err(tr_args("error.missingAfterExtract", &[&missing.join(", ")]));
+    }
+    Ok(failed)
+}
+
+async fn download_file_with_resume(
+    window: &WebviewWindow,
+    version: &str,
+    url: &str,
+    part_path: &PathBuf,
+    target_path: &PathBuf,
+    pause_flag: Arc<AtomicBool>,
+    cancel_rx: &mut broadcast::Receiver<()>,
+    base_status: &str,
+) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let (stall_timeout, max_retries, backoff) = match internal_get_config().await {
+        Ok(c) => (c.download_stall_timeout.max(5), c.download_retries, c.download_retry_backoff),
+        Err(_) => (default_stall_timeout(), default_download_retries(), default_retry_backoff()),
+    };
+
+    // 连接停滞或可恢复的错误时重新发起 Range 请求续传，超过重试次数后报错
+    let mut attempt = 0;
+    let mut retry = 0;
+    loop {
+        match download_attempt(window, &client, version, url, part_path, target_path, &pause_flag, cancel_rx, base_status, stall_timeout).await? {
+            DownloadOutcome::Finished => return Ok(()),
+            DownloadOutcome::Interrupted(downloaded, error) => {
+                retry += 1;
+                if retry > max_retries {
+                    return Err(tr_args("error.downloadRetried", &[&error, &max_retries]));
+                }
+                let delay = backoff.saturating_mul(1 << (retry - 1).min(5)).min(DOWNLOAD_RETRY_MAX_DELAY);
+                let _ = window.emit("download:retry", serde_json::json!({
+                    "version": version,
+                    "downloaded": downloaded,
+                    "error": error,
+                    "attempt": retry,
+                    "maxRetries": max_retries,
+                    "delaySecs": delay
+                }));
+                // 等待期间也可以取消
+                tokio::select! {
+                    _ = tokio::time::sleep(tokio::time::Duration::from_secs(delay)) => {}
+                    _ = cancel_rx.recv() => {
+                        let _ = fs::remove_file(part_path);
+                        return Err(tr("error.downloadCancelled"));
+                    }
+                }
+            }
+            DownloadOutcome::Stalled(downloaded) => {
+                attempt += 1;
+                let _ = window.emit("download:stalled", serde_json::json!({
+                    "version": version,
+                    "downloaded": downloaded,
+                    "timeoutSecs": stall_timeout,
+                    "attempt": attempt,
+                    "maxRetries": DOWNLOAD_STALL_RETRIES,
+                    "willRetry": attempt <= DOWNLOAD_STALL_RETRIES
+                }));
+                if attempt > DOWNLOAD_STALL_RETRIES {
+                    return Err(tr_args("error.downloadStalled", &[&stall_timeout, &DOWNLOAD_STALL_RETRIES]));
+                }
+            }
+        }
+    }
+}
+
+const DOWNLOAD_STALL_RETRIES: u32 = 3;
+const DOWNLOAD_RETRY_MAX_DELAY: u64 = 60;
+
+enum DownloadOutcome {
+    Finished,
+    Stalled(u64),             // 停滞时已下载的字节数
+    Interrupted(u64, String), // 可重试的错误：连接中断、请求超时或服务器 5xx
+}
+
+// 连接失败、超时和传输中断可以续传重试；其他错误（例如 URL 无效）直接报告
+fn is_recoverable_request_error(e: &reqwest::Error) -> bool {
+    e.is_connect() || e.is_timeout() || e.is_request() || e.is_body() || e.is_decode()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn download_attempt(
+    window: &WebviewWindow,
+    client: &reqwest::Client,
+    version: &str,
+    url: &str,
+    part_path: &PathBuf,
+    target_path: &PathBuf,
+    pause_flag: &AtomicBool,
+    cancel_rx: &mut broadcast::Receiver<()>,
+    base_status: &str,
+    stall_timeout: u64,
+) -> Result<DownloadOutcome, String> {
     let mut downloaded = if part_path.exists() {
         fs::metadata(part_path).map(|m| m.len()).unwrap_or(0)
     } else {
@@ -1086,7 +4167,16 @@ async fn download_file_with_resume(
         response = response.header(reqwest::header::RANGE, format!("bytes={}-", downloaded));
     }
     
-    let res = response.send().await.map_err(|e| e.to_string())?;
+    let res = match tokio::time::timeout(tokio::time::Duration::from_secs(stall_timeout), response.send()).await {
+        Ok(Ok(res)) => res,
+        Ok(Err(e)) if is_recoverable_request_error(&e) => return Ok(DownloadOutcome::Interrupted(downloaded, e.to_string())),
+        Ok(Err(e)) => return Err(e.to_string()),
+        Err(_) => return Ok(DownloadOutcome::Stalled(downloaded)),
+    };
+    // 404、403 等客户端错误不重试
+    if res.status().is_server_error() {
+        return Ok(DownloadOutcome::Interrupted(downloaded, format!("HTTP {}", res.status())));
+    }
     let total_size = res.content_length().unwrap_or(0) + downloaded;
 
     if res.status() == reqwest::StatusCode::PARTIAL_CONTENT || (downloaded == 0 && res.status().is_success()) {
@@ -1097,20 +4187,31 @@ async fn download_file_with_resume(
             .open(part_path)
             .map_err(|e| e.to_string())?;
 
-        while let Some(chunk_result) = stream.next().await {
+        loop {
+            // 只在等待数据时计时，暂停期间不会被判定为停滞
+            let chunk_result = match tokio::time::timeout(tokio::time::Duration::from_secs(stall_timeout), stream.next()).await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(_) => return Ok(DownloadOutcome::Stalled(downloaded)),
+            };
+
             // 检查是否取消
             if cancel_rx.try_recv().is_ok() {
                 drop(file);
                 let _ = fs::remove_file(part_path);
-                return Err("下载已取消".to_string());
+                return Err(tr("error.downloadCancelled"));
             }
 
             // 检查是否暂停
             while pause_flag.load(Ordering::SeqCst) {
+                let phase_progress = (downloaded as f64 / total_size as f64 * 100.0) as u32;
                 let _ = window.emit("install:progress", serde_json::json!({ 
                     "version": version, 
-                    "progress": (downloaded as f64 / total_size as f64 * 100.0) as u32, 
-                    "status": "已暂停",
+                    "progress": overall_install_progress("download", phase_progress), 
+                    "phase": "download",
+                    "phaseProgress": phase_progress,
+                    "status": tr("install.paused"),
+                    "code": "install.paused",
                     "isPaused": true
                 }));
                 tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -1118,18 +4219,25 @@ async fn download_file_with_resume(
                 if cancel_rx.try_recv().is_ok() {
                     drop(file);
                     let _ = fs::remove_file(part_path);
-                    return Err("下载已取消".to_string());
+                    return Err(tr("error.downloadCancelled"));
                 }
             }
 
-            let chunk = chunk_result.map_err(|e| e.to_string())?;
+            let chunk = match chunk_result {
+                Ok(chunk) => chunk,
+                // 传输中断时已写入 .part 的数据保留，下次从这里续传
+                Err(e) => return Ok(DownloadOutcome::Interrupted(downloaded, e.to_string())),
+            };
             file.write_all(&chunk).map_err(|e| e.to_string())?;
             downloaded += chunk.len() as u64;
 
-            let progress = (downloaded as f64 / total_size as f64 * 100.0) as u32;
+            let phase_progress = (downloaded as f64 / total_size as f64 * 100.0) as u32;
+            report_task_progress(window.app_handle(), version, overall_install_progress("download", phase_progress));
             let _ = window.emit("install:progress", serde_json::json!({ 
                 "version": version, 
-                "progress": progress, 
+                "progress": overall_install_progress("download", phase_progress), 
+                "phase": "download",
+                "phaseProgress": phase_progress,
                 "status": base_status
             }));
         }
@@ -1142,52 +4250,173 @@ async fn download_file_with_resume(
             fs::rename(part_path, target_path).map_err(|e| e.to_string())?;
         }
     } else {
-        return Err(format!("下载失败: HTTP {}", res.status()));
+        return Err(tr_args("error.downloadHttp", &[&res.status()]));
     }
 
-    Ok(())
+    Ok(DownloadOutcome::Finished)
 }
 
 #[tauri::command]
 async fn uninstall_version(version: String) -> Result<bool, String> {
+    // 通过 install_version_at 安装到其他磁盘的版本，nvm 只会删除联接本身
+    let junction_target = match internal_get_config().await {
+        Ok(config) => {
+            let dir_name = if version.starts_with('v') { version.clone() } else { format!("v{}", version) };
+            resolve_version_junction(&PathBuf::from(&config.nvm_path).join(dir_name))
+        }
+        Err(_) => None,
+    };
+
     let output = create_silent_command("nvm")
         .args(["uninstall", &version])
         .output()
         .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        if let Some(target) = junction_target {
+            let _ = fs::remove_dir_all(target);
+        }
+    }
     Ok(output.status.success())
 }
 
-#[tauri::command]
-async fn get_global_packages() -> Result<Vec<Package>, String> {
-    // 获取配置以检查是否有全局共享路径
-    let config = internal_get_config().await.ok();
-    let prefix = config.as_ref().and_then(|c| c.global_prefix.clone());
-    
-    // 如果设置了 prefix 且目录不存在项目 node_modules，说明还没有安装任何全局包
-    if let Some(ref p) = prefix {
-        let node_modules = PathBuf::from(p).join("node_modules");
-        if !node_modules.exists() {
-            return Ok(Vec::new());
+// --- npm 残留清理 ---
+
+// npm 6 及以下失败时留下 .staging，npm 7+ 留下 ".包名-随机8位" 的临时目录
+fn is_npm_cruft_name(name: &str) -> bool {
+    if name == ".staging" {
+        return true;
+    }
+    let rest = match name.strip_prefix('.') {
+        Some(r) => r,
+        None => return false,
+    };
+    match rest.rsplit_once('-') {
+        Some((pkg, suffix)) => !pkg.is_empty() && suffix.len() == 8 && suffix.chars().all(|c| c.is_ascii_alphanumeric()),
+        None => false,
+    }
+}
+
+// 当前激活版本与共享目录下的全局 node_modules
+async fn get_global_node_modules_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(config) = internal_get_config().await {
+        if let Some(ref prefix) = config.global_prefix {
+            dirs.push(PathBuf::from(prefix).join("node_modules"));
+        }
+        if let Some(version) = get_current_node_version(&config.nvm_symlink) {
+            dirs.push(PathBuf::from(&config.nvm_path).join(format!("v{}", version)).join("node_modules"));
+        }
+    }
+    dirs.into_iter().filter(|d| d.is_dir()).collect()
+}
+
+fn find_npm_cruft_in(node_modules: &Path, found: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(node_modules) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if is_npm_cruft_name(&name) {
+                found.push(path);
+            } else if name.starts_with('@') {
+                // 作用域包的临时目录位于 @scope 下
+                find_npm_cruft_in(&path, found);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NpmCruftItem {
+    pub path: String,
+    pub size: u64,
+}
+
+#[tauri::command]
+async fn scan_npm_cruft() -> Result<Vec<NpmCruftItem>, String> {
+    let dirs = get_global_node_modules_dirs().await;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut found = Vec::new();
+        for dir in &dirs {
+            find_npm_cruft_in(dir, &mut found);
+        }
+        found
+            .into_iter()
+            .map(|path| NpmCruftItem { size: get_dir_size(&path), path: path.to_string_lossy().to_string() })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// 只删除位于全局 node_modules（或其 @scope）下、且名称符合临时目录特征的路径，返回释放的字节数
+#[tauri::command]
+async fn clean_npm_cruft(paths: Vec<String>) -> Result<u64, String> {
+    let roots: Vec<String> = get_global_node_modules_dirs()
+        .await
+        .iter()
+        .map(|d| normalize_dir(&d.to_string_lossy()))
+        .collect();
+
+    // 先全部校验再删除，避免只删了一部分
+    for p in &paths {
+        let path = PathBuf::from(p);
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let parent = match path.parent() {
+            Some(parent) => parent,
+            None => return Err(tr_args("error.refuseDelete", &[&p])),
+        };
+        let in_root = roots.contains(&normalize_dir(&parent.to_string_lossy()))
+            || (parent.file_name().map(|n| n.to_string_lossy().starts_with('@')).unwrap_or(false)
+                && parent.parent().map(|gp| roots.contains(&normalize_dir(&gp.to_string_lossy()))).unwrap_or(false));
+        if !in_root || !is_npm_cruft_name(&name) {
+            return Err(tr_args("error.refuseDeleteNonCruft", &[&p]));
+        }
+    }
+
+    let mut freed = 0;
+    for p in paths {
+        let path = PathBuf::from(&p);
+        if !path.is_dir() {
+            continue;
+        }
+        let size = get_dir_size(&path);
+        fs::remove_dir_all(&path).map_err(|e| tr_args("error.delete", &[&p, &e]))?;
+        freed += size;
+    }
+    Ok(freed)
+}
+
+// 在指定 prefix 下列出全局包，prefix 为空时使用 npm 自身的配置
+// location 标记包的来源："shared" 表示共享全局目录，"version" 表示某个 Node 版本目录
+fn list_global_packages_at(prefix: Option<&str>, location: &str) -> Result<Vec<Package>, String> {
+    // 如果设置了 prefix 且目录不存在项目 node_modules，说明还没有安装任何全局包
+    if let Some(p) = prefix {
+        let node_modules = PathBuf::from(p).join("node_modules");
+        if !node_modules.exists() {
+            return Ok(Vec::new());
         }
     }
 
     let mut cmd = create_silent_command("npm.cmd");
     cmd.args(["list", "-g", "--depth=0", "--json"]);
 
-    // 如果配置了全局 prefix，显式传递给 npm，确保实时跟随配置
-    if let Some(ref p) = prefix {
+    // 显式传递 prefix 给 npm，确保实时跟随配置
+    if let Some(p) = prefix {
         cmd.args(["--prefix", p]);
     }
 
     let output = cmd.output()
-        .map_err(|e| format!("无法运行 npm 命令: {}. 请确保已执行 `nvm use` 并正确安装 Node.js。", e))?;
+        .map_err(|e| tr_args("error.runNpmHint", &[&e]))?;
     
     let stdout = String::from_utf8_lossy(&output.stdout);
     
     if !output.status.success() {
         if stdout.trim().is_empty() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("npm 命令执行失败: {}", stderr));
+            return Err(tr_args("error.npmFailed", &[&stderr]));
         }
         // 如果虽然失败但有 stdout，可能仍包含部分 JSON，继续处理
     }
@@ -1198,13 +4427,19 @@ async fn get_global_packages() -> Result<Vec<Package>, String> {
 
     let val: serde_json::Value = serde_json::from_str(&stdout).unwrap_or(serde_json::json!({}));
     let mut packages = Vec::new();
+    let node_modules = prefix.map(|p| PathBuf::from(p).join("node_modules"));
 
     if let Some(deps) = val.get("dependencies").and_then(|d| d.as_object()) {
         for (name, info) in deps {
-            if let Some(version) = info.get("version").and_then(|v| v.as_str()) {
+            let is_linked = node_modules.as_ref().map(|nm| is_linked_package(&nm.join(name))).unwrap_or(false);
+            // 链接包的 package.json 可能没有版本号，仍然需要列出
+            let version = info.get("version").and_then(|v| v.as_str());
+            if version.is_some() || is_linked {
                 packages.push(Package {
                     name: name.clone(),
-                    version: version.to_string(),
+                    version: version.unwrap_or_default().to_string(),
+                    location: location.to_string(),
+                    is_linked,
                 });
             }
         }
@@ -1213,21 +4448,386 @@ async fn get_global_packages() -> Result<Vec<Package>, String> {
     Ok(packages)
 }
 
+// npm link 创建的是符号链接（Windows 上为 junction），真实安装的包是普通目录
+fn is_linked_package(path: &Path) -> bool {
+    fs::symlink_metadata(path).map(|m| m.file_type().is_symlink()).unwrap_or(false)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkedPackage {
+    pub name: String,
+    #[serde(rename = "targetPath")]
+    pub target_path: String,
+}
+
+fn find_linked_packages_in(node_modules: &Path, scope: Option<&str>, found: &mut Vec<LinkedPackage>) {
+    if let Ok(entries) = fs::read_dir(node_modules) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if is_linked_package(&path) {
+                let target = fs::read_link(&path).unwrap_or_default();
+                found.push(LinkedPackage {
+                    name: match scope {
+                        Some(scope) => format!("{}/{}", scope, name),
+                        None => name,
+                    },
+                    target_path: target.to_string_lossy().to_string(),
+                });
+            } else if scope.is_none() && name.starts_with('@') && path.is_dir() {
+                find_linked_packages_in(&path, Some(&name), found);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_linked_packages() -> Result<Vec<LinkedPackage>, String> {
+    let mut found = Vec::new();
+    for dir in get_global_node_modules_dirs().await {
+        find_linked_packages_in(&dir, None, &mut found);
+    }
+    Ok(found)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BinProvider {
+    pub package: String,
+    pub version: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BinConflict {
+    pub command: String,
+    pub providers: Vec<BinProvider>,
+}
+
+// 读取目录下每个全局包 package.json 中的 bin，结果为 (命令名, 提供者)
+fn collect_package_bins_in(node_modules: &Path, scope: Option<&str>, found: &mut Vec<(String, BinProvider)>) {
+    if let Ok(entries) = fs::read_dir(node_modules) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            if scope.is_none() && name.starts_with('@') && path.is_dir() {
+                collect_package_bins_in(&path, Some(&name), found);
+                continue;
+            }
+            let manifest = match fs::read_to_string(path.join("package.json"))
+                .ok()
+                .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+            {
+                Some(m) => m,
+                None => continue,
+            };
+            let package = match scope {
+                Some(scope) => format!("{}/{}", scope, name),
+                None => name,
+            };
+            let provider = BinProvider {
+                package: package.clone(),
+                version: manifest.get("version").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                path: path.to_string_lossy().to_string(),
+            };
+            for command in bin_names_from_manifest(&manifest, &package) {
+                found.push((command, provider.clone()));
+            }
+        }
+    }
+}
+
+// 多个全局包提供同名命令时，实际运行哪个取决于 PATH 顺序和安装先后，容易用错
+#[tauri::command]
+async fn detect_bin_conflicts() -> Result<Vec<BinConflict>, String> {
+    let mut found = Vec::new();
+    for dir in get_global_node_modules_dirs().await {
+        collect_package_bins_in(&dir, None, &mut found);
+    }
+
+    let mut by_command: HashMap<String, Vec<BinProvider>> = HashMap::new();
+    for (command, provider) in found {
+        // Windows 下命令名不区分大小写
+        by_command.entry(command.to_lowercase()).or_default().push(provider);
+    }
+    let mut conflicts: Vec<BinConflict> = by_command
+        .into_iter()
+        .filter(|(_, providers)| providers.len() > 1)
+        .map(|(command, providers)| BinConflict { command, providers })
+        .collect();
+    conflicts.sort_by(|a, b| a.command.cmp(&b.command));
+    Ok(conflicts)
+}
+
+// --- 包管理器 ---
+
+const PACKAGE_MANAGERS: [&str; 3] = ["npm", "yarn", "pnpm"];
+
+fn package_manager_available(pm: &str) -> bool {
+    create_silent_command(&format!("{}.cmd", pm))
+        .arg("--version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackageManagerInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub path: Option<String>,
+    // 由 corepack 生成的 shim 提供
+    #[serde(rename = "viaCorepack")]
+    pub via_corepack: bool,
+}
+
+fn probe_package_manager(pm: &str) -> PackageManagerInfo {
+    let path = create_silent_command("where")
+        .arg(format!("{}.cmd", pm))
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8_lossy(&o.stdout).lines().next().map(|l| l.trim().to_string()))
+        .filter(|l| !l.is_empty());
+    let version = path.as_ref().and_then(|_| {
+        create_silent_command(&format!("{}.cmd", pm))
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    });
+    PackageManagerInfo {
+        name: pm.to_string(),
+        via_corepack: path.as_ref().map(|p| is_corepack_shim(Path::new(p))).unwrap_or(false),
+        version,
+        path,
+    }
+}
+
+// 并行检查 npm / yarn / pnpm 是否在 PATH 中及其版本，未安装的返回空值而不是报错
+#[tauri::command]
+async fn detect_package_managers() -> Result<Vec<PackageManagerInfo>, String> {
+    let probes: Vec<_> = PACKAGE_MANAGERS
+        .iter()
+        .map(|pm| tauri::async_runtime::spawn_blocking(move || probe_package_manager(pm)))
+        .collect();
+    let mut results = Vec::new();
+    for probe in join_all(probes).await {
+        results.push(probe.map_err(|e| e.to_string())?);
+    }
+    Ok(results)
+}
+
+// 配置的包管理器未安装（或配置值无效）时回退到 npm
+async fn resolve_package_manager() -> String {
+    let chosen = internal_get_config().await.map(|c| c.package_manager).unwrap_or_else(|_| default_package_manager());
+    if chosen != "npm" && PACKAGE_MANAGERS.contains(&chosen.as_str()) && package_manager_available(&chosen) {
+        chosen
+    } else {
+        "npm".to_string()
+    }
+}
+
+// 各包管理器对全局包执行 install / uninstall / update 的参数
+fn global_package_args(pm: &str, action: &str, package: &str) -> Vec<String> {
+    let args: &[&str] = match (pm, action) {
+        ("pnpm", "install") => &["add", "-g"],
+        ("pnpm", "uninstall") => &["remove", "-g"],
+        ("pnpm", _) => &["update", "-g"],
+        ("yarn", "install") => &["global", "add"],
+        ("yarn", "uninstall") => &["global", "remove"],
+        ("yarn", _) => &["global", "upgrade"],
+        (_, "install") => &["install", "-g"],
+        (_, "uninstall") => &["uninstall", "-g"],
+        _ => &["update", "-g"],
+    };
+    args.iter().map(|a| a.to_string()).chain(std::iter::once(package.to_string())).collect()
+}
+
+// pnpm list -g --json：数组中每一项是一个全局目录，依赖版本为 "link:..." 表示链接包
+fn parse_pnpm_list_output(stdout: &str) -> Vec<Package> {
+    let val: serde_json::Value = serde_json::from_str(stdout).unwrap_or(serde_json::json!([]));
+    let mut packages = Vec::new();
+    for root in val.as_array().into_iter().flatten() {
+        for (name, info) in root.get("dependencies").and_then(|d| d.as_object()).into_iter().flatten() {
+            let version = info.get("version").and_then(|v| v.as_str()).unwrap_or_default();
+            packages.push(Package {
+                name: name.clone(),
+                version: version.to_string(),
+                location: "shared".to_string(),
+                is_linked: version.starts_with("link:"),
+            });
+        }
+    }
+    packages
+}
+
+// yarn global list --json 每行一个事件，包信息在 `"name@version" has binaries:` 形式的 info 中
+fn parse_yarn_global_list(stdout: &str) -> Vec<Package> {
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|event| event.get("type").and_then(|t| t.as_str()) == Some("info"))
+        .filter_map(|event| {
+            let data = event.get("data")?.as_str()?;
+            let spec = data.strip_suffix(" has binaries:")?.trim_matches('"');
+            // 作用域包以 @ 开头，版本号前的 @ 取最后一个
+            let (name, version) = spec.rsplit_once('@').filter(|(name, _)| !name.is_empty())?;
+            Some(Package {
+                name: name.to_string(),
+                version: version.to_string(),
+                location: "shared".to_string(),
+                is_linked: false,
+            })
+        })
+        .collect()
+}
+
+// yarn outdated --json 中 type 为 table 的事件，列顺序由 head 给出
+fn parse_yarn_outdated(stdout: &str) -> Vec<OutdatedPackage> {
+    let mut outdated = Vec::new();
+    for event in stdout.lines().filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok()) {
+        if event.get("type").and_then(|t| t.as_str()) != Some("table") {
+            continue;
+        }
+        let head: Vec<&str> = event["data"]["head"].as_array().into_iter().flatten().filter_map(|h| h.as_str()).collect();
+        let column = |row: &serde_json::Value, name: &str| {
+            head.iter()
+                .position(|h| *h == name)
+                .and_then(|i| row.get(i))
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        for row in event["data"]["body"].as_array().into_iter().flatten() {
+            outdated.push(OutdatedPackage {
+                name: column(row, "Package"),
+                current: column(row, "Current"),
+                wanted: column(row, "Wanted"),
+                latest: column(row, "Latest"),
+                manager: "yarn".to_string(),
+            });
+        }
+    }
+    outdated
+}
+
+// npm outdated 与 pnpm outdated --format json 都输出以包名为键的对象
+fn parse_outdated_object(stdout: &str, pm: &str) -> Vec<OutdatedPackage> {
+    let val: serde_json::Value = serde_json::from_str(stdout).unwrap_or(serde_json::json!({}));
+    let mut outdated = Vec::new();
+    if let Some(obj) = val.as_object() {
+        for (name, info) in obj {
+            outdated.push(OutdatedPackage {
+                name: name.clone(),
+                current: info.get("current").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                wanted: info.get("wanted").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                latest: info.get("latest").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                manager: pm.to_string(),
+            });
+        }
+    }
+    outdated
+}
+
+// yarn 的全局包安装在 `yarn global dir` 下，outdated 需要在该目录中执行
+fn yarn_global_dir() -> Option<PathBuf> {
+    let output = create_silent_command("yarn.cmd").args(["global", "dir"]).output().ok()?;
+    let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !dir.is_empty() {
+        Some(PathBuf::from(dir))
+    } else {
+        None
+    }
+}
+
+// 使用 yarn / pnpm 时列出其自身全局目录中的包，与 Node 版本无关
+fn list_packages_with_manager(pm: &str) -> Result<Vec<Package>, String> {
+    let args: &[&str] = if pm == "yarn" { &["global", "list", "--json"] } else { &["list", "-g", "--depth=0", "--json"] };
+    let output = create_silent_command(&format!("{}.cmd", pm))
+        .args(args)
+        .output()
+        .map_err(|e| tr_args("error.runCommand", &[&pm, &e]))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() && stdout.trim().is_empty() {
+        return Err(tr_args("error.commandFailed", &[&pm, &String::from_utf8_lossy(&output.stderr)]));
+    }
+    Ok(if pm == "yarn" { parse_yarn_global_list(&stdout) } else { parse_pnpm_list_output(&stdout) })
+}
+
+// scope: "shared" 共享全局目录，"active" 当前激活版本目录，"all" 两者合并（同名包优先取共享目录）
+// 未指定时，配置了共享目录则为 "shared"，否则为 "active"。
+// 配置为 yarn / pnpm 时忽略 scope，列出该包管理器全局目录中的包
+#[tauri::command]
+async fn get_global_packages(scope: Option<String>) -> Result<Vec<Package>, String> {
+    let pm = resolve_package_manager().await;
+    if pm != "npm" {
+        return list_packages_with_manager(&pm);
+    }
+
+    // 获取配置以检查是否有全局共享路径
+    let config = internal_get_config().await.ok();
+    let prefix = config.as_ref().and_then(|c| c.global_prefix.clone());
+
+    // 当前激活版本的目录，未使用共享目录时全局包安装在这里
+    let active_dir = config.as_ref().and_then(|c| {
+        get_current_node_version(&c.nvm_symlink)
+            .map(|v| PathBuf::from(&c.nvm_path).join(format!("v{}", v)).to_string_lossy().to_string())
+    });
+
+    let scope = scope.unwrap_or_else(|| {
+        if prefix.is_some() { "shared".to_string() } else { "active".to_string() }
+    });
+
+    match scope.as_str() {
+        "shared" => match prefix {
+            Some(ref p) => list_global_packages_at(Some(p), "shared"),
+            None => Ok(Vec::new()),
+        },
+        "active" => list_global_packages_at(active_dir.as_deref(), "version"),
+        "all" => {
+            let mut packages = match prefix {
+                Some(ref p) => list_global_packages_at(Some(p), "shared")?,
+                None => Vec::new(),
+            };
+            for pkg in list_global_packages_at(active_dir.as_deref(), "version").unwrap_or_default() {
+                if !packages.iter().any(|p| p.name == pkg.name) {
+                    packages.push(pkg);
+                }
+            }
+            Ok(packages)
+        }
+        _ => Err(tr_args("error.unknownScope", &[&scope])),
+    }
+}
+
 #[tauri::command]
-async fn search_packages(query: String, page: Option<u32>, size: Option<u32>) -> Result<serde_json::Value, String> {
+async fn search_packages(
+    query: String,
+    page: Option<u32>,
+    size: Option<u32>,
+    registry: Option<String>,
+) -> Result<serde_json::Value, String> {
     let page = page.unwrap_or(1);
     let size = size.unwrap_or(10);
-    let cache_key = format!("search:{}:{}:{}", query, page, size);
-    
-    if let Some(cached) = get_from_cache(&cache_key).await {
-        return Ok(cached);
-    }
 
     let registry_base = {
         let config = internal_get_config().await.ok();
         let npm_mirror = config.as_ref().map(|c| c.npm_mirror.as_str()).unwrap_or("");
-        get_registry_for_npm(npm_mirror).unwrap_or_else(|| "https://registry.npmjs.org/".to_string())
+        effective_registry(registry.as_deref(), npm_mirror)?.unwrap_or_else(|| "https://registry.npmjs.org/".to_string())
     };
+    // 不同 registry 的搜索结果分开缓存
+    let cache_key = match registry {
+        Some(_) => format!("search:{}:{}:{}:{}", registry_base, query, page, size),
+        None => format!("search:{}:{}:{}", query, page, size),
+    };
+
+    if let Some(cached) = get_from_cache(&cache_key).await {
+        return Ok(cached);
+    }
 
     let from = (page - 1) * size;
     let url = format!(
@@ -1308,32 +4908,279 @@ async fn search_packages(query: String, page: Option<u32>, size: Option<u32>) ->
     Ok(final_result)
 }
 
+// 解析当前配置对应的 registry 地址，保证以 / 结尾
+async fn get_registry_base() -> String {
+    let config = internal_get_config().await.ok();
+    let npm_mirror = config.as_ref().map(|c| c.npm_mirror.as_str()).unwrap_or("");
+    let registry = get_registry_for_npm(npm_mirror).unwrap_or_else(|| "https://registry.npmjs.org/".to_string());
+    if registry.ends_with('/') { registry } else { format!("{}/", registry) }
+}
+
+// scope 包名中的 / 需要编码，例如 @vue/cli -> @vue%2Fcli
+fn encode_package_name(name: &str) -> String {
+    name.replace('/', "%2F")
+}
+
+// npm 下载统计从 2015-01-10 开始，单次 range 查询最多约 18 个月
+const NPM_STATS_EPOCH: (i32, u32, u32) = (2015, 1, 10);
+const NPM_RANGE_MAX_DAYS: i64 = 365;
+
+// 把 period 解析为起止日期：支持 last-week / last-month / last-year 以及 YYYY-MM-DD:YYYY-MM-DD
+fn resolve_trend_range(period: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let yesterday = today - chrono::Duration::days(1);
+    let (start, end) = match period {
+        "last-day" => (yesterday, yesterday),
+        "last-week" => (today - chrono::Duration::days(7), yesterday),
+        "last-month" => (today - chrono::Duration::days(30), yesterday),
+        "last-year" => (today - chrono::Duration::days(365), yesterday),
+        _ => {
+            let (a, b) = period.split_once(':').ok_or_else(|| tr_args("error.invalidPeriod", &[&period]))?;
+            let parse = |d: &str| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|_| tr_args("error.invalidDate", &[&d]));
+            (parse(a)?, parse(b)?.min(yesterday))
+        }
+    };
+    let epoch = NaiveDate::from_ymd_opt(NPM_STATS_EPOCH.0, NPM_STATS_EPOCH.1, NPM_STATS_EPOCH.2).unwrap();
+    let start = start.max(epoch);
+    if start > end {
+        return Err(tr_args("error.emptyPeriod", &[&period]));
+    }
+    Ok((start, end))
+}
+
+// 超过单次查询上限时拆成多段依次请求
+fn split_trend_range(start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut chunks = Vec::new();
+    let mut cursor = start;
+    while cursor <= end {
+        let chunk_end = (cursor + chrono::Duration::days(NPM_RANGE_MAX_DAYS - 1)).min(end);
+        chunks.push((cursor, chunk_end));
+        cursor = chunk_end + chrono::Duration::days(1);
+    }
+    chunks
+}
+
 #[tauri::command]
-async fn get_package_versions(package_name: String) -> Result<serde_json::Value, String> {
-    let cache_key = format!("pkg_versions:{}", package_name);
-    if let Some(cached) = get_from_cache(&cache_key).await {
-        return Ok(cached);
+async fn get_package_download_trend(name: String, period: String) -> Result<PackageDownloadTrend, String> {
+    let cache_key = format!("dl_trend:{}:{}", name, period);
+    if let Some(cached) = get_from_cache_with_ttl(&cache_key, 3600).await {
+        if let Ok(trend) = serde_json::from_value::<PackageDownloadTrend>(cached) {
+            return Ok(trend);
+        }
     }
 
-    let registry_base = {
-        let config = internal_get_config().await.ok();
-        let npm_mirror = config.as_ref().map(|c| c.npm_mirror.as_str()).unwrap_or("");
-        get_registry_for_npm(npm_mirror).unwrap_or_else(|| "https://registry.npmjs.org/".to_string())
+    let (start, end) = resolve_trend_range(&period, Local::now().date_naive())?;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut by_day: HashMap<String, u64> = HashMap::new();
+    for (chunk_start, chunk_end) in split_trend_range(start, end) {
+        let url = format!(
+            "https://api.npmjs.org/downloads/range/{}:{}/{}",
+            chunk_start.format("%Y-%m-%d"),
+            chunk_end.format("%Y-%m-%d"),
+            encode_package_name(&name)
+        );
+        let response = client
+            .get(&url)
+            .header("User-Agent", "Mozilla/5.0")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            // 包在这段时间内尚未发布时接口返回 404，当作没有下载量处理
+            continue;
+        }
+        if !response.status().is_success() {
+            return Err(tr_args("error.downloadTrendHttp", &[&name, &response.status()]));
+        }
+        let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+        if let Some(days) = json.get("downloads").and_then(|d| d.as_array()) {
+            for item in days {
+                if let Some(day) = item.get("day").and_then(|d| d.as_str()) {
+                    let count = item.get("downloads").and_then(|d| d.as_u64()).unwrap_or(0);
+                    by_day.insert(day.to_string(), count);
+                }
+            }
+        }
+    }
+
+    // 补齐缺失的日期，保证图表是连续的时间序列
+    let mut downloads = Vec::new();
+    let mut day = start;
+    while day <= end {
+        let key = day.format("%Y-%m-%d").to_string();
+        let count = by_day.get(&key).copied().unwrap_or(0);
+        downloads.push(DownloadPoint { day: key, downloads: count });
+        day += chrono::Duration::days(1);
+    }
+
+    let trend = PackageDownloadTrend {
+        package: name,
+        start: start.format("%Y-%m-%d").to_string(),
+        end: end.format("%Y-%m-%d").to_string(),
+        total: downloads.iter().map(|d| d.downloads).sum(),
+        downloads,
     };
+    if let Ok(value) = serde_json::to_value(&trend) {
+        save_to_cache(&cache_key, value).await;
+    }
+    Ok(trend)
+}
 
-    // 从 npm registry 获取包的所有版本
-    let url = format!(
-        "{}{}", 
-        if registry_base.ends_with('/') { registry_base } else { format!("{}/", registry_base) },
-        package_name
-    );
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .header("User-Agent", "Mozilla/5.0")
-        .send()
+#[tauri::command]
+async fn check_package_outdated(name: String) -> Result<serde_json::Value, String> {
+    let cache_key = format!("pkg_latest:{}", name);
+    let latest = match get_from_cache_with_ttl(&cache_key, 600).await {
+        Some(cached) => cached.as_str().map(|s| s.to_string()),
+        None => None,
+    };
+
+    let latest = match latest {
+        Some(v) => v,
+        None => {
+            let url = format!("{}{}", get_registry_base().await, encode_package_name(&name));
+            let client = reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .map_err(|e| e.to_string())?;
+            // 精简格式的元数据只包含安装所需字段，比完整文档小得多
+            let response = client
+                .get(&url)
+                .header("User-Agent", "Mozilla/5.0")
+                .header("Accept", "application/vnd.npm.install-v1+json")
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !response.status().is_success() {
+                return Err(tr_args("error.queryPackageHttp", &[&name, &response.status()]));
+            }
+            let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+            let latest = json
+                .get("dist-tags")
+                .and_then(|t| t.get("latest"))
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| tr_args("error.noLatestTag", &[&name]))?
+                .to_string();
+            save_to_cache(&cache_key, serde_json::json!(latest)).await;
+            latest
+        }
+    };
+
+    let current = get_global_packages(None)
         .await
-        .map_err(|e| e.to_string())?;
+        .unwrap_or_default()
+        .into_iter()
+        .find(|p| p.name == name)
+        .map(|p| p.version);
+
+    let has_update = current.as_ref().map(|c| compare_versions(c, &latest)).unwrap_or(false);
+
+    Ok(serde_json::json!({
+        "name": name,
+        "current": current,
+        "latest": latest,
+        "hasUpdate": has_update
+    }))
+}
+
+// package.json 的 bin 可以是字符串（命令名取去掉 scope 的包名）或 { 命令名: 路径 } 的映射
+fn bin_names_from_manifest(manifest: &serde_json::Value, package_name: &str) -> Vec<String> {
+    let mut names: Vec<String> = match manifest.get("bin") {
+        Some(serde_json::Value::String(_)) => {
+            vec![package_name.rsplit('/').next().unwrap_or(package_name).to_string()]
+        }
+        Some(serde_json::Value::Object(map)) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    names.sort();
+    names
+}
+
+// 安装前查看全局包会添加哪些命令，没有 bin 的包返回空列表
+#[tauri::command]
+async fn get_package_bins(name: String, version: Option<String>) -> Result<Vec<String>, String> {
+    let cache_key = format!("pkg_bins:{}@{}", name, version.as_deref().unwrap_or("latest"));
+    if let Some(cached) = get_from_cache(&cache_key).await {
+        if let Ok(bins) = serde_json::from_value::<Vec<String>>(cached) {
+            return Ok(bins);
+        }
+    }
+
+    let url = format!("{}{}", get_registry_base().await, encode_package_name(&name));
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+    // 精简格式的元数据同样包含每个版本的 bin 字段
+    let response = client
+        .get(&url)
+        .header("User-Agent", "Mozilla/5.0")
+        .header("Accept", "application/vnd.npm.install-v1+json")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(tr_args("error.queryPackageHttp", &[&name, &response.status()]));
+    }
+    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+
+    // version 也可以是 dist-tag（如 next）
+    let requested = version.unwrap_or_else(|| "latest".to_string());
+    let resolved = json
+        .get("dist-tags")
+        .and_then(|t| t.get(&requested))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or(requested);
+    let manifest = json
+        .get("versions")
+        .and_then(|v| v.get(&resolved))
+        .ok_or_else(|| tr_args("error.packageVersionMissing", &[&name, &resolved]))?;
+
+    let bins = bin_names_from_manifest(manifest, &name);
+    save_to_cache(&cache_key, serde_json::json!(bins)).await;
+    Ok(bins)
+}
+
+#[tauri::command]
+async fn get_package_versions(package_name: String) -> Result<serde_json::Value, String> {
+    fetch_package_versions(package_name, None).await
+}
+
+// registry 为单次操作临时使用的 registry，未指定时按配置的 npm 镜像查询；两者分开缓存
+async fn fetch_package_versions(package_name: String, registry: Option<String>) -> Result<serde_json::Value, String> {
+    let cache_key = match registry {
+        Some(ref r) => format!("pkg_versions:{}:{}", r, package_name),
+        None => format!("pkg_versions:{}", package_name),
+    };
+    if let Some(cached) = get_from_cache(&cache_key).await {
+        return Ok(cached);
+    }
+
+    let registry_base = match registry {
+        Some(r) => r,
+        None => {
+            let config = internal_get_config().await.ok();
+            let npm_mirror = config.as_ref().map(|c| c.npm_mirror.as_str()).unwrap_or("");
+            get_registry_for_npm(npm_mirror).unwrap_or_else(|| "https://registry.npmjs.org/".to_string())
+        }
+    };
+
+    // 从 npm registry 获取包的所有版本
+    let url = format!(
+        "{}{}", 
+        if registry_base.ends_with('/') { registry_base } else { format!("{}/", registry_base) },
+        package_name
+    );
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&url)
+        .header("User-Agent", "Mozilla/5.0")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
     
     let json: serde_json::Value = response
         .json()
@@ -1383,13 +5230,62 @@ async fn get_package_versions(package_name: String) -> Result<serde_json::Value,
     Ok(result)
 }
 
+// 版本描述是否为 dist-tag（next、beta 等）：以字母开头，且不是 x 通配或 v1.2.3 形式的版本号，
+// 也不是 git / 文件等其他来源
+fn is_dist_tag_spec(spec: &str) -> bool {
+    let spec = spec.trim();
+    let is_version = spec.strip_prefix(['v', 'V']).map(|rest| rest.starts_with(|c: char| c.is_ascii_digit())).unwrap_or(false);
+    spec.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !spec.eq_ignore_ascii_case("x")
+        && !is_version
+        && !spec.contains([':', '/', ' '])
+}
+
+// 拆分 name@spec，作用域包开头的 @ 不算分隔符
+fn split_package_spec(spec: &str) -> (String, Option<String>) {
+    match spec.rfind('@').filter(|&i| i > 0) {
+        Some(i) => (spec[..i].to_string(), Some(spec[i + 1..].to_string()).filter(|v| !v.is_empty())),
+        None => (spec.to_string(), None),
+    }
+}
+
+fn lookup_dist_tag(dist_tags: &serde_json::Value, name: &str, tag: &str) -> Result<String, String> {
+    if let Some(version) = dist_tags.get(tag).and_then(|v| v.as_str()) {
+        return Ok(version.to_string());
+    }
+    let mut tags: Vec<&str> = dist_tags.as_object().map(|o| o.keys().map(|k| k.as_str()).collect()).unwrap_or_default();
+    tags.sort();
+    Err(tr_args("error.distTagMissing", &[&name, &tag, &tags.join(", ")]))
+}
+
+// 查询 dist-tag 当前指向的具体版本，安装前展示给用户
+#[tauri::command]
+async fn resolve_dist_tag(name: String, tag: String) -> Result<String, String> {
+    let info = get_package_versions(name.clone()).await?;
+    lookup_dist_tag(&info["distTags"], &name, tag.trim())
+}
+
+// version 可以是具体版本、范围或 dist-tag（next、beta 等），原样交给包管理器；
+// dist-tag 会先确认存在，查询失败（例如离线）时不拦截，由包管理器报告错误
 #[tauri::command]
 async fn install_global_package(
     window: WebviewWindow,
     state: tauri::State<'_, DownloadState>,
     name: String, 
-    version: Option<String>
+    version: Option<String>,
+    registry: Option<String>,
 ) -> Result<bool, String> {
+    // 先校验临时 registry，避免启动任务后才报错；dist-tag 也在同一个 registry 上确认
+    let registry_override = match registry {
+        Some(ref r) => effective_registry(Some(r), "")?,
+        None => None,
+    };
+    let (base_name, inline_spec) = split_package_spec(name.trim());
+    if let Some(tag) = version.clone().or(inline_spec).filter(|v| is_dist_tag_spec(v)) {
+        if let Ok(info) = fetch_package_versions(base_name.clone(), registry_override).await {
+            lookup_dist_tag(&info["distTags"], &base_name, tag.trim())?;
+        }
+    }
     let package_spec = if let Some(ref v) = version {
         format!("{}@{}", name, v)
     } else {
@@ -1402,11 +5298,12 @@ async fn install_global_package(
     {
         let tasks = state.tasks.lock().unwrap();
         if tasks.contains_key(&install_id) {
-            return Err(format!("包 {} 正在安装中", install_id));
+            return Err(tr_args("error.packageInstalling", &[&install_id]));
         }
     }
 
     let (cancel_tx, _) = broadcast::channel(1);
+    let (done_tx, done_rx) = watch::channel(false);
     let pause_flag = Arc::new(AtomicBool::new(false));
     let pid_ref = Arc::new(Mutex::new(None));
 
@@ -1416,6 +5313,8 @@ async fn install_global_package(
             cancel_tx: cancel_tx.clone(),
             pause_flag: pause_flag.clone(),
             pid: pid_ref.clone(),
+            done_rx,
+            progress: None,
         });
     }
 
@@ -1428,7 +5327,8 @@ async fn install_global_package(
         let _ = window.emit("install:progress", serde_json::json!({
             "version": install_id_clone,
             "progress": 10,
-            "status": format!("正在安装 {}...", install_id_clone)
+            "status": tr_with("install.package", &install_id_clone),
+            "code": "install.package"
         }));
 
         let config_res = internal_get_config().await;
@@ -1441,11 +5341,12 @@ async fn install_global_package(
         }
         let config = config_res.unwrap();
 
-        let mut cmd = AsyncCommand::new("npm.cmd");
-        cmd.args(["install", "-g", &package_spec]);
+        let pm = resolve_package_manager().await;
+        let mut cmd = AsyncCommand::new(format!("{}.cmd", pm));
+        cmd.args(global_package_args(&pm, "install", &package_spec));
         
-        // 自动识别正确的 registry
-        if let Some(r) = get_registry_for_npm(&config.npm_mirror) {
+        // 显式指定的 registry 优先，否则自动识别
+        if let Ok(Some(r)) = effective_registry(registry.as_deref(), &config.npm_mirror) {
             cmd.args(["--registry", &r]);
         }
         
@@ -1454,6 +5355,7 @@ async fn install_global_package(
         {
             cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
         }
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
         let child = cmd.spawn();
         
@@ -1462,6 +5364,7 @@ async fn install_global_package(
             if let Some(p) = pid {
                 *pid_ref.lock().unwrap() = Some(p);
             }
+            forward_child_output(&window, &install_id_clone, &mut child);
 
             // 监听进程结束或取消信号
             tokio::select! {
@@ -1471,7 +5374,8 @@ async fn install_global_package(
                         let _ = window.emit("install:progress", serde_json::json!({
                             "version": install_id_clone,
                             "progress": 100,
-                            "status": "安装完成",
+                            "status": tr("install.done"),
+                            "code": "install.done",
                             "finished": true
                         }));
                     } else {
@@ -1486,24 +5390,12 @@ async fn install_global_package(
                     // 同时清理子进程树
                     if let Some(p) = pid {
                         #[cfg(windows)]
-                        {
-                            use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
-                            use windows_sys::Win32::Foundation::CloseHandle;
-                            let pids = get_all_process_children(p);
-                            for child_p in pids {
-                                unsafe {
-                                    let handle = OpenProcess(PROCESS_TERMINATE, 0, child_p);
-                                    if handle != 0 {
-                                        TerminateProcess(handle, 1);
-                                        CloseHandle(handle);
-                                    }
-                                }
-                            }
-                        }
+                        let _ = terminate_process_tree(p);
                     }
                     let _ = window.emit("install:progress", serde_json::json!({
                         "version": install_id_clone,
-                        "status": "已取消",
+                        "status": tr("install.cancelled"),
+                        "code": "install.cancelled",
                         "finished": true
                     }));
                 }
@@ -1521,36 +5413,145 @@ async fn install_global_package(
             let mut tasks = state.tasks.lock().unwrap();
             tasks.remove(&install_id_clone);
         }
+        let _ = done_tx.send(true);
     });
 
     Ok(true)
 }
 
+const NPM_LOG_MAX_LINES: usize = 2000;
+const NPM_LOG_MAX_LINE_LEN: usize = 1000;
+
+// 逐行读取子进程输出，以 npm:log 事件转发给前端
+// 同一任务的 stdout/stderr 共享行数上限，超出后只发送一次省略提示，避免失控的输出刷爆事件通道
+fn forward_npm_stream<S>(window: WebviewWindow, task_id: String, stream: &'static str, reader: S, counter: Arc<AtomicUsize>)
+where
+    S: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let count = counter.fetch_add(1, Ordering::SeqCst);
+            if count < NPM_LOG_MAX_LINES {
+                let line: String = line.chars().take(NPM_LOG_MAX_LINE_LEN).collect();
+                let _ = window.emit("npm:log", serde_json::json!({
+                    "taskId": task_id,
+                    "stream": stream,
+                    "line": line
+                }));
+            } else if count == NPM_LOG_MAX_LINES {
+                let _ = window.emit("npm:log", serde_json::json!({
+                    "taskId": task_id,
+                    "stream": stream,
+                    "line": tr("npm.logTruncated"),
+                    "truncated": true
+                }));
+            }
+        }
+    });
+}
+
+fn forward_child_output(window: &WebviewWindow, task_id: &str, child: &mut tokio::process::Child) {
+    let counter = Arc::new(AtomicUsize::new(0));
+    if let Some(stdout) = child.stdout.take() {
+        forward_npm_stream(window.clone(), task_id.to_string(), "stdout", stdout, counter.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_npm_stream(window.clone(), task_id.to_string(), "stderr", stderr, counter);
+    }
+}
+
+// 运行包管理器命令（npm / yarn / pnpm）并实时转发输出，返回进程是否成功退出
+async fn run_npm_with_log(window: &WebviewWindow, task_id: &str, pm: &str, args: &[String]) -> Result<bool, String> {
+    let mut cmd = AsyncCommand::new(format!("{}.cmd", pm));
+    cmd.args(args);
+    #[cfg(windows)]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    forward_child_output(window, task_id, &mut child);
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    Ok(status.success())
+}
+
 #[tauri::command]
-async fn uninstall_global_package(name: String) -> Result<bool, String> {
-    let output = create_silent_command("npm.cmd")
-        .args(["uninstall", "-g", &name])
-        .output()
-        .map_err(|e| e.to_string())?;
-    Ok(output.status.success())
+async fn uninstall_global_package(window: WebviewWindow, name: String) -> Result<bool, String> {
+    let pm = resolve_package_manager().await;
+    let args = global_package_args(&pm, "uninstall", &name);
+    run_npm_with_log(&window, &name, &pm, &args).await
 }
 
 #[tauri::command]
-async fn update_global_package(name: String) -> Result<bool, String> {
+async fn update_global_package(window: WebviewWindow, name: String, registry: Option<String>) -> Result<bool, String> {
     let config = internal_get_config().await?;
-    let mut cmd = create_silent_command("npm.cmd");
-    cmd.args(["update", "-g", &name]);
+    let pm = resolve_package_manager().await;
+    let mut args = global_package_args(&pm, "update", &name);
     
-    if let Some(r) = get_registry_for_npm(&config.npm_mirror) {
-        cmd.args(["--registry", &r]);
+    if let Some(r) = effective_registry(registry.as_deref(), &config.npm_mirror)? {
+        args.push("--registry".to_string());
+        args.push(r);
     }
 
-    let output = cmd.output().map_err(|e| e.to_string())?;
-    Ok(output.status.success())
+    run_npm_with_log(&window, &name, &pm, &args).await
 }
 
 #[tauri::command]
 async fn check_outdated_packages() -> Result<Vec<OutdatedPackage>, String> {
+    outdated_with_manager(&resolve_package_manager().await).await
+}
+
+// 对所有已安装的包管理器分别检查并合并结果；未安装的跳过，
+// 单个包管理器失败不影响其他结果，全部失败时才返回错误
+#[tauri::command]
+async fn check_all_outdated() -> Result<Vec<OutdatedPackage>, String> {
+    let available: Vec<_> = PACKAGE_MANAGERS
+        .iter()
+        .map(|pm| tauri::async_runtime::spawn_blocking(move || (*pm, *pm == "npm" || package_manager_available(pm))))
+        .collect();
+    let managers: Vec<&str> = join_all(available)
+        .await
+        .into_iter()
+        .flatten()
+        .filter(|(_, ok)| *ok)
+        .map(|(pm, _)| pm)
+        .collect();
+
+    let mut merged = Vec::new();
+    let mut errors = Vec::new();
+    for pm in &managers {
+        match outdated_with_manager(pm).await {
+            Ok(packages) => merged.extend(packages),
+            Err(e) => errors.push(format!("{}: {}", pm, e)),
+        }
+    }
+    if !managers.is_empty() && errors.len() == managers.len() {
+        return Err(errors.join("\n"));
+    }
+    Ok(merged)
+}
+
+async fn outdated_with_manager(pm: &str) -> Result<Vec<OutdatedPackage>, String> {
+    if pm == "yarn" {
+        let dir = yarn_global_dir().ok_or_else(|| tr("error.yarnGlobalDir"))?;
+        // 有可更新的包时 yarn outdated 同样以非 0 状态退出
+        let output = create_silent_command("yarn.cmd")
+            .args(["outdated", "--json"])
+            .current_dir(&dir)
+            .output()
+            .map_err(|e| tr_args("error.runYarn", &[&e]))?;
+        return Ok(parse_yarn_outdated(&String::from_utf8_lossy(&output.stdout)));
+    }
+    if pm == "pnpm" {
+        let output = create_silent_command("pnpm.cmd")
+            .args(["outdated", "-g", "--format", "json"])
+            .output()
+            .map_err(|e| tr_args("error.runPnpm", &[&e]))?;
+        return Ok(parse_outdated_object(&String::from_utf8_lossy(&output.stdout), "pnpm"));
+    }
+
     // 获取配置以检查是否有全局共享路径
     let config = internal_get_config().await.ok();
     let prefix = config.as_ref().and_then(|c| c.global_prefix.clone());
@@ -1579,7 +5580,7 @@ async fn check_outdated_packages() -> Result<Vec<OutdatedPackage>, String> {
     }
 
     let output = cmd.output()
-        .map_err(|e| format!("无法运行 npm 命令: {}. 请确保已执行 `nvm use` 并正确安装 Node.js。", e))?;
+        .map_err(|e| tr_args("error.runNpmHint", &[&e]))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     
@@ -1588,32 +5589,175 @@ async fn check_outdated_packages() -> Result<Vec<OutdatedPackage>, String> {
         if !output.status.success() {
              let stderr = String::from_utf8_lossy(&output.stderr);
              if !stderr.trim().is_empty() {
-                 return Err(format!("npm 命令执行失败: {}", stderr));
+                 return Err(tr_args("error.npmFailed", &[&stderr]));
              }
         }
         return Ok(Vec::new());
     }
 
-    let val: serde_json::Value = serde_json::from_str(&stdout).unwrap_or(serde_json::json!({}));
-    let mut outdated = Vec::new();
+    Ok(parse_outdated_object(&stdout, "npm"))
+}
 
-    if let Some(obj) = val.as_object() {
-        for (name, info) in obj {
-            outdated.push(OutdatedPackage {
-                name: name.clone(),
-                current: info.get("current").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
-                wanted: info.get("wanted").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
-                latest: info.get("latest").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+// 解析 npm audit --json 的输出，兼容 npm 6 (advisories) 和 npm 7+ (vulnerabilities) 两种格式。
+// 返回 None 表示 audit 不可用（例如全局目录没有 lockfile 时的 ENOLOCK）
+fn parse_npm_audit_output(val: &serde_json::Value) -> Option<Vec<AuditFinding>> {
+    if val.get("error").is_some() {
+        return None;
+    }
+    let str_field = |v: &serde_json::Value, key: &str| v.get(key).and_then(|s| s.as_str()).map(|s| s.to_string());
+    let mut findings = Vec::new();
+
+    if let Some(vulns) = val.get("vulnerabilities").and_then(|v| v.as_object()) {
+        for (name, info) in vulns {
+            // fixAvailable 可能是 true/false，也可能是描述升级方案的对象
+            let fix_available = match info.get("fixAvailable") {
+                Some(serde_json::Value::Bool(b)) => *b,
+                Some(serde_json::Value::Object(_)) => true,
+                _ => false,
+            };
+            // via 中的字符串表示经由其他有漏洞的依赖引入，那个依赖会有自己的条目
+            for via in info.get("via").and_then(|v| v.as_array()).into_iter().flatten() {
+                if via.is_object() {
+                    findings.push(AuditFinding {
+                        package: str_field(via, "name").unwrap_or_else(|| name.clone()),
+                        severity: str_field(via, "severity").unwrap_or_else(|| "unknown".to_string()),
+                        title: str_field(via, "title").unwrap_or_default(),
+                        url: str_field(via, "url"),
+                        fix_available,
+                    });
+                }
+            }
+        }
+        return Some(findings);
+    }
+
+    if let Some(advisories) = val.get("advisories").and_then(|v| v.as_object()) {
+        for advisory in advisories.values() {
+            let patched = str_field(advisory, "patched_versions").unwrap_or_default();
+            findings.push(AuditFinding {
+                package: str_field(advisory, "module_name").unwrap_or_default(),
+                severity: str_field(advisory, "severity").unwrap_or_else(|| "unknown".to_string()),
+                title: str_field(advisory, "title").unwrap_or_default(),
+                url: str_field(advisory, "url"),
+                fix_available: !patched.is_empty() && patched != "<0.0.0",
             });
         }
+        return Some(findings);
+    }
+
+    None
+}
+
+// 受影响范围的每一段都有上限（< 或 <=）时，说明存在已修复的新版本
+fn advisory_has_fix(vulnerable_versions: &str) -> bool {
+    vulnerable_versions.split("||").all(|set| set.contains('<'))
+}
+
+// 全局安装通常没有 lockfile，npm audit 无法使用时改为向 registry 批量查询已安装版本的安全公告
+async fn audit_via_registry(packages: &[Package]) -> Result<Vec<AuditFinding>, String> {
+    let mut body = serde_json::Map::new();
+    for pkg in packages.iter().filter(|p| !p.is_linked) {
+        body.insert(pkg.name.clone(), serde_json::json!([pkg.version]));
+    }
+    if body.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(20))
+        .build()
+        .map_err(|e| e.to_string())?;
+    // 部分镜像不提供安全公告接口，此时退回官方 registry
+    let mut bases = vec![get_registry_base().await];
+    if bases[0] != "https://registry.npmjs.org/" {
+        bases.push("https://registry.npmjs.org/".to_string());
+    }
+
+    let mut last_error = String::new();
+    for base in bases {
+        let url = format!("{}-/npm/v1/security/advisories/bulk", base);
+        let response = match client.post(&url).header("User-Agent", "Mozilla/5.0").json(&body).send().await {
+            Ok(res) if res.status().is_success() => res,
+            Ok(res) => {
+                last_error = format!("HTTP {}", res.status());
+                continue;
+            }
+            Err(e) => {
+                last_error = e.to_string();
+                continue;
+            }
+        };
+        let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+        let mut findings = Vec::new();
+        for (name, advisories) in json.as_object().into_iter().flatten() {
+            for advisory in advisories.as_array().into_iter().flatten() {
+                let range = advisory.get("vulnerable_versions").and_then(|v| v.as_str()).unwrap_or("*");
+                findings.push(AuditFinding {
+                    package: name.clone(),
+                    severity: advisory.get("severity").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
+                    title: advisory.get("title").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    url: advisory.get("url").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                    fix_available: advisory_has_fix(range),
+                });
+            }
+        }
+        return Ok(findings);
+    }
+    Err(tr_args("error.advisories", &[&last_error]))
+}
+
+#[tauri::command]
+async fn audit_global_packages() -> Result<Vec<AuditFinding>, String> {
+    let config = internal_get_config().await.ok();
+    let prefix = config.as_ref().and_then(|c| c.global_prefix.clone());
+
+    let mut cmd = create_silent_command("npm.cmd");
+    cmd.args(["audit", "--json", "-g"]);
+    if let Some(ref p) = prefix {
+        cmd.args(["--prefix", p]);
+    }
+    if let Some(ref config) = config {
+        if let Some(r) = get_registry_for_npm(&config.npm_mirror) {
+            cmd.args(["--registry", &r]);
+        }
+    }
+
+    // 发现漏洞时 npm audit 以非 0 退出，只要输出能解析就采用
+    if let Ok(output) = cmd.output() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if let Ok(val) = serde_json::from_str::<serde_json::Value>(&stdout) {
+            if let Some(findings) = parse_npm_audit_output(&val) {
+                return Ok(findings);
+            }
+        }
     }
 
-    Ok(outdated)
+    let packages = get_global_packages(None).await?;
+    audit_via_registry(&packages).await
+}
+
+// 按配置过滤掉隐藏的内置镜像，开启 custom_mirrors_only 时不返回任何内置镜像
+fn get_visible_mirror_presets(config: Option<&NvmConfig>) -> Vec<MirrorPreset> {
+    let presets = get_all_mirror_presets();
+    match config {
+        Some(c) if c.custom_mirrors_only => Vec::new(),
+        Some(c) => presets.into_iter().filter(|p| !c.hidden_mirrors.contains(&p.id)).collect(),
+        None => presets,
+    }
 }
 
 #[tauri::command]
 async fn get_mirror_presets() -> Result<Vec<MirrorPreset>, String> {
-    Ok(get_all_mirror_presets())
+    let config = internal_get_config().await.ok();
+    Ok(get_visible_mirror_presets(config.as_ref()))
+}
+
+#[tauri::command]
+async fn set_mirror_visibility(hidden_ids: Vec<String>, custom_only: bool) -> Result<bool, String> {
+    let hidden = if hidden_ids.is_empty() { None } else { Some(hidden_ids.join(",")) };
+    let custom_only = if custom_only { Some("true".to_string()) } else { None };
+    update_settings_file(&get_settings_path()?, &[("hidden_mirrors", hidden), ("custom_mirrors_only", custom_only)])?;
+    Ok(true)
 }
 
 #[tauri::command]
@@ -1638,6 +5782,26 @@ async fn get_current_mirror() -> Result<serde_json::Value, String> {
     }))
 }
 
+// 区分请求失败的原因，便于用户判断是网络屏蔽还是镜像本身的问题
+fn describe_request_error(e: &reqwest::Error) -> String {
+    if e.is_timeout() {
+        return tr("error.requestTimeout");
+    }
+    if e.is_connect() {
+        let mut source = std::error::Error::source(e);
+        while let Some(err) = source {
+            if let Some(io) = err.downcast_ref::<std::io::Error>() {
+                if io.kind() == std::io::ErrorKind::ConnectionRefused {
+                    return tr("error.connectionRefused");
+                }
+            }
+            source = err.source();
+        }
+        return tr_args("error.connect", &[&e]);
+    }
+    tr_args("error.request", &[&e])
+}
+
 #[tauri::command]
 async fn test_all_mirror_speed() -> Result<Vec<SpeedTestResult>, String> {
     let client = reqwest::Client::builder()
@@ -1645,7 +5809,8 @@ async fn test_all_mirror_speed() -> Result<Vec<SpeedTestResult>, String> {
         .build()
         .map_err(|e| e.to_string())?;
 
-    let presets = get_all_mirror_presets();
+    let config = internal_get_config().await.ok();
+    let presets = get_visible_mirror_presets(config.as_ref());
     let futures: Vec<_> = presets
         .into_iter()
         .map(|preset| {
@@ -1654,10 +5819,23 @@ async fn test_all_mirror_speed() -> Result<Vec<SpeedTestResult>, String> {
                 let start = SystemTime::now();
                 let res = client.head(&preset.node_url).send().await;
                 let latency = start.elapsed().map(|d| d.as_millis() as i64).unwrap_or(-1);
+                let (success, error, status_code) = match res {
+                    Ok(resp) => {
+                        let status = resp.status();
+                        if status.is_success() || status.is_redirection() {
+                            (true, None, Some(status.as_u16()))
+                        } else {
+                            (false, Some(tr_args("error.httpStatus", &[&status])), Some(status.as_u16()))
+                        }
+                    }
+                    Err(e) => (false, Some(describe_request_error(&e)), e.status().map(|s| s.as_u16())),
+                };
                 SpeedTestResult {
                     mirror_id: preset.id,
                     latency,
-                    success: res.is_ok(),
+                    success,
+                    error,
+                    status_code,
                 }
             }
         })
@@ -1672,74 +5850,600 @@ async fn test_all_mirror_speed() -> Result<Vec<SpeedTestResult>, String> {
     Ok(results)
 }
 
+// 检查指定版本在各镜像上是否存在，避免安装到一半才发现 404
 #[tauri::command]
-async fn get_arch() -> Result<String, String> {
+async fn check_version_availability(version: String) -> Result<Vec<VersionAvailability>, String> {
     let config = internal_get_config().await?;
-    Ok(config.arch)
-}
+    let arch = to_dist_arch(&config.arch);
+    let version = if version.starts_with('v') { version } else { format!("v{}", version) };
 
-#[tauri::command]
-async fn set_arch(arch: String) -> Result<bool, String> {
-    let mut config = internal_get_config().await?;
-    config.arch = arch;
-    set_config(config).await
-}
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())?;
 
-#[tauri::command]
-async fn set_config(new_config: NvmConfig) -> Result<bool, String> {
-    let path = get_settings_path()?;
-    let mut content = format!(
-        "root: {}\npath: {}\nnode_mirror: {}\nnpm_mirror: {}\narch: {}\nclose_action: {}\n",
-        new_config.nvm_path,
-        new_config.nvm_symlink,
-        new_config.node_mirror,
-        new_config.npm_mirror,
-        new_config.arch,
-        new_config.close_action
-    );
-    if let Some(ref prefix) = new_config.global_prefix {
-        content.push_str(&format!("global_prefix: {}\n", prefix));
+    let mut mirrors: Vec<(String, String)> = get_visible_mirror_presets(Some(&config))
+        .into_iter()
+        .map(|p| (p.id, p.node_url))
+        .collect();
+    // 当前配置的自定义镜像也一并检查
+    let current = config.node_mirror.trim_end_matches('/');
+    if !current.is_empty() && !mirrors.iter().any(|(_, url)| url.trim_end_matches('/') == current) {
+        mirrors.push(("custom".to_string(), config.node_mirror.clone()));
     }
-    fs::write(path, content).map_err(|e| e.to_string())?;
-    
-    // 立即应用 npm registry 设置
-    let _ = apply_npm_registry().await;
-    
-    Ok(true)
-}
 
-#[tauri::command]
-async fn select_directory(window: WebviewWindow) -> Result<Option<String>, String> {
-    let (tx, rx) = std::sync::mpsc::channel();
-    window.dialog().file().pick_folder(move |folder| {
-        let _ = tx.send(folder.map(|f| f.to_string()));
-    });
-    Ok(rx.recv().unwrap_or(None))
+    let futures: Vec<_> = mirrors
+        .into_iter()
+        .map(|(id, node_url)| {
+            let client = client.clone();
+            let url = build_download_url(&node_url, &version, arch);
+            async move {
+                let status_code = client.head(&url).send().await.ok().map(|r| r.status().as_u16());
+                VersionAvailability {
+                    mirror_id: id,
+                    available: status_code.map(|c| (200..300).contains(&c)).unwrap_or(false),
+                    status_code,
+                }
+            }
+        })
+        .collect();
+
+    Ok(join_all(futures).await)
 }
 
+// 比较各镜像 index.json 中的最新版本与官方源，结果缓存 10 分钟
 #[tauri::command]
-async fn get_total_size() -> Result<u64, String> {
-    let config = internal_get_config().await?;
-    let nvm_path = Path::new(&config.nvm_path);
-    if !nvm_path.exists() {
-        return Ok(0);
-    }
+async fn check_mirror_freshness() -> Result<Vec<MirrorFreshness>, String> {
+    // 官方源始终参与比较作为基准，但只返回可见的镜像
+    let config = internal_get_config().await.ok();
+    let visible: Vec<String> = get_visible_mirror_presets(config.as_ref()).into_iter().map(|p| p.id).collect();
 
-    let mut total = 0;
-    if let Ok(entries) = fs::read_dir(nvm_path) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() && entry.file_name().to_string_lossy().starts_with('v') {
-                total += get_dir_size(&path);
-            }
+    let cache_key = "mirror_freshness";
+    if let Some(cached) = get_from_cache_with_ttl(cache_key, 600).await {
+        if let Ok(results) = serde_json::from_value::<Vec<MirrorFreshness>>(cached) {
+            return Ok(results.into_iter().filter(|r| visible.contains(&r.mirror_id)).collect());
         }
     }
-    Ok(total)
-}
 
-#[tauri::command]
-async fn validate_path(path: String) -> Result<serde_json::Value, String> {
-    let p = Path::new(&path);
+    #[derive(Deserialize)]
+    struct IndexEntry {
+        version: String,
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let presets = get_all_mirror_presets();
+    let futures: Vec<_> = presets
+        .iter()
+        .map(|preset| {
+            let client = client.clone();
+            let url = format!("{}/index.json", preset.node_url.trim_end_matches('/'));
+            async move {
+                let response = client.get(&url).header("User-Agent", "Mozilla/5.0").send().await.ok()?;
+                let entries = response.json::<Vec<IndexEntry>>().await.ok()?;
+                Some(entries.into_iter().map(|e| e.version).collect::<Vec<String>>())
+            }
+        })
+        .collect();
+    let indexes = join_all(futures).await;
+
+    let official = presets
+        .iter()
+        .position(|p| p.id == "official")
+        .and_then(|i| indexes[i].clone());
+
+    let results: Vec<MirrorFreshness> = presets
+        .iter()
+        .zip(indexes.iter())
+        .map(|(preset, index)| {
+            let newest = index.as_ref().and_then(|versions| versions.iter().max_by(|a, b| compare_semver(a, b)).cloned());
+            let behind = match (&official, &newest) {
+                (Some(official), Some(newest)) => Some(
+                    official.iter().filter(|v| compare_semver(v, newest) == std::cmp::Ordering::Greater).count() as u32,
+                ),
+                _ => None,
+            };
+            MirrorFreshness {
+                mirror_id: preset.id.clone(),
+                success: newest.is_some(),
+                newest,
+                behind,
+            }
+        })
+        .collect();
+
+    if let Ok(json_val) = serde_json::to_value(&results) {
+        save_to_cache(cache_key, json_val).await;
+    }
+    Ok(results.into_iter().filter(|r| visible.contains(&r.mirror_id)).collect())
+}
+
+#[tauri::command]
+async fn get_arch() -> Result<String, String> {
+    let config = internal_get_config().await?;
+    Ok(config.arch)
+}
+
+#[tauri::command]
+async fn set_arch(arch: String) -> Result<bool, String> {
+    update_settings_file(&get_settings_path()?, &[("arch", Some(arch))])?;
+    Ok(true)
+}
+
+const CLOSE_ACTIONS: [&str; 3] = ["ask", "quit", "hide"];
+
+#[tauri::command]
+async fn get_close_action() -> Result<String, String> {
+    Ok(internal_get_config().await?.close_action)
+}
+
+// 只修改 close_action 一个键，不重写其他配置
+#[tauri::command]
+async fn set_close_action(action: String) -> Result<bool, String> {
+    if !CLOSE_ACTIONS.contains(&action.as_str()) {
+        return Err(tr_args("error.invalidCloseAction", &[&action]));
+    }
+    update_settings_file(&get_settings_path()?, &[("close_action", Some(action))])?;
+    Ok(true)
+}
+
+// settings.txt 的写入锁，所有写入都经过 update_settings_file，避免并发写入互相覆盖
+static SETTINGS_LOCK: Mutex<()> = Mutex::new(());
+
+// 将 updates 合并进 settings.txt 内容：已有的键原地替换，值为 None 的键删除，新键追加到末尾
+// 其他键（包括 nvm 自身或其他工具写入的）和注释保持不变
+fn merge_settings_content(content: &str, updates: &[(&str, Option<String>)]) -> String {
+    let mut applied = vec![false; updates.len()];
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        let key = if trimmed.starts_with('#') {
+            None
+        } else {
+            trimmed.split_once(':').map(|(k, _)| k.trim().to_lowercase())
+        };
+        let matched = key.and_then(|k| updates.iter().position(|(name, _)| name.eq_ignore_ascii_case(&k)));
+        match matched {
+            Some(i) => {
+                // 重复的键只保留第一处
+                if !applied[i] {
+                    applied[i] = true;
+                    if let Some(ref value) = updates[i].1 {
+                        lines.push(format!("{}: {}", updates[i].0, value));
+                    }
+                }
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+
+    for (i, (name, value)) in updates.iter().enumerate() {
+        if !applied[i] {
+            if let Some(value) = value {
+                lines.push(format!("{}: {}", name, value));
+            }
+        }
+    }
+
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+fn update_settings_file(path: &Path, updates: &[(&str, Option<String>)]) -> Result<(), String> {
+    let _guard = SETTINGS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let content = fs::read_to_string(path).unwrap_or_default();
+    fs::write(path, merge_settings_content(&content, updates)).map_err(|e| e.to_string())
+}
+
+// NvmConfig 中需要持久化到 settings.txt 的全部键
+fn config_to_settings(config: &NvmConfig) -> Vec<(&'static str, Option<String>)> {
+    vec![
+        ("root", Some(config.nvm_path.clone())),
+        ("path", Some(config.nvm_symlink.clone())),
+        ("node_mirror", Some(config.node_mirror.clone())),
+        ("npm_mirror", Some(config.npm_mirror.clone())),
+        ("arch", Some(config.arch.clone())),
+        ("close_action", Some(config.close_action.clone())),
+        ("global_prefix", config.global_prefix.clone()),
+        ("auto_switch_mode", Some(config.auto_switch_mode.clone())),
+        ("keep_archives", if config.keep_archives { Some("true".to_string()) } else { None }),
+        ("locale", Some(config.locale.clone())),
+        ("hidden_mirrors", if config.hidden_mirrors.is_empty() { None } else { Some(config.hidden_mirrors.join(",")) }),
+        ("custom_mirrors_only", if config.custom_mirrors_only { Some("true".to_string()) } else { None }),
+        ("download_stall_timeout", if config.download_stall_timeout == default_stall_timeout() { None } else { Some(config.download_stall_timeout.to_string()) }),
+        ("download_retries", if config.download_retries == default_download_retries() { None } else { Some(config.download_retries.to_string()) }),
+        ("download_retry_backoff", if config.download_retry_backoff == default_retry_backoff() { None } else { Some(config.download_retry_backoff.to_string()) }),
+        ("package_manager", if config.package_manager == default_package_manager() { None } else { Some(config.package_manager.clone()) }),
+    ]
+}
+
+// 规范化镜像地址：去掉首尾空白，只允许 http(s)，并保证恰好一个结尾斜杠
+// （下载和版本列表直接拼接 index.json 等路径）。空字符串表示使用默认镜像
+fn normalize_mirror_url(raw: &str) -> Result<String, String> {
+    let url = raw.trim();
+    if url.is_empty() {
+        return Ok(String::new());
+    }
+    let lower = url.to_ascii_lowercase();
+    let rest = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))
+        .ok_or_else(|| tr_args("error.mirrorScheme", &[&url]))?;
+    if rest.trim_matches('/').is_empty() || url.chars().any(char::is_whitespace) {
+        return Err(tr_args("error.invalidMirror", &[&url]));
+    }
+    Ok(format!("{}/", url.trim_end_matches('/')))
+}
+
+#[tauri::command]
+async fn set_config<R: Runtime>(app: AppHandle<R>, mut new_config: NvmConfig) -> Result<bool, String> {
+    new_config.node_mirror = normalize_mirror_url(&new_config.node_mirror)?;
+    new_config.npm_mirror = normalize_mirror_url(&new_config.npm_mirror)?;
+    if !PACKAGE_MANAGERS.contains(&new_config.package_manager.as_str()) {
+        return Err(tr_args("error.unsupportedPackageManager", &[&new_config.package_manager]));
+    }
+
+    // 只对发生变化的路径做同步盘检查，避免每次保存都提示
+    let mut nvm_path_changed = true;
+    let mut node_mirror_changed = true;
+    if let Ok(old) = internal_get_config().await {
+        nvm_path_changed = new_config.nvm_path != old.nvm_path;
+        node_mirror_changed = new_config.node_mirror != old.node_mirror;
+        let mut changed: Vec<&str> = Vec::new();
+        if new_config.nvm_path != old.nvm_path {
+            changed.push(&new_config.nvm_path);
+        }
+        if new_config.nvm_symlink != old.nvm_symlink {
+            changed.push(&new_config.nvm_symlink);
+        }
+        if let Some(ref prefix) = new_config.global_prefix {
+            if old.global_prefix.as_ref() != Some(prefix) {
+                changed.push(prefix);
+            }
+        }
+        emit_sync_risk_warnings(&app, &changed);
+    }
+
+    let path = get_settings_path()?;
+    update_settings_file(&path, &config_to_settings(&new_config))?;
+    set_current_locale(&new_config.locale);
+
+    // 安装目录变化后重新监听新目录
+    if nvm_path_changed {
+        let _ = start_version_watcher(&app, &new_config.nvm_path);
+    }
+
+    // 版本列表缓存属于旧镜像，切换后重新获取
+    if node_mirror_changed {
+        remove_from_cache(&AVAILABLE_VERSIONS_CACHE_KEYS).await;
+    }
+    
+    // 立即应用 npm registry 设置
+    let _ = apply_npm_registry().await;
+    
+    Ok(true)
+}
+
+// --- 同步盘检测 ---
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncRisk {
+    pub path: String,
+    #[serde(rename = "atRisk")]
+    pub at_risk: bool,
+    pub provider: Option<String>,
+    pub warning: Option<String>,
+}
+
+// 通过同步客户端设置的环境变量和常见的同步目录名判断
+fn detect_sync_provider_by_path(path: &str) -> Option<String> {
+    let target = normalize_dir(path);
+    for var in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+        if let Ok(root) = env::var(var) {
+            let root = normalize_dir(&root);
+            if !root.is_empty() && (target == root || target.starts_with(&format!("{}\\", root))) {
+                return Some("OneDrive".to_string());
+            }
+        }
+    }
+    let known = [
+        ("onedrive", "OneDrive"),
+        ("dropbox", "Dropbox"),
+        ("google drive", "Google Drive"),
+        ("my drive", "Google Drive"),
+        ("iclouddrive", "iCloud Drive"),
+        ("icloud drive", "iCloud Drive"),
+        ("坚果云", "Nutstore"),
+        ("nutstore", "Nutstore"),
+    ];
+    Path::new(&target).components().find_map(|c| {
+        let name = c.as_os_str().to_string_lossy().to_lowercase();
+        known
+            .iter()
+            .find(|(pattern, _)| name == *pattern || name.starts_with(&format!("{} -", pattern)))
+            .map(|(_, provider)| provider.to_string())
+    })
+}
+
+// 云文件占位符属性：按需下载 / 固定 / 取消固定
+#[cfg(windows)]
+fn has_cloud_file_attributes(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const CLOUD_ATTRIBUTES: u32 = 0x0040_0000 | 0x0008_0000 | 0x0010_0000;
+    // 路径可能还不存在，检查最近的已存在的上级目录
+    path.ancestors()
+        .find_map(|p| fs::metadata(p).ok())
+        .map(|m| m.file_attributes() & CLOUD_ATTRIBUTES != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+fn has_cloud_file_attributes(_path: &Path) -> bool {
+    false
+}
+
+#[tauri::command]
+fn check_path_sync_risk(path: String) -> SyncRisk {
+    let provider = detect_sync_provider_by_path(&path).or_else(|| {
+        if has_cloud_file_attributes(Path::new(&path)) {
+            Some("Cloud Files".to_string())
+        } else {
+            None
+        }
+    });
+    let warning = provider.as_ref().map(|p| tr_args("path.syncRisk", &[&path, p]));
+    SyncRisk {
+        at_risk: provider.is_some(),
+        path,
+        provider,
+        warning,
+    }
+}
+
+// 不阻止保存，只通过 path:sync-risk 事件提醒前端
+fn emit_sync_risk_warnings<R: Runtime>(app: &AppHandle<R>, paths: &[&str]) {
+    for path in paths.iter().filter(|p| !p.is_empty()) {
+        let risk = check_path_sync_risk(path.to_string());
+        if risk.at_risk {
+            let _ = app.emit("path:sync-risk", &risk);
+        }
+    }
+}
+
+#[tauri::command]
+async fn select_directory(window: WebviewWindow) -> Result<Option<String>, String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    window.dialog().file().pick_folder(move |folder| {
+        let _ = tx.send(folder.map(|f| f.to_string()));
+    });
+    Ok(rx.recv().unwrap_or(None))
+}
+
+// 在阻塞线程中逐个统计版本目录，每完成一个发送 size:progress（累计值）。
+// 各目录按 路径+修改时间 复用存储扫描的缓存，未变化时立即返回；
+// 统计进行中时再次调用会等待并共享同一个结果，只有 cancel_total_size 会取消统计
+#[tauri::command]
+async fn get_total_size<R: Runtime>(app: AppHandle<R>, state: tauri::State<'_, StorageScanState>) -> Result<u64, String> {
+    let config = internal_get_config().await?;
+    let nvm_path = PathBuf::from(&config.nvm_path);
+    if !nvm_path.exists() {
+        return Ok(0);
+    }
+
+    let (mut result_rx, started) = {
+        let mut in_flight = state.total_in_flight.lock().unwrap();
+        match in_flight.as_ref() {
+            Some(rx) if rx.borrow().is_none() => (rx.clone(), None),
+            _ => {
+                let (tx, rx) = watch::channel(None);
+                *in_flight = Some(rx.clone());
+                let cancel = Arc::new(AtomicBool::new(false));
+                *state.total_cancel_flag.lock().unwrap() = cancel.clone();
+                (rx, Some((tx, cancel)))
+            }
+        }
+    };
+
+    // 结果在阻塞线程内发送，发起统计的调用方提前返回也不影响其他等待者
+    if let Some((result_tx, cancel)) = started {
+        tauri::async_runtime::spawn_blocking(move || {
+            let total = measure_total_size(&app, &nvm_path, &cancel);
+            let result = if cancel.load(Ordering::SeqCst) { Err(tr("error.sizeCancelled")) } else { Ok(total) };
+            let _ = result_tx.send(Some(result));
+        });
+    }
+
+    let result = result_rx
+        .wait_for(|result| result.is_some())
+        .await
+        .map_err(|e| e.to_string())?
+        .clone();
+    result.unwrap_or_else(|| Err(tr("error.sizeCancelled")))
+}
+
+fn measure_total_size<R: Runtime>(app: &AppHandle<R>, nvm_path: &Path, cancel: &AtomicBool) -> u64 {
+    let dirs: Vec<(String, PathBuf)> = fs::read_dir(nvm_path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir() && e.file_name().to_string_lossy().starts_with('v'))
+                .filter(|e| !is_unsafe_reparse_point(&e.path(), nvm_path))
+                .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let count = dirs.len();
+    let mut total = 0;
+    for (done, (id, path)) in dirs.into_iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        total += measure_storage_item(app, id, "version".to_string(), path, cancel).size;
+        let _ = app.emit("size:progress", serde_json::json!({
+            "total": total,
+            "done": done + 1,
+            "count": count
+        }));
+    }
+    total
+}
+
+#[tauri::command]
+async fn cancel_total_size(state: tauri::State<'_, StorageScanState>) -> Result<bool, String> {
+    state.total_cancel_flag.lock().unwrap().store(true, Ordering::SeqCst);
+    Ok(true)
+}
+
+// --- 存储占用 ---
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageItem {
+    pub id: String,
+    pub kind: String, // "version" / "npmCache" / "globalPrefix"
+    pub path: String,
+    pub size: u64,
+    pub cached: bool,
+}
+
+fn get_dir_mtime(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn get_npm_cache_path() -> Option<PathBuf> {
+    let output = create_silent_command("npm.cmd")
+        .args(["config", "get", "cache"])
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !value.is_empty() && value != "undefined" {
+        return Some(PathBuf::from(value));
+    }
+    env::var("LOCALAPPDATA").ok().map(|p| PathBuf::from(p).join("npm-cache"))
+}
+
+// 汇总需要统计大小的目录：各个已安装版本、npm 缓存以及共享全局目录
+fn collect_storage_targets(config: &NvmConfig) -> Vec<(String, String, PathBuf)> {
+    let mut targets = Vec::new();
+    if let Ok(entries) = fs::read_dir(&config.nvm_path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() && name.starts_with('v') && !is_unsafe_reparse_point(&path, Path::new(&config.nvm_path)) {
+                targets.push((name, "version".to_string(), path));
+            }
+        }
+    }
+    if let Some(cache) = get_npm_cache_path() {
+        if cache.exists() {
+            targets.push(("npm-cache".to_string(), "npmCache".to_string(), cache));
+        }
+    }
+    if let Some(ref prefix) = config.global_prefix {
+        let prefix_path = PathBuf::from(prefix);
+        if prefix_path.exists() {
+            targets.push(("global-prefix".to_string(), "globalPrefix".to_string(), prefix_path));
+        }
+    }
+    targets
+}
+
+// 按 路径+修改时间 查询缓存，未命中时在阻塞线程中统计并写回缓存
+fn measure_storage_item<R: Runtime>(app: &AppHandle<R>, id: String, kind: String, path: PathBuf, cancel: &AtomicBool) -> StorageItem {
+    let state = app.state::<StorageScanState>();
+    let key = path.to_string_lossy().to_string();
+    let mtime = get_dir_mtime(&path);
+
+    let cached = state.size_cache.lock().unwrap().get(&key).copied();
+    if let Some((cached_mtime, size)) = cached {
+        if cached_mtime == mtime {
+            return StorageItem { id, kind, path: key, size, cached: true };
+        }
+    }
+
+    let size = get_dir_size_with_cancel(&path, cancel);
+    // 被取消时结果不完整，不能写入缓存
+    if !cancel.load(Ordering::Relaxed) {
+        state.size_cache.lock().unwrap().insert(key.clone(), (mtime, size));
+    }
+    StorageItem { id, kind, path: key, size, cached: false }
+}
+
+// 忽略缓存重新统计单个目录（例如在版本目录下装了全局包之后），并写回缓存供下次扫描使用
+#[tauri::command]
+async fn recompute_version_size(path: String, state: tauri::State<'_, StorageScanState>) -> Result<u64, String> {
+    let dir = PathBuf::from(&path);
+    if !dir.is_dir() {
+        return Err(tr("error.pathMissing"));
+    }
+    let (mtime, size) = {
+        let dir = dir.clone();
+        tauri::async_runtime::spawn_blocking(move || (get_dir_mtime(&dir), get_dir_size(&dir)))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    state.size_cache.lock().unwrap().insert(dir.to_string_lossy().to_string(), (mtime, size));
+    Ok(size)
+}
+
+#[tauri::command]
+async fn get_storage_breakdown(
+    window: WebviewWindow,
+    state: tauri::State<'_, StorageScanState>,
+) -> Result<Vec<StorageItem>, String> {
+    let config = internal_get_config().await?;
+
+    // 新的扫描开始时取消上一次未完成的扫描
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut flag = state.cancel_flag.lock().unwrap();
+        flag.store(true, Ordering::SeqCst);
+        *flag = cancel.clone();
+    }
+
+    let targets = collect_storage_targets(&config);
+    let handles: Vec<_> = targets
+        .into_iter()
+        .map(|(id, kind, path)| {
+            let cancel = cancel.clone();
+            let window = window.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let item = measure_storage_item(window.app_handle(), id, kind, path, &cancel);
+                if !cancel.load(Ordering::Relaxed) {
+                    let _ = window.emit("storage:item", &item);
+                }
+                item
+            })
+        })
+        .collect();
+
+    let items: Vec<StorageItem> = join_all(handles).await.into_iter().flatten().collect();
+
+    if cancel.load(Ordering::SeqCst) {
+        return Err(tr("error.storageScanCancelled"));
+    }
+    Ok(items)
+}
+
+#[tauri::command]
+async fn cancel_storage_scan(state: tauri::State<'_, StorageScanState>) -> Result<bool, String> {
+    state.cancel_flag.lock().unwrap().store(true, Ordering::SeqCst);
+    Ok(true)
+}
+
+#[tauri::command]
+async fn validate_path(path: String) -> Result<serde_json::Value, String> {
+    let p = Path::new(&path);
     let valid = p.exists() && p.is_dir();
     Ok(serde_json::json!({ "valid": valid }))
 }
@@ -1785,31 +6489,116 @@ async fn check_nvm_installation() -> Result<NvmInstallStatus, String> {
     })
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NvmInstallCandidate {
+    pub home: String,
+    pub root: String,
+    pub symlink: String,
+    pub version: Option<String>,
+    // 是否为应用当前使用的安装
+    pub current: bool,
+}
+
+// 有 nvm.exe 和 settings.txt 才算有效的 nvm 安装
+fn inspect_nvm_install(home: &Path) -> Option<NvmInstallCandidate> {
+    let nvm_exe = home.join("nvm.exe");
+    if !nvm_exe.is_file() {
+        return None;
+    }
+    let content = fs::read_to_string(home.join("settings.txt")).ok()?;
+    let settings = parse_nvm_settings(&content);
+    let version = create_silent_command(&nvm_exe.to_string_lossy())
+        .arg("version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    let home_str = home.to_string_lossy().to_string();
+    Some(NvmInstallCandidate {
+        current: env::var("NVM_HOME").map(|h| normalize_dir(&h) == normalize_dir(&home_str)).unwrap_or(false),
+        home: home_str,
+        root: settings.nvm_path,
+        symlink: settings.nvm_symlink,
+        version,
+    })
+}
+
+// 在注册表 NVM_HOME 和常见安装位置中查找已有的 nvm
 #[tauri::command]
-async fn get_nvm_latest_release() -> Result<GithubRelease, String> {
-    let cache_key = "nvm_latest_release";
-    if let Some(cached) = get_from_cache(cache_key).await {
-        if let Ok(release) = serde_json::from_value::<GithubRelease>(cached) {
-            return Ok(release);
+async fn discover_nvm_installs() -> Result<Vec<NvmInstallCandidate>, String> {
+    let mut homes: Vec<PathBuf> = Vec::new();
+    for value in [read_registry_env(false, "NVM_HOME"), read_registry_env(true, "NVM_HOME"), env::var("NVM_HOME").ok()]
+        .into_iter()
+        .flatten()
+    {
+        homes.push(PathBuf::from(expand_env_vars(&value)));
+    }
+    for (var, sub) in [("APPDATA", "nvm"), ("ProgramData", "nvm"), ("LOCALAPPDATA", "nvm")] {
+        if let Ok(base) = env::var(var) {
+            homes.push(PathBuf::from(base).join(sub));
         }
     }
+    homes.push(PathBuf::from("C:\\nvm"));
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| e.to_string())?;
-    
-    let response = client
-        .get("https://api.github.com/repos/coreybutler/nvm-windows/releases/latest")
+    let mut seen = Vec::new();
+    let mut found = Vec::new();
+    for home in homes {
+        let key = normalize_dir(&home.to_string_lossy());
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+        if let Some(candidate) = inspect_nvm_install(&home) {
+            found.push(candidate);
+        }
+    }
+    Ok(found)
+}
+
+// 让应用改用指定的 nvm 安装：写入用户环境变量 NVM_HOME / NVM_SYMLINK 并重新加载配置
+#[tauri::command]
+async fn adopt_nvm_install<R: Runtime>(app: AppHandle<R>, home: String) -> Result<NvmConfig, String> {
+    let candidate = inspect_nvm_install(Path::new(&home))
+        .ok_or_else(|| tr_args("error.invalidNvmInstall", &[&home]))?;
+    if candidate.symlink.trim().is_empty() {
+        return Err(tr("error.settingsNoSymlink"));
+    }
+
+    setup_user_environment(&candidate.home, &candidate.symlink)?;
+    apply_nvm_env_to_process(&candidate.home, &candidate.symlink);
+
+    let config = internal_get_config().await?;
+    set_current_locale(&config.locale);
+    let _ = start_version_watcher(&app, &config.nvm_path);
+    let _ = refresh_tray(app).await;
+    Ok(config)
+}
+
+#[tauri::command]
+async fn get_nvm_latest_release() -> Result<GithubRelease, String> {
+    let cache_key = "nvm_latest_release";
+    if let Some(cached) = get_from_cache(cache_key).await {
+        if let Ok(release) = serde_json::from_value::<GithubRelease>(cached) {
+            return Ok(release);
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| e.to_string())?;
+    
+    let response = client
+        .get("https://api.github.com/repos/coreybutler/nvm-windows/releases/latest")
         .header("User-Agent", "nvm-windows-gui")
         .send()
         .await
-        .map_err(|e| format!("请求 GitHub API 失败: {}", e))?;
+        .map_err(|e| tr_args("error.githubRequest", &[&e]))?;
     
     let release: GithubRelease = response
         .json()
         .await
-        .map_err(|e| format!("解析 GitHub 响应失败: {}", e))?;
+        .map_err(|e| tr_args("error.githubParse", &[&e]))?;
     
     if let Ok(json_val) = serde_json::to_value(&release) {
         save_to_cache(cache_key, json_val).await;
@@ -1818,30 +6607,136 @@ async fn get_nvm_latest_release() -> Result<GithubRelease, String> {
     Ok(release)
 }
 
+// nvm:install:progress 事件中的 step 依次为：
+// fetching_release -> selecting_proxy -> downloading -> extracting -> writing_config -> setting_env -> done
 #[tauri::command]
 async fn download_and_install_nvm(
     window: WebviewWindow,
+    state: tauri::State<'_, NvmInstallState>,
     target_dir: String,
     symlink_dir: String,
 ) -> Result<bool, String> {
+    emit_sync_risk_warnings(window.app_handle(), &[&target_dir, &symlink_dir]);
+
+    let (cancel_tx, mut cancel_rx) = broadcast::channel(1);
+    {
+        let mut current = state.cancel_tx.lock().unwrap();
+        if current.is_some() {
+            return Err(tr("error.nvmInstalling"));
+        }
+        *current = Some(cancel_tx);
+    }
+
+    let result = install_nvm_files(&window, &target_dir, &symlink_dir, &mut cancel_rx).await;
+    // 文件阶段结束（成功、失败或取消）后关闭取消通道，之后的配置写入不可中断
+    *state.cancel_tx.lock().unwrap() = None;
+
+    match result {
+        Ok(()) => {}
+        Err(NvmInstallError::Cancelled) => {
+            let _ = window.emit("nvm:install:progress", serde_json::json!({
+                "step": "cancelled",
+                "progress": 0,
+                "status": tr("nvm.cancelled")
+            }));
+            return Err(tr("error.installCancelled"));
+        }
+        Err(NvmInstallError::Failed(e)) => return Err(e),
+    }
+
+    let _ = window.emit("nvm:install:progress", serde_json::json!({
+        "step": "writing_config",
+        "progress": 80,
+        "status": tr("nvm.writingConfig")
+    }));
+    
+    // 创建 settings.txt
+    let settings_content = format!(
+        "root: {}\npath: {}\narch: 64\nnode_mirror: https://npmmirror.com/mirrors/node/\nnpm_mirror: https://npmmirror.com/mirrors/npm/\n",
+        target_dir, symlink_dir
+    );
+    let settings_path = PathBuf::from(&target_dir).join("settings.txt");
+    fs::write(&settings_path, settings_content).map_err(|e| tr_args("error.createSettings", &[&e]))?;
+    
+    let _ = window.emit("nvm:install:progress", serde_json::json!({
+        "step": "setting_env",
+        "progress": 90,
+        "status": tr("nvm.settingEnv")
+    }));
+    
+    // 设置环境变量
+    setup_user_environment(&target_dir, &symlink_dir)?;
+    
+    // 更新当前进程的环境变量，确保后续 nvm 命令可用
+    apply_nvm_env_to_process(&target_dir, &symlink_dir);
+    
+    let _ = window.emit("nvm:install:progress", serde_json::json!({
+        "step": "done",
+        "progress": 100,
+        "status": tr("install.done")
+    }));
+    
+    Ok(true)
+}
+
+#[tauri::command]
+async fn cancel_nvm_install(state: tauri::State<'_, NvmInstallState>) -> Result<bool, String> {
+    match state.cancel_tx.lock().unwrap().as_ref() {
+        Some(tx) => {
+            let _ = tx.send(());
+            Ok(true)
+        }
+        // 没有进行中的安装，或已进入不可取消的配置阶段
+        None => Ok(false),
+    }
+}
+
+enum NvmInstallError {
+    Cancelled,
+    Failed(String),
+}
+
+impl From<String> for NvmInstallError {
+    fn from(e: String) -> Self {
+        NvmInstallError::Failed(e)
+    }
+}
+
+impl From<&str> for NvmInstallError {
+    fn from(e: &str) -> Self {
+        NvmInstallError::Failed(e.to_string())
+    }
+}
+
+// 下载并解压 nvm，取消时删除临时 zip 和本次解压出的文件
+async fn install_nvm_files(
+    window: &WebviewWindow,
+    target_dir: &str,
+    symlink_dir: &str,
+    cancel_rx: &mut broadcast::Receiver<()>,
+) -> Result<(), NvmInstallError> {
     // 获取最新版本信息
     let _ = window.emit("nvm:install:progress", serde_json::json!({
+        "step": "fetching_release",
         "progress": 5,
-        "status": "正在获取最新版本信息..."
+        "status": tr("nvm.fetchingRelease")
     }));
     
-    let release = get_nvm_latest_release().await.map_err(|e| format!("获取版本失败: {}", e))?;
+    let release = tokio::select! {
+        r = get_nvm_latest_release() => r.map_err(|e| tr_args("error.fetchRelease", &[&e]))?,
+        _ = cancel_rx.recv() => return Err(NvmInstallError::Cancelled),
+    };
     let asset = release.assets
         .iter()
         .find(|a| a.name.contains("noinstall") && a.name.ends_with(".zip"))
-        .ok_or("未找到 nvm-noinstall.zip 下载文件")?;
+        .ok_or_else(|| tr("error.nvmAssetMissing"))?;
     
     // 创建目标目录
-    fs::create_dir_all(&target_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    fs::create_dir_all(target_dir).map_err(|e| tr_args("error.createDir", &[&e]))?;
     // NVM_SYMLINK 目录不能提前创建为真实目录，否则 nvm use 会失败
     // nvm-windows 会在切换版本时自动创建该符号链接
     // 如果该路径已经是真实文件夹，尝试清理它（如果它是空的）
-    let symlink_path = Path::new(&symlink_dir);
+    let symlink_path = Path::new(symlink_dir);
     if symlink_path.exists() && symlink_path.is_dir() && !symlink_path.is_symlink() {
         if let Ok(entries) = fs::read_dir(symlink_path) {
             if entries.count() == 0 {
@@ -1850,24 +6745,20 @@ async fn download_and_install_nvm(
         }
     }
 
-    // 代理列表 (加速 GitHub 下载)
-    let proxy_prefixes = vec![
-        "https://ghp.ci/",
-        "https://gh-proxy.com/",
-        "https://mirror.ghproxy.com/",
-        "https://ghproxy.net/",
-    ];
+    let proxy_prefixes = GITHUB_PROXY_PREFIXES;
 
-    let mut response = None;
     let mut last_error = String::new();
+    let mut verified = false;
+    let temp_path = PathBuf::from(target_dir).join("nvm-noinstall.zip");
 
-    // 尝试所有加速代理
+    // 尝试所有加速代理，下载后校验文件，无效则换下一个代理
     for (i, prefix) in proxy_prefixes.iter().enumerate() {
         let download_url = format!("{}{}", prefix, asset.browser_download_url);
         
         let _ = window.emit("nvm:install:progress", serde_json::json!({
+            "step": "selecting_proxy",
             "progress": 10 + (i * 2) as u32,
-            "status": format!("正在重试加速代理 {}/{} ...", i + 1, proxy_prefixes.len())
+            "status": tr_with("nvm.selectingProxy", &format!("{}/{}", i + 1, proxy_prefixes.len()))
         }));
 
         let client = reqwest::Client::builder()
@@ -1876,38 +6767,124 @@ async fn download_and_install_nvm(
             .build()
             .map_err(|e: reqwest::Error| e.to_string())?;
 
-        match client
+        let request = client
             .get(&download_url)
             .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-            .send()
-            .await 
-        {
-            Ok(res) if res.status().is_success() => {
-                response = Some(res);
-                break;
+            .send();
+        let response = tokio::select! {
+            r = request => r,
+            _ = cancel_rx.recv() => {
+                let _ = cleanup_if_empty(Path::new(target_dir));
+                return Err(NvmInstallError::Cancelled);
             }
+        };
+        let response = match response {
+            Ok(res) if res.status().is_success() => res,
             Ok(res) => {
-                last_error = format!("代理 {} 返回错误码: {}", prefix, res.status());
+                last_error = tr_args("error.proxyStatus", &[&prefix, &res.status()]);
+                continue;
+            }
+            Err(e) => {
+                last_error = tr_args("error.proxyConnect", &[&prefix, &e]);
+                continue;
+            }
+        };
+
+        match download_nvm_zip(window, response, &temp_path, cancel_rx).await {
+            Ok(()) => {}
+            Err(NvmInstallError::Cancelled) => {
+                let _ = fs::remove_file(&temp_path);
+                let _ = cleanup_if_empty(Path::new(target_dir));
+                return Err(NvmInstallError::Cancelled);
+            }
+            Err(NvmInstallError::Failed(e)) => {
+                last_error = tr_args("error.proxyDownload", &[&prefix, &e]);
+                let _ = fs::remove_file(&temp_path);
+                continue;
+            }
+        }
+
+        match verify_nvm_zip(&temp_path, asset.size) {
+            Ok(()) => {
+                verified = true;
+                break;
             }
             Err(e) => {
-                last_error = format!("代理 {} 连接失败: {}", prefix, e);
+                last_error = tr_args("error.proxyInvalidFile", &[&prefix, &e]);
+                let _ = fs::remove_file(&temp_path);
             }
         }
     }
 
-    let response = response.ok_or_else(|| format!("所有加速代理均失效，最后一次错误: {}", last_error))?;
+    if !verified {
+        return Err(tr_args("error.allProxiesFailed", &[&last_error]).into());
+    }
+    
+    let _ = window.emit("nvm:install:progress", serde_json::json!({
+        "step": "extracting",
+        "progress": 65,
+        "status": tr("nvm.extracting")
+    }));
+    
+    // 解压文件，记录新创建的文件，取消时逐个删除
+    let zip_file = File::open(&temp_path).map_err(|e| tr_args("error.openZip", &[&e]))?;
+    let mut archive = ZipArchive::new(zip_file).map_err(|e| tr_args("error.readZip", &[&e]))?;
+    let mut extracted: Vec<PathBuf> = Vec::new();
+    
+    for i in 0..archive.len() {
+        if cancel_rx.try_recv().is_ok() {
+            for path in extracted.iter().rev() {
+                let _ = fs::remove_file(path);
+            }
+            let _ = fs::remove_file(&temp_path);
+            let _ = cleanup_if_empty(Path::new(target_dir));
+            return Err(NvmInstallError::Cancelled);
+        }
+        let mut file = archive.by_index(i).map_err(|e| tr_args("error.extract", &[&e]))?;
+        let outpath = PathBuf::from(target_dir).join(file.name());
+        
+        if file.name().ends_with('/') {
+            fs::create_dir_all(&outpath).ok();
+        } else {
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent).ok();
+            }
+            if !outpath.exists() {
+                extracted.push(outpath.clone());
+            }
+            let mut outfile = File::create(&outpath).map_err(|e| tr_args("error.createFile", &[&e]))?;
+            std::io::copy(&mut file, &mut outfile).map_err(|e| tr_args("error.write", &[&e]))?;
+        }
+    }
     
+    // 删除临时 zip 文件
+    fs::remove_file(&temp_path).ok();
+    Ok(())
+}
+
+// 将响应内容写入临时文件并发送下载进度
+async fn download_nvm_zip(
+    window: &WebviewWindow,
+    response: reqwest::Response,
+    temp_path: &Path,
+    cancel_rx: &mut broadcast::Receiver<()>,
+) -> Result<(), NvmInstallError> {
     let total_size = response.content_length().unwrap_or(0);
     let mut downloaded: u64 = 0;
     
-    // 保存到临时文件
-    let temp_path = PathBuf::from(&target_dir).join("nvm-noinstall.zip");
-    let mut file = File::create(&temp_path).map_err(|e| format!("创建临时文件失败: {}", e))?;
+    let mut file = File::create(temp_path).map_err(|e| tr_args("error.createTempFile", &[&e]))?;
     
     let mut stream = response.bytes_stream();
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("下载错误: {}", e))?;
-        file.write_all(&chunk).map_err(|e| format!("写入文件失败: {}", e))?;
+    loop {
+        let chunk = tokio::select! {
+            c = stream.next() => match c {
+                Some(c) => c,
+                None => break,
+            },
+            _ = cancel_rx.recv() => return Err(NvmInstallError::Cancelled),
+        };
+        let chunk = chunk.map_err(|e| tr_args("error.download", &[&e]))?;
+        file.write_all(&chunk).map_err(|e| tr_args("error.writeFile", &[&e]))?;
         downloaded += chunk.len() as u64;
         
         let progress = if total_size > 0 {
@@ -1923,224 +6900,770 @@ async fn download_and_install_nvm(
         };
 
         let _ = window.emit("nvm:install:progress", serde_json::json!({
+            "step": "downloading",
             "progress": progress,
-            "status": format!("正在下载... {}", status_percent)
+            "status": tr_with("nvm.downloading", &status_percent)
         }));
     }
-    drop(file);
-    
-    let _ = window.emit("nvm:install:progress", serde_json::json!({
-        "progress": 65,
-        "status": "正在解压文件..."
-    }));
-    
-    // 解压文件
-    let zip_file = File::open(&temp_path).map_err(|e| format!("打开 zip 文件失败: {}", e))?;
-    let mut archive = ZipArchive::new(zip_file).map_err(|e| format!("读取 zip 文件失败: {}", e))?;
-    
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i).map_err(|e| format!("解压失败: {}", e))?;
-        let outpath = PathBuf::from(&target_dir).join(file.name());
-        
-        if file.name().ends_with('/') {
-            fs::create_dir_all(&outpath).ok();
-        } else {
-            if let Some(parent) = outpath.parent() {
-                fs::create_dir_all(parent).ok();
-            }
-            let mut outfile = File::create(&outpath).map_err(|e| format!("创建文件失败: {}", e))?;
-            std::io::copy(&mut file, &mut outfile).map_err(|e| format!("写入失败: {}", e))?;
-        }
+    Ok(())
+}
+
+// 校验下载的 nvm-noinstall.zip：大小与 GitHub 元数据一致、是有效的 zip 且包含 nvm.exe
+// 部分代理会以 200 状态码返回错误页面，必须在解压前识别出来
+fn verify_nvm_zip(path: &Path, expected_size: u64) -> Result<(), String> {
+    let actual_size = fs::metadata(path).map(|m| m.len()).map_err(|e| e.to_string())?;
+    if expected_size > 0 && actual_size != expected_size {
+        return Err(tr_args("error.sizeMismatch", &[&expected_size, &actual_size]));
     }
-    
-    // 删除临时 zip 文件
-    fs::remove_file(&temp_path).ok();
-    
-    let _ = window.emit("nvm:install:progress", serde_json::json!({
-        "progress": 80,
-        "status": "正在创建配置文件..."
-    }));
-    
-    // 创建 settings.txt
-    let settings_content = format!(
-        "root: {}\npath: {}\narch: 64\nnode_mirror: https://npmmirror.com/mirrors/node/\nnpm_mirror: https://npmmirror.com/mirrors/npm/\n",
-        target_dir, symlink_dir
-    );
-    let settings_path = PathBuf::from(&target_dir).join("settings.txt");
-    fs::write(&settings_path, settings_content).map_err(|e| format!("创建配置文件失败: {}", e))?;
-    
-    let _ = window.emit("nvm:install:progress", serde_json::json!({
-        "progress": 90,
-        "status": "正在配置环境变量..."
-    }));
-    
-    // 设置环境变量
-    setup_user_environment(&target_dir, &symlink_dir)?;
-    
-    // 更新当前进程的环境变量，确保后续 nvm 命令可用
-    env::set_var("NVM_HOME", &target_dir);
-    env::set_var("NVM_SYMLINK", &symlink_dir);
+
+    let file = File::open(path).map_err(|e| tr_args("error.openZip", &[&e]))?;
+    let archive = ZipArchive::new(file).map_err(|e| tr_args("error.invalidZip", &[&e]))?;
+    let has_nvm_exe = archive.file_names().any(|name| {
+        let name = name.to_lowercase();
+        name == "nvm.exe" || name.ends_with("/nvm.exe")
+    });
+    if !has_nvm_exe {
+        return Err(tr("error.nvmExeMissing"));
+    }
+    Ok(())
+}
+
+fn apply_nvm_env_to_process(nvm_home: &str, nvm_symlink: &str) {
+    env::set_var("NVM_HOME", nvm_home);
+    env::set_var("NVM_SYMLINK", nvm_symlink);
     if let Ok(current_path) = env::var("Path") {
         let mut paths: Vec<String> = env::split_paths(&current_path).map(|p| p.to_string_lossy().to_string()).collect();
-        if !paths.iter().any(|p| p.eq_ignore_ascii_case(&target_dir)) {
-            paths.push(target_dir.clone());
+        if !paths.iter().any(|p| p.eq_ignore_ascii_case(nvm_home)) {
+            paths.push(nvm_home.to_string());
         }
-        if !paths.iter().any(|p| p.eq_ignore_ascii_case(&symlink_dir)) {
-            paths.push(symlink_dir.clone());
+        if !paths.iter().any(|p| p.eq_ignore_ascii_case(nvm_symlink)) {
+            paths.push(nvm_symlink.to_string());
         }
         if let Ok(new_path) = env::join_paths(paths) {
             env::set_var("Path", new_path);
         }
     }
-    
-    let _ = window.emit("nvm:install:progress", serde_json::json!({
-        "progress": 100,
-        "status": "安装完成"
-    }));
-    
-    Ok(true)
 }
 
 fn setup_user_environment(nvm_home: &str, nvm_symlink: &str) -> Result<(), String> {
     let hkcu = RegKey::predef(HKEY_CURRENT_USER);
     let env_key = hkcu
         .open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)
-        .map_err(|e| format!("打开注册表失败: {}", e))?;
+        .map_err(|e| tr_args("error.openRegistry", &[&e]))?;
     
-    // 设置 NVM_HOME
-    env_key.set_value("NVM_HOME", &nvm_home)
-        .map_err(|e| format!("设置 NVM_HOME 失败: {}", e))?;
-    
-    // 设置 NVM_SYMLINK
-    env_key.set_value("NVM_SYMLINK", &nvm_symlink)
-        .map_err(|e| format!("设置 NVM_SYMLINK 失败: {}", e))?;
+    // 记录原值，任何一步写入失败都恢复，避免环境变量只设置了一半
+    let names = TRACKED_ENV_VARS;
+    let previous: Vec<Option<String>> = names.iter().map(|n| env_key.get_value::<String, _>(n).ok()).collect();
+
+    let write_result = (|| -> Result<(), String> {
+        // 设置 NVM_HOME
+        env_key.set_value("NVM_HOME", &nvm_home)
+            .map_err(|e| tr_args("error.setNvmHome", &[&e]))?;
+        
+        // 设置 NVM_SYMLINK
+        env_key.set_value("NVM_SYMLINK", &nvm_symlink)
+            .map_err(|e| tr_args("error.setNvmSymlink", &[&e]))?;
+        
+        // 更新 PATH
+        let current_path = previous[2].clone().unwrap_or_default();
+        let mut paths: Vec<&str> = current_path.split(';').collect();
+        
+        // 添加 NVM_HOME 和 NVM_SYMLINK 到 PATH (如果不存在)
+        if !paths.iter().any(|p| p.eq_ignore_ascii_case(nvm_home)) {
+            paths.push(nvm_home);
+        }
+        if !paths.iter().any(|p| p.eq_ignore_ascii_case(nvm_symlink)) {
+            paths.push(nvm_symlink);
+        }
+        
+        let new_path = paths.join(";");
+        env_key.set_value("Path", &new_path)
+            .map_err(|e| tr_args("error.setPath", &[&e]))
+    })();
+
+    if let Err(e) = write_result {
+        for (name, value) in names.iter().zip(previous.iter()) {
+            match value {
+                Some(v) => { let _ = env_key.set_value(name, v); }
+                None => { let _ = env_key.delete_value(name); }
+            }
+        }
+        return Err(e);
+    }
+    record_env_change("setup_user_environment", &previous);
     
-    // 更新 PATH
-    let current_path: String = env_key.get_value("Path").unwrap_or_default();
-    let mut paths: Vec<&str> = current_path.split(';').collect();
+    // 通知系统环境变量已更改
+    notify_env_change_in_background();
     
-    // 添加 NVM_HOME 和 NVM_SYMLINK 到 PATH (如果不存在)
-    if !paths.iter().any(|p| p.eq_ignore_ascii_case(nvm_home)) {
-        paths.push(nvm_home);
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnvValueChange {
+    pub name: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnvChange {
+    pub source: String, // 触发修改的操作，例如 "setup_user_environment"
+    pub timestamp: String,
+    #[serde(rename = "addedPaths")]
+    pub added_paths: Vec<String>,
+    #[serde(rename = "removedPaths")]
+    pub removed_paths: Vec<String>,
+    pub changed: Vec<EnvValueChange>,
+}
+
+const ENV_CHANGE_HISTORY_LIMIT: usize = 5;
+const TRACKED_ENV_VARS: [&str; 3] = ["NVM_HOME", "NVM_SYMLINK", "Path"];
+
+fn snapshot_user_env() -> Vec<Option<String>> {
+    TRACKED_ENV_VARS.iter().map(|name| read_registry_env(false, name)).collect()
+}
+
+// 比较修改前后的用户环境变量：PATH 按条目比较（不区分大小写），其余变量比较整体取值
+fn diff_env_snapshots(before: &[Option<String>], after: &[Option<String>]) -> (Vec<String>, Vec<String>, Vec<EnvValueChange>) {
+    let split = |v: &Option<String>| -> Vec<String> {
+        v.as_deref()
+            .unwrap_or_default()
+            .split(';')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect()
+    };
+    let contains = |list: &[String], item: &str| list.iter().any(|p| p.eq_ignore_ascii_case(item));
+
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for (i, name) in TRACKED_ENV_VARS.iter().enumerate() {
+        if *name == "Path" {
+            let (old, new) = (split(&before[i]), split(&after[i]));
+            added.extend(new.iter().filter(|p| !contains(&old, p)).cloned());
+            removed.extend(old.iter().filter(|p| !contains(&new, p)).cloned());
+        } else if before[i] != after[i] {
+            changed.push(EnvValueChange {
+                name: name.to_string(),
+                before: before[i].clone(),
+                after: after[i].clone(),
+            });
+        }
     }
-    if !paths.iter().any(|p| p.eq_ignore_ascii_case(nvm_symlink)) {
-        paths.push(nvm_symlink);
+    (added, removed, changed)
+}
+
+// 与修改前的快照比较并记录到应用状态，没有实际变化时不记录
+fn record_env_change(source: &str, before: &[Option<String>]) {
+    let (added_paths, removed_paths, changed) = diff_env_snapshots(before, &snapshot_user_env());
+    if added_paths.is_empty() && removed_paths.is_empty() && changed.is_empty() {
+        return;
     }
-    
-    let new_path = paths.join(";");
-    env_key.set_value("Path", &new_path)
-        .map_err(|e| format!("设置 PATH 失败: {}", e))?;
-    
-    // 通知系统环境变量已更改
-    #[cfg(windows)]
-    unsafe {
-        use std::ffi::OsStr;
-        use std::os::windows::ffi::OsStrExt;
-        use windows_sys::Win32::UI::WindowsAndMessaging::{SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE};
-        
-        let param: Vec<u16> = OsStr::new("Environment").encode_wide().chain(Some(0)).collect();
+    let change = EnvChange {
+        source: source.to_string(),
+        timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        added_paths,
+        removed_paths,
+        changed,
+    };
+    let _ = update_app_state(|state| {
+        state.env_changes.insert(0, change);
+        state.env_changes.truncate(ENV_CHANGE_HISTORY_LIMIT);
+    });
+}
+
+#[tauri::command]
+async fn get_last_env_change() -> Result<Option<EnvChange>, String> {
+    Ok(load_app_state().env_changes.into_iter().next())
+}
+
+#[tauri::command]
+async fn get_env_change_history() -> Result<Vec<EnvChange>, String> {
+    Ok(load_app_state().env_changes)
+}
+
+// 广播 WM_SETTINGCHANGE 通知资源管理器等进程重新读取环境变量。
+// 有程序未响应时会超时失败，此时用更长的超时重试一次
+#[cfg(windows)]
+fn notify_env_change() -> bool {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE};
+
+    let param: Vec<u16> = OsStr::new("Environment").encode_wide().chain(Some(0)).collect();
+    [5000, 15000].iter().any(|&timeout| unsafe {
         SendMessageTimeoutW(
             HWND_BROADCAST,
             WM_SETTINGCHANGE,
             0,
             param.as_ptr() as isize,
             SMTO_ABORTIFHUNG,
-            5000,
+            timeout,
             std::ptr::null_mut(),
-        );
-    }
-    
-    Ok(())
+        ) != 0
+    })
 }
 
-// --- 共享全局包目录 ---
+#[cfg(not(windows))]
+fn notify_env_change() -> bool {
+    false
+}
+
+// 广播在有程序未响应时最长会阻塞约 20 秒，异步命令中放到阻塞线程执行，不等待结果
+fn notify_env_change_in_background() {
+    tauri::async_runtime::spawn_blocking(notify_env_change);
+}
 
+// 用户关闭终端后可手动重新广播，解决修改后新终端仍看不到 node 的问题
 #[tauri::command]
-async fn get_global_prefix() -> Result<Option<String>, String> {
-    let output = create_silent_command("npm.cmd")
-        .args(["config", "get", "prefix"])
-        .output()
-        .map_err(|e| e.to_string())?;
-    
-    if output.status.success() {
-        let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if prefix.is_empty() || prefix == "undefined" {
-            Ok(None)
-        } else {
-            Ok(Some(prefix))
-        }
+async fn broadcast_env_change() -> Result<bool, String> {
+    tauri::async_runtime::spawn_blocking(notify_env_change)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NvmUninstallSummary {
+    #[serde(rename = "backupPath")]
+    pub backup_path: String,
+    #[serde(rename = "removedEnvVars")]
+    pub removed_env_vars: Vec<String>,
+    #[serde(rename = "removedPathEntries")]
+    pub removed_path_entries: Vec<String>,
+    #[serde(rename = "removedSymlink")]
+    pub removed_symlink: bool,
+    #[serde(rename = "removedDirs")]
+    pub removed_dirs: Vec<String>,
+    // 未选择删除 Node 版本时保留下来的版本目录
+    #[serde(rename = "keptVersions")]
+    pub kept_versions: Vec<String>,
+}
+
+// 删除 nvm 目录；keep_versions 为 true 时保留其中的 vX.Y.Z 版本目录，目录为空时才删除目录本身。
+// keep_dirs 中的目录（例如位于 NVM_HOME 内、已单独处理过的版本根目录）及包含它们的子目录不会被删除
+fn remove_nvm_dir(dir: &Path, keep_versions: bool, keep_dirs: &[&Path], removed: &mut Vec<String>, kept: &mut Vec<String>) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    // 防止配置异常时误删整个磁盘
+    if dir.parent().is_none() {
+        return Err(tr_args("error.refuseDeleteRoot", &[&dir.display()]));
+    }
+    if !keep_versions {
+        fs::remove_dir_all(dir).map_err(|e| tr_args("error.delete", &[&dir.display(), &e]))?;
+        removed.push(dir.to_string_lossy().to_string());
+        return Ok(());
+    }
+    let entries = fs::read_dir(dir).map_err(|e| tr_args("error.read", &[&dir.display(), &e]))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() && name.starts_with('v') && name.split('.').count() >= 3 {
+            kept.push(path.to_string_lossy().to_string());
+            continue;
+        }
+        let path_str = path.to_string_lossy();
+        if keep_dirs.iter().any(|keep| is_same_or_nested_path(&keep.to_string_lossy(), &path_str)) {
+            continue;
+        }
+        let result = if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        result.map_err(|e| tr_args("error.delete", &[&path.display(), &e]))?;
+    }
+    if fs::read_dir(dir).map(|mut d| d.next().is_none()).unwrap_or(false) {
+        let _ = fs::remove_dir(dir);
+    }
+    if !dir.exists() {
+        removed.push(dir.to_string_lossy().to_string());
+    }
+    Ok(())
+}
+
+// 卸载时的备份位置：应用数据目录默认就是 NVM_HOME，会随卸载一起删除，因此放到 %LOCALAPPDATA%，
+// 该位置不可用或恰好位于将被删除的目录中时退回系统临时目录
+fn uninstall_backup_root(local_appdata: Option<&str>, removed_dirs: &[&str]) -> PathBuf {
+    local_appdata
+        .map(|dir| PathBuf::from(dir).join("nvm-windows-gui").join("backups"))
+        .filter(|root| !removed_dirs.iter().any(|dir| is_same_or_nested_path(&root.to_string_lossy(), dir)))
+        .unwrap_or_else(|| env::temp_dir().join("nvm-windows-gui-backups"))
+}
+
+// 卸载 nvm-windows：先备份 settings.txt、应用状态和用户环境变量，再删除 NVM_HOME / NVM_SYMLINK 及其 PATH 条目、
+// 符号链接和 nvm 目录。remove_node_versions 为 false 时保留已安装的版本目录。
+// 操作不可撤销，必须传入 confirm = true
+#[tauri::command]
+async fn uninstall_nvm<R: Runtime>(
+    app: AppHandle<R>,
+    state: tauri::State<'_, DownloadState>,
+    remove_node_versions: bool,
+    confirm: bool,
+) -> Result<NvmUninstallSummary, String> {
+    if !confirm {
+        return Err(tr("error.uninstallConfirm"));
+    }
+    if !state.tasks.lock().unwrap().is_empty() {
+        return Err(tr("error.uninstallBusy"));
+    }
+
+    let config = internal_get_config().await?;
+    let nvm_home = get_settings_path()?
+        .parent()
+        .map(|p| p.to_path_buf())
+        .ok_or_else(|| tr("error.nvmHomeUnknown"))?;
+
+    let nvm_home_str = nvm_home.to_string_lossy().to_string();
+    let backup_root = uninstall_backup_root(
+        env::var("LOCALAPPDATA").ok().as_deref(),
+        &[nvm_home_str.as_str(), config.nvm_path.as_str()],
+    );
+    let backup_dir = backup_app_files_to(&backup_root, "uninstall")?;
+    let env_before = snapshot_user_env();
+    let env_backup: serde_json::Map<String, serde_json::Value> = TRACKED_ENV_VARS
+        .iter()
+        .zip(env_before.iter())
+        .map(|(name, value)| (name.to_string(), serde_json::json!(value)))
+        .collect();
+    fs::write(
+        backup_dir.join("env.json"),
+        serde_json::to_string_pretty(&env_backup).map_err(|e| e.to_string())?,
+    )
+    .map_err(|e| tr_args("error.backupEnv", &[&e]))?;
+
+    // 环境变量：安装程序写入的 PATH 条目可能是 %NVM_HOME% 形式，也可能是展开后的路径
+    let env_key = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)
+        .map_err(|e| tr_args("error.openRegistry", &[&e]))?;
+    let mut removed_env_vars = Vec::new();
+    for name in ["NVM_HOME", "NVM_SYMLINK"] {
+        if env_key.delete_value(name).is_ok() {
+            removed_env_vars.push(name.to_string());
+        }
+    }
+    let targets = ["%NVM_HOME%", "%NVM_SYMLINK%", nvm_home_str.as_str(), config.nvm_symlink.as_str()];
+    let current_path: String = env_key.get_value("Path").unwrap_or_default();
+    let (kept_paths, removed_path_entries): (Vec<&str>, Vec<&str>) = current_path
+        .split(';')
+        .filter(|p| !p.trim().is_empty())
+        .partition(|p| !targets.iter().any(|t| normalize_dir(p) == normalize_dir(t)));
+    if !removed_path_entries.is_empty() {
+        env_key
+            .set_value("Path", &kept_paths.join(";"))
+            .map_err(|e| tr_args("error.setPath", &[&e]))?;
+    }
+    record_env_change("uninstall_nvm", &env_before);
+    notify_env_change_in_background();
+
+    let symlink = Path::new(&config.nvm_symlink);
+    let removed_symlink = fs::symlink_metadata(symlink).map(|m| m.file_type().is_symlink()).unwrap_or(false)
+        && (fs::remove_dir(symlink).is_ok() || fs::remove_file(symlink).is_ok());
+
+    let mut removed_dirs = Vec::new();
+    let mut kept_versions = Vec::new();
+    let nvm_root = PathBuf::from(&config.nvm_path);
+    remove_nvm_dir(&nvm_root, !remove_node_versions, &[], &mut removed_dirs, &mut kept_versions)?;
+    if normalize_dir(&nvm_home_str) != normalize_dir(&config.nvm_path) {
+        // 版本根目录可能位于 NVM_HOME 之内（例如 C:\nvm\versions），保留版本时不能再次删除
+        remove_nvm_dir(&nvm_home, !remove_node_versions, &[nvm_root.as_path()], &mut removed_dirs, &mut kept_versions)?;
+    }
+
+    let _ = refresh_tray(app).await;
+    Ok(NvmUninstallSummary {
+        backup_path: backup_dir.to_string_lossy().to_string(),
+        removed_env_vars,
+        removed_path_entries: removed_path_entries.into_iter().map(|p| p.to_string()).collect(),
+        removed_symlink,
+        removed_dirs,
+        kept_versions,
+    })
+}
+
+// --- 共享全局包目录 ---
+
+#[tauri::command]
+async fn get_global_prefix() -> Result<Option<String>, String> {
+    let output = create_silent_command("npm.cmd")
+        .args(["config", "get", "prefix"])
+        .output()
+        .map_err(|e| e.to_string())?;
+    
+    if output.status.success() {
+        let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if prefix.is_empty() || prefix == "undefined" {
+            Ok(None)
+        } else {
+            Ok(Some(prefix))
+        }
     } else {
         Ok(None)
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NpmPrefixStatus {
+    pub effective: Option<String>,
+    pub expected: String,
+    pub shared: bool,
+    pub ok: bool,
+}
+
+// 配置了共享目录时 prefix 应指向共享目录，否则应为 npm 默认值（当前版本目录，即 nvm 的符号链接）
+#[tauri::command]
+async fn verify_npm_prefix() -> Result<NpmPrefixStatus, String> {
+    let config = internal_get_config().await?;
+    let effective = get_global_prefix().await?;
+
+    let (expected, shared, accepted) = match config.global_prefix {
+        Some(ref prefix) => (prefix.clone(), true, vec![normalize_dir(prefix)]),
+        None => {
+            let mut accepted = vec![normalize_dir(&config.nvm_symlink)];
+            if let Some(version) = get_current_node_version(&config.nvm_symlink) {
+                let version_dir = PathBuf::from(&config.nvm_path).join(format!("v{}", version));
+                accepted.push(normalize_dir(&version_dir.to_string_lossy()));
+            }
+            (config.nvm_symlink.clone(), false, accepted)
+        }
+    };
+    let ok = effective.as_deref().map(|p| accepted.contains(&normalize_dir(p))).unwrap_or(false);
+
+    Ok(NpmPrefixStatus { effective, expected, shared, ok })
+}
+
+#[tauri::command]
+async fn fix_npm_prefix() -> Result<NpmPrefixStatus, String> {
+    let config = internal_get_config().await?;
+    let output = match config.global_prefix {
+        Some(ref prefix) => create_silent_command("npm.cmd").args(["config", "set", "prefix", prefix]).output(),
+        // 未使用共享目录时删除用户级 prefix，恢复 npm 默认位置
+        None => create_silent_command("npm.cmd").args(["config", "delete", "prefix"]).output(),
+    }
+    .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(tr_args("error.fixPrefix", &[&String::from_utf8_lossy(&output.stderr)]));
+    }
+    verify_npm_prefix().await
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NpmrcPrefixFix {
+    pub changed: bool,
+    pub removed: Vec<String>,
+    pub kept: Option<String>,
+}
+
+// 识别 .npmrc 中指定键的声明，不区分大小写并容忍等号两侧的空格；注释行不算
+fn parse_npmrc_line(line: &str, key: &str) -> Option<String> {
+    let line = line.trim();
+    if line.starts_with('#') || line.starts_with(';') {
+        return None;
+    }
+    let (name, value) = line.split_once('=')?;
+    if name.trim().eq_ignore_ascii_case(key) {
+        Some(value.trim().to_string())
+    } else {
+        None
+    }
+}
+
+// 删除指定键的所有声明，保留其他配置和注释，keep 不为空时在末尾写入唯一的一行
+fn rewrite_npmrc_key(content: &str, key: &str, keep: Option<&str>) -> (String, Vec<String>) {
+    let mut removed = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match parse_npmrc_line(line, key) {
+            Some(value) => removed.push(value),
+            None => lines.push(line.to_string()),
+        }
+    }
+    if let Some(value) = keep {
+        lines.push(format!("{}={}", key, value));
+    }
+    (lines.join("\n"), removed)
+}
+
+// 修复被其他工具改乱的 .npmrc：只保留一条 prefix。
+// 优先使用配置的共享目录，未配置时保留最后一条（npm 以最后出现的为准）
+#[tauri::command]
+async fn normalize_npmrc_prefix() -> Result<NpmrcPrefixFix, String> {
+    let config = internal_get_config().await?;
+    let npmrc_path = get_user_npmrc_path()?;
+    let content = fs::read_to_string(&npmrc_path).unwrap_or_default();
+
+    let declared: Vec<String> = content.lines().filter_map(|line| parse_npmrc_line(line, "prefix")).collect();
+    let kept = config.global_prefix.clone().or_else(|| declared.last().cloned());
+    let (new_content, mut removed) = rewrite_npmrc_key(&content, "prefix", kept.as_deref());
+
+    // 与保留值相同的那一条不算被删除
+    if let Some(ref keep) = kept {
+        if let Some(pos) = removed.iter().position(|v| v == keep) {
+            removed.remove(pos);
+        }
+    }
+    let changed = new_content.trim_end() != content.trim_end();
+    if changed {
+        fs::write(&npmrc_path, new_content).map_err(|e| tr_args("error.writeNpmrc", &[&e]))?;
+    }
+    Ok(NpmrcPrefixFix { changed, removed, kept })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NpmCacheDir {
+    pub path: String,
+    pub size: u64,
+    // 是否由用户 .npmrc 显式指定，否则为 npm 默认位置
+    #[serde(rename = "isCustom")]
+    pub is_custom: bool,
+}
+
+#[tauri::command]
+async fn get_npm_cache_dir() -> Result<NpmCacheDir, String> {
+    let npmrc_path = get_user_npmrc_path()?;
+    let content = fs::read_to_string(&npmrc_path).unwrap_or_default();
+    let is_custom = content.lines().any(|line| parse_npmrc_line(line, "cache").is_some());
+    let path = get_npm_cache_path().ok_or_else(|| tr("error.npmCacheDir"))?;
+    let size = {
+        let path = path.clone();
+        tauri::async_runtime::spawn_blocking(move || get_dir_size(&path))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    Ok(NpmCacheDir {
+        path: path.to_string_lossy().to_string(),
+        size,
+        is_custom,
+    })
+}
+
+// 修改 npm 缓存目录：先校验新目录可写，migrate 为 true 时把旧缓存复制过去再删除，
+// 最后写入 .npmrc 的 cache 配置
+#[tauri::command]
+async fn set_npm_cache_dir(window: WebviewWindow, path: String, migrate: Option<bool>) -> Result<NpmCacheDir, String> {
+    let target = PathBuf::from(path.trim());
+    if !target.is_absolute() {
+        return Err(tr("error.cacheDirNotAbsolute"));
+    }
+    emit_sync_risk_warnings(window.app_handle(), &[&target.to_string_lossy()]);
+
+    fs::create_dir_all(&target).map_err(|e| tr_args("error.createDir", &[&e]))?;
+    let probe = target.join(".nvm-gui-write-test");
+    fs::write(&probe, b"ok").map_err(|e| tr_args("error.cacheDirNotWritable", &[&e]))?;
+    let _ = fs::remove_file(&probe);
+
+    let old_cache = get_npm_cache_path();
+    if migrate.unwrap_or(false) {
+        if let Some(old) = old_cache.filter(|old| old.exists() && normalize_dir(&old.to_string_lossy()) != normalize_dir(&target.to_string_lossy())) {
+            let window = window.clone();
+            let dest = target.clone();
+            tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+                let (total_files, total_bytes) = count_dir_files(&old);
+                let cancel = AtomicBool::new(false);
+                let mut last_progress = u32::MAX;
+                // 缓存内容按哈希寻址，目标已存在的文件直接跳过
+                copy_dir_with_progress(&old, &dest, false, &cancel, &mut |files, bytes| {
+                    let progress = if total_bytes > 0 {
+                        (bytes as f64 / total_bytes as f64 * 100.0) as u32
+                    } else {
+                        100
+                    };
+                    if progress != last_progress {
+                        last_progress = progress;
+                        let _ = window.emit("npm-cache:migrate:progress", serde_json::json!({
+                            "progress": progress,
+                            "files": files,
+                            "totalFiles": total_files,
+                            "bytes": bytes,
+                            "totalBytes": total_bytes
+                        }));
+                    }
+                })
+                .map_err(|e| tr_args("error.migrateCache", &[&e]))?;
+                // 缓存可以随时重建，旧目录删除失败不影响结果
+                let _ = fs::remove_dir_all(&old);
+                Ok(())
+            })
+            .await
+            .map_err(|e| e.to_string())??;
+        }
+    }
+
+    let npmrc_path = get_user_npmrc_path()?;
+    let content = fs::read_to_string(&npmrc_path).unwrap_or_default();
+    let (new_content, _) = rewrite_npmrc_key(&content, "cache", Some(&target.to_string_lossy()));
+    fs::write(&npmrc_path, new_content).map_err(|e| tr_args("error.writeNpmrc", &[&e]))?;
+
+    get_npm_cache_dir().await
+}
+
+// 静默模式涉及的 .npmrc 键，全部为 false 时视为开启
+const NPM_QUIET_KEYS: [&str; 3] = ["fund", "audit", "progress"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NpmQuietStatus {
+    pub enabled: bool,
+    pub fund: Option<String>,
+    pub audit: Option<String>,
+    pub progress: Option<String>,
+}
+
+fn npm_quiet_status(content: &str) -> NpmQuietStatus {
+    // 同一键多次出现时 npm 以最后一条为准
+    let value = |key: &str| content.lines().filter_map(|line| parse_npmrc_line(line, key)).last();
+    let (fund, audit, progress) = (value("fund"), value("audit"), value("progress"));
+    let enabled = [&fund, &audit, &progress]
+        .iter()
+        .all(|v| v.as_deref().map(|v| v.eq_ignore_ascii_case("false")).unwrap_or(false));
+    NpmQuietStatus { enabled, fund, audit, progress }
+}
+
+#[tauri::command]
+async fn get_npm_quiet() -> Result<NpmQuietStatus, String> {
+    let content = fs::read_to_string(get_user_npmrc_path()?).unwrap_or_default();
+    Ok(npm_quiet_status(&content))
+}
+
+// 开启时写入 fund/audit/progress=false；关闭时删除这三项，恢复 npm 默认行为，其他配置不动
+#[tauri::command]
+async fn set_npm_quiet(enabled: bool) -> Result<NpmQuietStatus, String> {
+    let npmrc_path = get_user_npmrc_path()?;
+    let content = fs::read_to_string(&npmrc_path).unwrap_or_default();
+    let mut new_content = content.clone();
+    for key in NPM_QUIET_KEYS {
+        new_content = rewrite_npmrc_key(&new_content, key, if enabled { Some("false") } else { None }).0;
+    }
+    if new_content.trim_end() != content.trim_end() {
+        fs::write(&npmrc_path, &new_content).map_err(|e| tr_args("error.writeNpmrc", &[&e]))?;
+    }
+    Ok(npm_quiet_status(&new_content))
+}
+
+// 全局命令所在目录：npm 9 起移除了 `npm bin -g`，此时根据 prefix 推导（Windows 下即 prefix 本身）
+#[tauri::command]
+async fn get_npm_bin_dir() -> Result<String, String> {
+    if let Ok(output) = create_silent_command("npm.cmd").args(["bin", "-g"]).output() {
+        let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !dir.is_empty() {
+            return Ok(dir);
+        }
+    }
+    get_global_prefix().await?.ok_or_else(|| tr("error.npmGlobalDir"))
+}
+
+#[tauri::command]
+async fn check_npm_bin_on_path() -> Result<serde_json::Value, String> {
+    let bin_dir = get_npm_bin_dir().await?;
+    let target = normalize_dir(&bin_dir);
+    let on_path = [false, true].iter().any(|&system| {
+        read_registry_env(system, "Path")
+            .unwrap_or_default()
+            .split(';')
+            .any(|p| normalize_dir(&expand_env_vars(p)) == target)
+    });
+    Ok(serde_json::json!({
+        "binDir": bin_dir,
+        "onPath": on_path
+    }))
+}
+
 #[tauri::command]
-async fn set_global_prefix(path: String, migrate_packages: bool) -> Result<bool, String> {
+async fn set_global_prefix(
+    window: WebviewWindow,
+    state: tauri::State<'_, PrefixMigrationState>,
+    path: String,
+    migrate_packages: bool,
+) -> Result<bool, String> {
+    // 同步盘检查由最后的 set_config 完成（global_prefix 变化时），这里不重复提示
     // 获取当前的 prefix（如果有）
     let old_prefix = get_global_prefix().await.ok().flatten();
     
     // 获取用户 .npmrc 路径
-    let user_profile = env::var("USERPROFILE")
-        .map_err(|_| "无法获取 USERPROFILE 环境变量".to_string())?;
-    let npmrc_path = PathBuf::from(&user_profile).join(".npmrc");
-    
-    // 读取现有 .npmrc 内容
-    let existing_content = fs::read_to_string(&npmrc_path).unwrap_or_default();
-    
-    // 解析并更新 prefix 设置
-    let mut lines: Vec<String> = existing_content
-        .lines()
-        .filter(|line| !line.trim().starts_with("prefix=") && !line.trim().starts_with("prefix ="))
-        .map(|s| s.to_string())
-        .collect();
-    
-    // 添加新的 prefix 设置
-    lines.push(format!("prefix={}", path));
-    
-    // 写回 .npmrc
-    let new_content = lines.join("\n");
-    fs::write(&npmrc_path, new_content)
-        .map_err(|e| format!("写入 .npmrc 失败: {}", e))?;
+    let npmrc_path = get_user_npmrc_path()?;
     
     // 创建新目录
-    fs::create_dir_all(&path).map_err(|e| format!("创建目录失败: {}", e))?;
+    fs::create_dir_all(&path).map_err(|e| tr_args("error.createDir", &[&e]))?;
     
     // 迁移全局包（如果需要且旧路径存在）
     if migrate_packages {
         if let Some(ref old_path) = old_prefix {
             if old_path != &path && Path::new(old_path).exists() {
-                // 迁移 node_modules 目录
-                let old_modules = PathBuf::from(old_path).join("node_modules");
-                let new_modules = PathBuf::from(&path).join("node_modules");
-                if old_modules.exists() && !new_modules.exists() {
-                    if let Err(_e) = fs::rename(&old_modules, &new_modules) {
-                        // 如果 rename 失败（跨盘符），尝试复制
-                        let _ = copy_dir_all(&old_modules, &new_modules);
-                        let _ = fs::remove_dir_all(&old_modules);
+                let cancel = Arc::new(AtomicBool::new(false));
+                *state.cancel_flag.lock().unwrap() = cancel.clone();
+
+                // 按预览同样的枚举结果执行迁移，目标位置已存在的条目跳过
+                // 每个已完成的条目（rename、跨盘符复制或文件复制）都记录下来，取消或失败时全部移回原处
+                let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+                let mut result = Ok(());
+                for item in collect_prefix_migration_items(old_path, &path) {
+                    if item.conflict {
+                        continue;
                     }
-                }
-                
-                // 迁移可执行文件（.cmd, .ps1 等）
-                if let Ok(entries) = fs::read_dir(old_path) {
-                    for entry in entries.flatten() {
-                        let file_path = entry.path();
-                        if file_path.is_file() {
-                            if let Some(name) = file_path.file_name() {
-                                let dest = PathBuf::from(&path).join(name);
-                                if !dest.exists() {
-                                    let _ = fs::rename(&file_path, &dest).or_else(|_| {
-                                        fs::copy(&file_path, &dest).map(|_| ())
-                                    });
-                                }
+                    if cancel.load(Ordering::SeqCst) {
+                        result = Err(tr("error.migrationCancelled"));
+                        break;
+                    }
+                    let source = PathBuf::from(&item.source);
+                    let dest = PathBuf::from(&item.destination);
+                    if item.is_dir {
+                        if fs::rename(&source, &dest).is_ok() {
+                            moved.push((source, dest));
+                            continue;
+                        }
+                        // 如果 rename 失败（跨盘符），复制并校验后再删除源目录，失败则回滚
+                        let window = window.clone();
+                        let cancel = cancel.clone();
+                        let (src, dst) = (source.clone(), dest.clone());
+                        let migrated = tauri::async_runtime::spawn_blocking(move || {
+                            migrate_dir_across_volumes(&window, &src, &dst, &cancel)
+                        })
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|r| r);
+                        match migrated {
+                            Ok(()) => moved.push((source, dest)),
+                            Err(e) => {
+                                result = Err(e);
+                                break;
+                            }
+                        }
+                    } else if fs::rename(&source, &dest).is_ok() {
+                        moved.push((source, dest));
+                    } else {
+                        // bin 目录下的命令文件移动失败时不能只跳过，否则 .npmrc 改写后命令会缺失
+                        match fs::copy(&source, &dest) {
+                            Ok(_) => moved.push((source, dest)),
+                            Err(e) => {
+                                result = Err(tr_args("error.movePrefixEntry", &[&source.display(), &e]));
+                                break;
                             }
                         }
                     }
                 }
+
+                if let Err(e) = result {
+                    let _ = tauri::async_runtime::spawn_blocking(move || {
+                        for (source, dest) in moved.iter().rev() {
+                            rollback_prefix_move(source, dest);
+                        }
+                    })
+                    .await;
+                    return Err(e);
+                }
             }
         }
     }
     
+    // 迁移成功后再更新 .npmrc，避免迁移失败时 npm 指向空目录
+    // 读取现有 .npmrc 内容
+    let existing_content = fs::read_to_string(&npmrc_path).unwrap_or_default();
+    
+    // 替换所有 prefix 声明（包括大小写、空格不规范的）为新的设置
+    let (new_content, _) = rewrite_npmrc_key(&existing_content, "prefix", Some(&path));
+    
+    // 写回 .npmrc
+    fs::write(&npmrc_path, new_content)
+        .map_err(|e| tr_args("error.writeNpmrc", &[&e]))?;
+    
     // 更新 PATH 环境变量（移除旧路径，添加新路径）
+    let env_before = snapshot_user_env();
     let hkcu = RegKey::predef(HKEY_CURRENT_USER);
     if let Ok(env_key) = hkcu.open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE) {
         let current_path: String = env_key.get_value("Path").unwrap_or_default();
@@ -2162,54 +7685,249 @@ async fn set_global_prefix(path: String, migrate_packages: bool) -> Result<bool,
         
         let new_path_env = paths.join(";");
         let _ = env_key.set_value("Path", &new_path_env);
+        record_env_change("set_global_prefix", &env_before);
         
         // 广播环境变量更改通知
-        #[cfg(windows)]
-        unsafe {
-            use std::ffi::OsStr;
-            use std::os::windows::ffi::OsStrExt;
-            use windows_sys::Win32::UI::WindowsAndMessaging::{SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE};
-            
-            let param: Vec<u16> = OsStr::new("Environment").encode_wide().chain(Some(0)).collect();
-            SendMessageTimeoutW(
-                HWND_BROADCAST,
-                WM_SETTINGCHANGE,
-                0,
-                param.as_ptr() as isize,
-                SMTO_ABORTIFHUNG,
-                5000,
-                std::ptr::null_mut(),
-            );
-        }
+        notify_env_change_in_background();
     }
     
     // 更新 settings.txt
     let mut config = internal_get_config().await?;
     config.global_prefix = Some(path);
-    set_config(config).await?;
+    set_config(window.app_handle().clone(), config).await?;
     
     Ok(true)
 }
 
-// 递归复制目录
-fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrefixMigrationItem {
+    pub source: String,
+    pub destination: String,
+    #[serde(rename = "isDir")]
+    pub is_dir: bool,
+    pub size: u64,
+    pub conflict: bool, // 目标位置已存在同名条目，迁移时会跳过
+}
+
+// 枚举迁移全局 prefix 时需要移动的条目：node_modules 目录以及根目录下的可执行文件（.cmd, .ps1 等）
+// 预览和实际迁移共用这份枚举结果，保证两者一致
+fn collect_prefix_migration_items(old_path: &str, new_path: &str) -> Vec<PrefixMigrationItem> {
+    let mut items = Vec::new();
+
+    let old_modules = PathBuf::from(old_path).join("node_modules");
+    let new_modules = PathBuf::from(new_path).join("node_modules");
+    if old_modules.exists() {
+        items.push(PrefixMigrationItem {
+            source: old_modules.to_string_lossy().to_string(),
+            destination: new_modules.to_string_lossy().to_string(),
+            is_dir: true,
+            size: get_dir_size(&old_modules),
+            conflict: new_modules.exists(),
+        });
+    }
+
+    if let Ok(entries) = fs::read_dir(old_path) {
+        for entry in entries.flatten() {
+            let file_path = entry.path();
+            if file_path.is_file() {
+                let dest = PathBuf::from(new_path).join(entry.file_name());
+                items.push(PrefixMigrationItem {
+                    source: file_path.to_string_lossy().to_string(),
+                    destination: dest.to_string_lossy().to_string(),
+                    is_dir: false,
+                    size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                    conflict: dest.exists(),
+                });
+            }
+        }
+    }
+
+    items
+}
+
+// 比较两个路径的盘符/卷前缀，不同则 rename 无法完成，只能复制后删除
+fn is_cross_volume(a: &Path, b: &Path) -> bool {
+    let volume = |p: &Path| {
+        p.components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+    };
+    volume(a) != volume(b)
+}
+
+#[tauri::command]
+async fn preview_global_prefix_change(path: String) -> Result<serde_json::Value, String> {
+    let old_prefix = get_global_prefix().await.ok().flatten();
+
+    let mut items = Vec::new();
+    let mut cross_volume = false;
+    if let Some(ref old_path) = old_prefix {
+        if old_path != &path && Path::new(old_path).exists() {
+            items = collect_prefix_migration_items(old_path, &path);
+            cross_volume = is_cross_volume(Path::new(old_path), Path::new(&path));
+        }
+    }
+
+    let estimated_size: u64 = items.iter().filter(|i| !i.conflict).map(|i| i.size).sum();
+    let conflicts: Vec<&PrefixMigrationItem> = items.iter().filter(|i| i.conflict).collect();
+
+    Ok(serde_json::json!({
+        "oldPrefix": old_prefix,
+        "newPrefix": path,
+        "targetExists": Path::new(&path).exists(),
+        "items": items,
+        "conflicts": conflicts,
+        "estimatedSize": estimated_size,
+        "crossVolume": cross_volume
+    }))
+}
+
+// 统计目录下的文件数与总字节数，用于跨盘复制的进度计算与完整性校验
+fn count_dir_files(path: &Path) -> (u64, u64) {
+    let mut files = 0;
+    let mut bytes = 0;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            if let Ok(md) = entry.metadata() {
+                if md.is_dir() {
+                    let (f, b) = count_dir_files(&entry.path());
+                    files += f;
+                    bytes += b;
+                } else {
+                    files += 1;
+                    bytes += md.len();
+                }
+            }
+        }
+    }
+    (files, bytes)
+}
+
+// 把已迁移到 dest 的条目放回 source：优先 rename，跨盘符时复制回去（不覆盖源位置残留的文件）后删除 dest
+fn rollback_prefix_move(source: &Path, dest: &Path) {
+    if fs::rename(dest, source).is_ok() {
+        return;
+    }
+    let restored = if dest.is_dir() {
+        copy_dir_with_progress(dest, source, false, &AtomicBool::new(false), &mut |_, _| {}).is_ok()
+    } else {
+        source.exists() || fs::copy(dest, source).is_ok()
+    };
+    if restored {
+        if dest.is_dir() {
+            let _ = fs::remove_dir_all(dest);
+        } else {
+            let _ = fs::remove_file(dest);
+        }
+    }
+}
+
+// 跨盘迁移目录：复制 -> 校验文件数与大小 -> 删除源目录
+// 任一步失败都会删除不完整的目标目录，保证源目录完好
+fn migrate_dir_across_volumes(window: &WebviewWindow, src: &Path, dst: &Path, cancel: &AtomicBool) -> Result<(), String> {
+    let (total_files, total_bytes) = count_dir_files(src);
+    let source = src.to_string_lossy().to_string();
+    let mut last_progress = u32::MAX;
+
+    let copy_result = copy_dir_with_progress(src, dst, true, cancel, &mut |files, bytes| {
+        let progress = if total_bytes > 0 {
+            (bytes as f64 / total_bytes as f64 * 100.0) as u32
+        } else {
+            100
+        };
+        // 仅在百分比变化时发送，避免大量小文件刷屏
+        if progress != last_progress {
+            last_progress = progress;
+            let _ = window.emit("prefix:migrate:progress", serde_json::json!({
+                "source": source,
+                "progress": progress,
+                "files": files,
+                "totalFiles": total_files,
+                "bytes": bytes,
+                "totalBytes": total_bytes
+            }));
+        }
+    });
+
+    if let Err(e) = copy_result {
+        let _ = fs::remove_dir_all(dst);
+        return Err(tr_args("error.copyGlobalPackages", &[&e]));
+    }
+
+    let (copied_files, copied_bytes) = count_dir_files(dst);
+    if copied_files != total_files || copied_bytes != total_bytes {
+        let _ = fs::remove_dir_all(dst);
+        return Err(tr_args("error.copyVerify", &[&copied_files, &total_files, &copied_bytes, &total_bytes]));
+    }
+
+    // 目标已校验完整，源目录删除失败也不会丢失数据
+    let _ = fs::remove_dir_all(src);
+    Ok(())
+}
+
+// 带进度回调的递归复制，回调参数为已处理的文件数和字节数
+// overwrite 为 false 时跳过目标已存在的文件；cancel 被置位时以 Interrupted 错误中止
+// 复制后保留源文件的访问和修改时间
+fn copy_dir_with_progress(
+    src: &Path,
+    dst: &Path,
+    overwrite: bool,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(u64, u64),
+) -> std::io::Result<(u64, u64)> {
+    let mut copied = (0, 0);
+    copy_dir_with_progress_inner(src, dst, overwrite, cancel, &mut copied, on_progress)?;
+    Ok(copied)
+}
+
+fn copy_dir_with_progress_inner(
+    src: &Path,
+    dst: &Path,
+    overwrite: bool,
+    cancel: &AtomicBool,
+    copied: &mut (u64, u64),
+    on_progress: &mut dyn FnMut(u64, u64),
+) -> std::io::Result<()> {
     fs::create_dir_all(dst)?;
     for entry in fs::read_dir(src)? {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(std::io::Error::new(std::io::ErrorKind::Interrupted, tr("error.copyCancelled")));
+        }
         let entry = entry?;
         let ty = entry.file_type()?;
         let src_path = entry.path();
         let dst_path = dst.join(entry.file_name());
         if ty.is_dir() {
-            copy_dir_all(&src_path, &dst_path)?;
+            copy_dir_with_progress_inner(&src_path, &dst_path, overwrite, cancel, copied, on_progress)?;
         } else {
-            fs::copy(&src_path, &dst_path)?;
+            let meta = entry.metadata()?;
+            if overwrite || !dst_path.exists() {
+                fs::copy(&src_path, &dst_path)?;
+                let mut times = fs::FileTimes::new();
+                if let Ok(modified) = meta.modified() {
+                    times = times.set_modified(modified);
+                }
+                if let Ok(accessed) = meta.accessed() {
+                    times = times.set_accessed(accessed);
+                }
+                let _ = fs::OpenOptions::new().write(true).open(&dst_path).and_then(|f| f.set_times(times));
+            }
+            copied.1 += meta.len();
+            copied.0 += 1;
+            on_progress(copied.0, copied.1);
         }
     }
     Ok(())
 }
 
 #[tauri::command]
-async fn get_shared_packages_config() -> Result<NvmSharedConfig, String> {
+async fn cancel_prefix_migration(state: tauri::State<'_, PrefixMigrationState>) -> Result<bool, String> {
+    state.cancel_flag.lock().unwrap().store(true, Ordering::SeqCst);
+    Ok(true)
+}
+
+#[tauri::command]
+async fn get_shared_packages_config() -> Result<NvmSharedConfig, String> {
     let config = internal_get_config().await.ok();
     let prefix_path = config.as_ref().and_then(|c| c.global_prefix.clone());
     
@@ -2228,7 +7946,7 @@ async fn get_shared_packages_config() -> Result<NvmSharedConfig, String> {
     
     // 获取全局包数量
     let package_count = if enabled {
-        let packages = get_global_packages().await.unwrap_or_default();
+        let packages = get_global_packages(None).await.unwrap_or_default();
         packages.len() as u32
     } else {
         0
@@ -2257,148 +7975,1215 @@ async fn check_path_contains(path: String) -> Result<bool, String> {
 }
 
 #[tauri::command]
-async fn add_to_user_path(path: String) -> Result<bool, String> {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let env_key = hkcu
-        .open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)
-        .map_err(|e| format!("打开注册表失败: {}", e))?;
-    
-    let current_path: String = env_key.get_value("Path").unwrap_or_default();
-    let mut paths: Vec<&str> = current_path.split(';').collect();
-    
-    if !paths.iter().any(|p| p.eq_ignore_ascii_case(&path)) {
-        paths.push(&path);
-        let new_path = paths.join(";");
-        env_key.set_value("Path", &new_path)
-            .map_err(|e| format!("设置 PATH 失败: {}", e))?;
-        
-        // 广播环境变量更改通知
-        #[cfg(windows)]
-        unsafe {
-            use std::ffi::OsStr;
-            use std::os::windows::ffi::OsStrExt;
-            use windows_sys::Win32::UI::WindowsAndMessaging::{SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE};
-            
-            let param: Vec<u16> = OsStr::new("Environment").encode_wide().chain(Some(0)).collect();
-            SendMessageTimeoutW(
-                HWND_BROADCAST,
-                WM_SETTINGCHANGE,
-                0,
-                param.as_ptr() as isize,
-                SMTO_ABORTIFHUNG,
-                5000,
-                std::ptr::null_mut(),
-            );
-        }
+async fn add_to_user_path(path: String) -> Result<bool, String> {
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let env_key = hkcu
+        .open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)
+        .map_err(|e| tr_args("error.openRegistry", &[&e]))?;
+    
+    let current_path: String = env_key.get_value("Path").unwrap_or_default();
+    let mut paths: Vec<&str> = current_path.split(';').collect();
+    
+    if !paths.iter().any(|p| p.eq_ignore_ascii_case(&path)) {
+        paths.push(&path);
+        let new_path = paths.join(";");
+        env_key.set_value("Path", &new_path)
+            .map_err(|e| tr_args("error.setPath", &[&e]))?;
+        
+        // 广播环境变量更改通知
+        notify_env_change_in_background();
+    }
+    
+    Ok(true)
+}
+
+// --- PATH 诊断 ---
+
+const SYSTEM_ENV_KEY: &str = "SYSTEM\\CurrentControlSet\\Control\\Session Manager\\Environment";
+
+// 读取注册表中的环境变量，system 为 true 时读取 HKLM 系统环境，否则读取 HKCU 用户环境
+fn read_registry_env(system: bool, name: &str) -> Option<String> {
+    let (hive, subkey) = if system {
+        (HKEY_LOCAL_MACHINE, SYSTEM_ENV_KEY)
+    } else {
+        (HKEY_CURRENT_USER, "Environment")
+    };
+    RegKey::predef(hive)
+        .open_subkey(subkey)
+        .ok()?
+        .get_value::<String, _>(name)
+        .ok()
+}
+
+// 展开 REG_EXPAND_SZ 中的 %VAR% 引用，未定义的变量保持原样
+fn expand_env_vars(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(end) => {
+                let name = &after[..end];
+                match env::var(name) {
+                    Ok(v) if !name.is_empty() => result.push_str(&v),
+                    _ => {
+                        result.push('%');
+                        result.push_str(name);
+                        result.push('%');
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('%');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn normalize_dir(path: &str) -> String {
+    path.trim().trim_end_matches(['\\', '/']).to_lowercase()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PathEntry {
+    pub path: String,
+    pub expanded: String,
+    pub exists: bool,
+    // "nvmSymlink" / "nvmHome" / "globalPrefix" / "node"（其他包含 node.exe 的目录），与 Node 无关则为 None
+    pub kind: Option<String>,
+}
+
+fn classify_path_entry(expanded: &str, config: Option<&NvmConfig>) -> Option<String> {
+    let dir = normalize_dir(expanded);
+    if dir.is_empty() {
+        return None;
+    }
+    if let Some(c) = config {
+        if !c.nvm_symlink.is_empty() && dir == normalize_dir(&c.nvm_symlink) {
+            return Some("nvmSymlink".to_string());
+        }
+        if !c.nvm_path.is_empty() && dir == normalize_dir(&c.nvm_path) {
+            return Some("nvmHome".to_string());
+        }
+        if let Some(ref prefix) = c.global_prefix {
+            if dir == normalize_dir(prefix) {
+                return Some("globalPrefix".to_string());
+            }
+        }
+    }
+    if Path::new(expanded.trim()).join("node.exe").exists() {
+        return Some("node".to_string());
+    }
+    None
+}
+
+fn build_path_entries(raw: &str, config: Option<&NvmConfig>) -> Vec<PathEntry> {
+    raw.split(';')
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| {
+            let expanded = expand_env_vars(p.trim());
+            PathEntry {
+                path: p.to_string(),
+                exists: Path::new(&expanded).exists(),
+                kind: classify_path_entry(&expanded, config),
+                expanded,
+            }
+        })
+        .collect()
+}
+
+// 检查 PATH 顺序问题：新开的终端中 PATH = 系统 PATH + 用户 PATH
+fn find_path_order_issues(system: &[PathEntry], user: &[PathEntry]) -> Vec<String> {
+    let mut issues = Vec::new();
+    let combined: Vec<&PathEntry> = system.iter().chain(user.iter()).collect();
+
+    let symlink_index = combined.iter().position(|e| e.kind.as_deref() == Some("nvmSymlink"));
+    match symlink_index {
+        None => issues.push(tr("path.symlinkMissing")),
+        Some(idx) => {
+            for entry in combined.iter().take(idx) {
+                if entry.kind.as_deref() == Some("node") {
+                    issues.push(tr_args("path.nodeBeforeSymlink", &[&entry.expanded]));
+                }
+            }
+        }
+    }
+
+    let mut seen: Vec<String> = Vec::new();
+    for entry in &combined {
+        let key = normalize_dir(&entry.expanded);
+        if entry.kind.is_some() && seen.contains(&key) {
+            issues.push(tr_args("path.duplicate", &[&entry.expanded]));
+        }
+        seen.push(key);
+    }
+    issues
+}
+
+#[tauri::command]
+async fn get_effective_path() -> Result<serde_json::Value, String> {
+    let config = internal_get_config().await.ok();
+
+    let process_raw = env::var("PATH").unwrap_or_default();
+    let user_raw = read_registry_env(false, "Path").unwrap_or_default();
+    let system_raw = read_registry_env(true, "Path").unwrap_or_default();
+
+    let process = build_path_entries(&process_raw, config.as_ref());
+    let user = build_path_entries(&user_raw, config.as_ref());
+    let system = build_path_entries(&system_raw, config.as_ref());
+    let issues = find_path_order_issues(&system, &user);
+
+    Ok(serde_json::json!({
+        "process": process,
+        "user": user,
+        "system": system,
+        "issues": issues
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SymlinkPathCheck {
+    #[serde(rename = "symlinkDir")]
+    pub symlink_dir: String,
+    // 注册表中的 NVM_SYMLINK，与 settings.txt 的 path 不一致时 nvm 会切换到别处
+    #[serde(rename = "envSymlink")]
+    pub env_symlink: Option<String>,
+    #[serde(rename = "envMismatch")]
+    pub env_mismatch: bool,
+    // 在新终端的 PATH（系统 PATH + 用户 PATH）中的位置，不存在时为 None
+    pub position: Option<usize>,
+    // 排在符号链接目录之前、包含 node.exe 的目录
+    #[serde(rename = "shadowedBy")]
+    pub shadowed_by: Vec<String>,
+    pub ok: bool,
+}
+
+// 专门检查 nvm 符号链接目录在 PATH 中的优先级：它被其他 Node 目录挡住时 nvm use 看起来没有任何效果
+#[tauri::command]
+async fn check_symlink_on_path() -> Result<SymlinkPathCheck, String> {
+    let config = internal_get_config().await?;
+    let env_symlink = read_registry_env(false, "NVM_SYMLINK").or_else(|| read_registry_env(true, "NVM_SYMLINK"));
+    let env_mismatch = env_symlink
+        .as_ref()
+        .map(|v| normalize_dir(&expand_env_vars(v)) != normalize_dir(&config.nvm_symlink))
+        .unwrap_or(false);
+
+    let system = build_path_entries(&read_registry_env(true, "Path").unwrap_or_default(), Some(&config));
+    let user = build_path_entries(&read_registry_env(false, "Path").unwrap_or_default(), Some(&config));
+    let combined: Vec<&PathEntry> = system.iter().chain(user.iter()).collect();
+
+    let position = combined.iter().position(|e| e.kind.as_deref() == Some("nvmSymlink"));
+    let shadowed_by: Vec<String> = combined
+        .iter()
+        .take(position.unwrap_or(combined.len()))
+        .filter(|e| e.kind.as_deref() == Some("node"))
+        .map(|e| e.expanded.clone())
+        .collect();
+
+    Ok(SymlinkPathCheck {
+        ok: position.is_some() && shadowed_by.is_empty() && !env_mismatch,
+        symlink_dir: config.nvm_symlink,
+        env_symlink,
+        env_mismatch,
+        position,
+        shadowed_by,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnvDivergence {
+    pub name: String,
+    pub process: Option<String>,
+    pub registry: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnvRefreshStatus {
+    pub recommendation: String, // "none", "restartTerminal", "logoff"
+    pub diverging: Vec<EnvDivergence>,
+    #[serde(rename = "missingPathEntries")]
+    pub missing_path_entries: Vec<String>,
+}
+
+// 比较本进程与注册表中的 NVM_HOME / NVM_SYMLINK / PATH，给出修改后需要的刷新方式：
+// - 注册表已更新、本进程未更新：广播后新开的终端即可读到，重启终端即可；
+// - 本进程有而注册表没有（只通过 env::set_var 设置，未持久化），或 PATH 引用的变量
+//   是刚写入注册表的（资源管理器收到广播时不一定能展开嵌套引用）：建议注销后重新登录
+#[tauri::command]
+async fn env_refresh_status() -> Result<EnvRefreshStatus, String> {
+    let mut diverging = Vec::new();
+    let mut needs_logoff = false;
+
+    for name in ["NVM_HOME", "NVM_SYMLINK"] {
+        let process = env::var(name).ok();
+        let registry = read_registry_env(false, name).or_else(|| read_registry_env(true, name));
+        let same = match (&process, &registry) {
+            (Some(p), Some(r)) => normalize_dir(p) == normalize_dir(r),
+            (None, None) => true,
+            _ => false,
+        };
+        if !same {
+            if registry.is_none() {
+                needs_logoff = true;
+            }
+            diverging.push(EnvDivergence { name: name.to_string(), process, registry });
+        }
+    }
+
+    let process_entries: Vec<String> = env::var("PATH")
+        .unwrap_or_default()
+        .split(';')
+        .filter(|p| !p.trim().is_empty())
+        .map(normalize_dir)
+        .collect();
+    let registry_raw = [read_registry_env(true, "Path"), read_registry_env(false, "Path")];
+    let mut missing_path_entries = Vec::new();
+    for raw in registry_raw.iter().flatten() {
+        for entry in raw.split(';').filter(|p| !p.trim().is_empty()) {
+            let expanded = expand_env_vars(entry);
+            if expanded.contains('%') {
+                // 引用的变量已写入注册表但本进程还没有
+                let referenced = entry.split('%').nth(1).unwrap_or_default();
+                if read_registry_env(false, referenced).or_else(|| read_registry_env(true, referenced)).is_some() {
+                    needs_logoff = true;
+                    missing_path_entries.push(entry.to_string());
+                }
+                continue;
+            }
+            if !process_entries.contains(&normalize_dir(&expanded)) {
+                missing_path_entries.push(expanded);
+            }
+        }
+    }
+
+    let recommendation = if needs_logoff {
+        "logoff"
+    } else if !diverging.is_empty() || !missing_path_entries.is_empty() {
+        "restartTerminal"
+    } else {
+        "none"
+    };
+    Ok(EnvRefreshStatus {
+        recommendation: recommendation.to_string(),
+        diverging,
+        missing_path_entries,
+    })
+}
+
+// --- 健康检查 ---
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthCheckItem {
+    pub check: String,
+    pub status: String, // "ok", "warn", "fail"
+    pub detail: String,
+    // detail 来自消息表时附带消息码，前端可自行本地化；版本号、路径等原始值为空
+    pub code: Option<String>,
+    // 前端可调用的修复命令
+    #[serde(rename = "fixAction")]
+    pub fix_action: Option<String>,
+}
+
+fn health_item(check: &str, status: &str, detail: String, fix_action: Option<&str>) -> HealthCheckItem {
+    HealthCheckItem {
+        check: check.to_string(),
+        status: status.to_string(),
+        detail,
+        code: None,
+        fix_action: fix_action.map(|a| a.to_string()),
+    }
+}
+
+fn health_message(check: &str, status: &str, code: &str, args: &[&dyn std::fmt::Display], fix_action: Option<&str>) -> HealthCheckItem {
+    HealthCheckItem {
+        code: Some(code.to_string()),
+        ..health_item(check, status, tr_args(code, args), fix_action)
+    }
+}
+
+#[cfg(windows)]
+fn get_disk_free_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut free: u64 = 0;
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, std::ptr::null_mut(), std::ptr::null_mut()) };
+    if ok != 0 { Some(free) } else { None }
+}
+
+#[cfg(not(windows))]
+fn get_disk_free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+// 环境变量既要存在于当前进程，也要写入了注册表（否则重启后丢失）
+fn check_env_var(name: &str) -> HealthCheckItem {
+    let process = env::var(name).ok();
+    let registry = read_registry_env(false, name).or_else(|| read_registry_env(true, name));
+    match (process, registry) {
+        (Some(value), Some(_)) => health_item(name, "ok", value, None),
+        (None, Some(value)) => health_message(name, "warn", "health.envNotLoaded", &[&value], None),
+        (Some(value), None) => health_message(name, "warn", "health.envNotPersisted", &[&value], Some("download_and_install_nvm")),
+        (None, None) => health_message(name, "fail", "health.envMissing", &[&name], Some("download_and_install_nvm")),
+    }
+}
+
+// 安装中断时版本目录没有 node.exe，或残留 node.zip.part
+fn find_incomplete_installs(nvm_path: &Path) -> Vec<String> {
+    let mut incomplete = Vec::new();
+    if let Ok(entries) = fs::read_dir(nvm_path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() && name.starts_with('v') && name.split('.').count() >= 3
+                && (!path.join("node.exe").exists() || path.join("node.zip.part").exists())
+            {
+                incomplete.push(name);
+            }
+        }
+    }
+    incomplete
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitInstallation {
+    pub version: String,
+    pub path: String,
+}
+
+// npm 安装 Git 地址的依赖时需要 git，缺失时报错信息很难理解
+#[tauri::command]
+async fn check_git_installation() -> Result<Option<GitInstallation>, String> {
+    let path = match create_silent_command("where").arg("git").output() {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .next()
+            .map(|l| l.trim().to_string())
+            .unwrap_or_default(),
+        _ => return Ok(None),
+    };
+    let version = match create_silent_command("git").arg("--version").output() {
+        // 输出形如 "git version 2.43.0.windows.1"
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
+            .trim()
+            .trim_start_matches("git version")
+            .trim()
+            .to_string(),
+        _ => return Ok(None),
+    };
+    Ok(Some(GitInstallation { version, path }))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermissionCheck {
+    pub location: String, // "nvmRoot" / "symlinkParent" / "globalPrefix" / "settings" / "registry"
+    pub path: Option<String>,
+    pub writable: bool,
+    pub error: Option<String>,
+}
+
+// 在目录中创建并删除一个临时文件；目录还不存在时检查最近的已存在上级目录（之后会由 create_dir_all 创建）
+fn probe_dir_writable(dir: &Path) -> Result<(), String> {
+    let existing = dir.ancestors().find(|p| p.is_dir()).ok_or_else(|| tr_args("error.dirMissing", &[&dir.display()]))?;
+    let probe = existing.join(format!(".nvm-gui-write-test-{}", std::process::id()));
+    fs::write(&probe, b"ok").map_err(|e| format!("{}: {}", existing.display(), e))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+fn permission_check(location: &str, path: Option<String>, result: Result<(), String>) -> PermissionCheck {
+    PermissionCheck {
+        location: location.to_string(),
+        path,
+        writable: result.is_ok(),
+        error: result.err(),
+    }
+}
+
+// 提前检查各个需要写入的位置，避免安装或修改配置时才报“拒绝访问”
+#[tauri::command]
+async fn check_permissions() -> Result<Vec<PermissionCheck>, String> {
+    let config = internal_get_config().await?;
+    let mut checks = Vec::new();
+
+    let root = PathBuf::from(&config.nvm_path);
+    checks.push(permission_check("nvmRoot", Some(config.nvm_path.clone()), probe_dir_writable(&root)));
+
+    // 符号链接由 nvm 删除后重建，需要的是其上级目录的写权限
+    let symlink_parent = Path::new(&config.nvm_symlink).parent().map(|p| p.to_path_buf());
+    let result = match symlink_parent {
+        Some(ref parent) => probe_dir_writable(parent),
+        None => Err(tr("error.invalidSymlink")),
+    };
+    checks.push(permission_check("symlinkParent", symlink_parent.map(|p| p.to_string_lossy().to_string()), result));
+
+    if let Some(ref prefix) = config.global_prefix {
+        checks.push(permission_check("globalPrefix", Some(prefix.clone()), probe_dir_writable(Path::new(prefix))));
+    }
+
+    // 以追加方式打开不会改动内容，只验证能否写入
+    let settings_path = get_settings_path()?;
+    let result = fs::OpenOptions::new()
+        .append(true)
+        .open(&settings_path)
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    checks.push(permission_check("settings", Some(settings_path.to_string_lossy().to_string()), result));
+
+    let result = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    checks.push(permission_check("registry", Some("HKCU\\Environment".to_string()), result));
+
+    Ok(checks)
+}
+
+// 启动时检查一次写权限，有问题时通知前端
+fn start_permission_check<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        if let Ok(checks) = check_permissions().await {
+            let issues: Vec<&PermissionCheck> = checks.iter().filter(|c| !c.writable).collect();
+            if !issues.is_empty() {
+                let _ = app.emit("permissions:issues", &issues);
+            }
+        }
+    });
+}
+
+const HEALTH_VERSION_CHECK_TIMEOUT_SECS: u64 = 30;
+
+#[tauri::command]
+async fn run_health_scan() -> Result<Vec<HealthCheckItem>, String> {
+    let mut items = vec![check_env_var("NVM_HOME"), check_env_var("NVM_SYMLINK")];
+
+    let config = match internal_get_config().await {
+        Ok(c) => c,
+        Err(e) => {
+            items.push(health_item("settings", "fail", e, Some("download_and_install_nvm")));
+            return Ok(items);
+        }
+    };
+
+    // 符号链接及其指向的 node.exe
+    let symlink = Path::new(&config.nvm_symlink);
+    match fs::read_link(symlink) {
+        Ok(target) if target.join("node.exe").exists() => {
+            items.push(health_item("symlink", "ok", target.to_string_lossy().to_string(), None));
+        }
+        Ok(target) => {
+            items.push(health_message("symlink", "fail", "health.symlinkBroken", &[&target.display()], Some("switch_version")));
+        }
+        Err(_) => {
+            items.push(health_message("symlink", "warn", "health.noActiveVersion", &[], Some("switch_version")));
+        }
+    }
+
+    match create_silent_command("npm.cmd").arg("--version").output() {
+        Ok(o) if o.status.success() => {
+            items.push(health_item("npm", "ok", String::from_utf8_lossy(&o.stdout).trim().to_string(), None));
+        }
+        _ => items.push(health_message("npm", "fail", "health.npmUnavailable", &[], Some("switch_version"))),
+    }
+
+    match verify_npm_prefix().await {
+        Ok(status) if status.ok => items.push(health_item("npmPrefix", "ok", status.expected, None)),
+        Ok(status) => items.push(health_message(
+            "npmPrefix",
+            "warn",
+            "health.npmPrefixMismatch",
+            &[&status.effective.unwrap_or_default(), &status.expected],
+            Some("fix_npm_prefix"),
+        )),
+        Err(e) => items.push(health_item("npmPrefix", "warn", e, None)),
+    }
+
+    let registry = get_registry_base().await;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())?;
+    match client.head(&registry).send().await {
+        Ok(_) => items.push(health_item("registry", "ok", registry, None)),
+        Err(e) => items.push(health_message("registry", "fail", "health.registryUnreachable", &[&registry, &e], Some("test_all_mirror_speed"))),
+    }
+
+    let user_path = build_path_entries(&read_registry_env(false, "Path").unwrap_or_default(), Some(&config));
+    let system_path = build_path_entries(&read_registry_env(true, "Path").unwrap_or_default(), Some(&config));
+    let path_issues = find_path_order_issues(&system_path, &user_path);
+    if path_issues.is_empty() {
+        items.push(health_message("path", "ok", "health.pathOk", &[], None));
+    } else {
+        items.push(health_item("path", "warn", path_issues.join("\n"), Some("get_effective_path")));
+    }
+
+    if let Ok(checks) = check_permissions().await {
+        let denied: Vec<String> = checks
+            .iter()
+            .filter(|c| !c.writable)
+            .map(|c| format!("{}: {}", c.path.as_deref().unwrap_or(&c.location), c.error.as_deref().unwrap_or("")))
+            .collect();
+        if denied.is_empty() {
+            items.push(health_message("permissions", "ok", "health.permissionsOk", &[], None));
+        } else {
+            items.push(health_item("permissions", "fail", denied.join("\n"), Some("check_permissions")));
+        }
+    }
+
+    let nvm_path = PathBuf::from(&config.nvm_path);
+    match get_disk_free_space(&nvm_path) {
+        Some(free) if free < 200 * 1024 * 1024 => {
+            items.push(health_message("diskSpace", "fail", "health.diskSpaceLow", &[&(free / 1024 / 1024)], Some("get_storage_breakdown")));
+        }
+        Some(free) if free < 1024 * 1024 * 1024 => {
+            items.push(health_message("diskSpace", "warn", "health.diskSpace", &[&(free / 1024 / 1024)], Some("get_storage_breakdown")));
+        }
+        Some(free) => items.push(health_message("diskSpace", "ok", "health.diskSpace", &[&(free / 1024 / 1024)], None)),
+        None => {}
+    }
+
+    let incomplete = find_incomplete_installs(&nvm_path);
+    if incomplete.is_empty() {
+        items.push(health_message("incompleteInstalls", "ok", "health.noIncompleteInstalls", &[], None));
+    } else {
+        items.push(health_item("incompleteInstalls", "warn", incomplete.join(", "), Some("uninstall_version")));
+    }
+
+    // cache.json 无法解析时所有缓存读取都会落空，版本列表等每次都要重新下载
+    if let Ok(cache) = check_cache_health().await {
+        if !cache.valid {
+            items.push(if cache.corrupt {
+                health_message("cache", "warn", "health.cacheCorrupt", &[], Some("repair_cache"))
+            } else {
+                health_message("cache", "warn", "health.cacheInvalidEntries", &[&cache.invalid_entries.join(", ")], Some("repair_cache"))
+            });
+        }
+    }
+
+    let cruft = scan_npm_cruft().await.unwrap_or_default();
+    if !cruft.is_empty() {
+        let size: u64 = cruft.iter().map(|c| c.size).sum();
+        items.push(health_message("npmCruft", "warn", "health.npmCruft", &[&cruft.len(), &(size / 1024 / 1024)], Some("clean_npm_cruft")));
+    }
+
+    // 逐个运行各版本的 node.exe 较慢，放到阻塞线程中并限制总时长，避免卡住整个体检
+    let versions_root = config.nvm_path.clone();
+    let version_checks = tauri::async_runtime::spawn_blocking(move || {
+        list_installed_version_names(Path::new(&versions_root))
+            .into_iter()
+            .filter_map(|v| {
+                let check = check_node_runs(&PathBuf::from(&versions_root).join(format!("v{}", v)).join("node.exe"), &v);
+                check.error.filter(|_| !check.runs).map(|e| format!("v{}: {}", v, e))
+            })
+            .collect::<Vec<String>>()
+    });
+    match tokio::time::timeout(std::time::Duration::from_secs(HEALTH_VERSION_CHECK_TIMEOUT_SECS), version_checks).await {
+        Ok(Ok(broken)) if broken.is_empty() => {
+            items.push(health_message("versionRuns", "ok", "health.versionsRun", &[], None));
+        }
+        Ok(Ok(broken)) => items.push(health_item("versionRuns", "fail", broken.join("\n"), Some("uninstall_version"))),
+        Ok(Err(e)) => items.push(health_item("versionRuns", "warn", e.to_string(), None)),
+        Err(_) => items.push(health_message("versionRuns", "warn", "health.versionCheckTimeout", &[], Some("test_version_runs"))),
+    }
+
+    match check_git_installation().await.unwrap_or(None) {
+        Some(git) => items.push(health_item("git", "ok", format!("{} ({})", git.version, git.path), None)),
+        None => items.push(health_message("git", "warn", "health.gitMissing", &[], None)),
+    }
+
+    Ok(items)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    #[serde(rename = "hasUpdate")]
+    pub has_update: bool,
+    #[serde(rename = "currentVersion")]
+    pub current_version: String,
+    #[serde(rename = "latestVersion")]
+    pub latest_version: String,
+    #[serde(rename = "releaseUrl")]
+    pub release_url: String,
+    #[serde(rename = "releaseNotes")]
+    pub release_notes: String,
+    #[serde(rename = "publishedAt")]
+    pub published_at: String,
+}
+
+#[tauri::command]
+async fn check_for_updates() -> Result<UpdateInfo, String> {
+    let client = reqwest::Client::builder()
+        .user_agent("nvm-windows-gui")
+        .build()
+        .map_err(|e| e.to_string())?;
+    
+    // 获取当前版本
+    let current_version = env!("CARGO_PKG_VERSION").to_string();
+    
+    // 查询 GitHub API
+    let response = client
+        .get("https://api.github.com/repos/Mr-Youngs/nvm-windows-GUI/releases/latest")
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err(format!("GitHub API error: {}", response.status()));
+    }
+    
+    let release: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    
+    let latest_version = release["tag_name"]
+        .as_str()
+        .unwrap_or("0.0.0")
+        .trim_start_matches('v')
+        .to_string();
+    
+    let release_url = release["html_url"]
+        .as_str()
+        .unwrap_or("")
+        .to_string();
+    
+    let release_notes = release["body"]
+        .as_str()
+        .unwrap_or("")
+        .to_string();
+    
+    let published_at = release["published_at"]
+        .as_str()
+        .unwrap_or("")
+        .to_string();
+    
+    // 比较版本
+    let has_update = compare_versions(&current_version, &latest_version);
+    
+    Ok(UpdateInfo {
+        has_update,
+        current_version,
+        latest_version,
+        release_url,
+        release_notes,
+        published_at,
+    })
+}
+
+fn compare_versions(current: &str, latest: &str) -> bool {
+    let parse_version = |v: &str| -> Vec<u32> {
+        v.split('.')
+            .filter_map(|s| s.parse().ok())
+            .collect()
+    };
+    
+    let current_parts = parse_version(current);
+    let latest_parts = parse_version(latest);
+    
+    for i in 0..3 {
+        let c = current_parts.get(i).copied().unwrap_or(0);
+        let l = latest_parts.get(i).copied().unwrap_or(0);
+        if l > c {
+            return true;
+        }
+        if l < c {
+            return false;
+        }
+    }
+    false
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrefixCandidate {
+    pub id: String, // "configured" / "existing" / "default" / "nvmHome"
+    pub label: String,
+    pub path: String,
+    pub exists: bool,
+}
+
+// 按优先级排列全局包目录的候选，路径相同的只保留第一个。
+// npm 当前的 prefix 位于 nvm 目录或符号链接下时是各版本自己的目录，不适合作为共享目录
+fn build_prefix_candidates(
+    configured: Option<&str>,
+    npm_prefix: Option<&str>,
+    appdata: &str,
+    nvm_home: &str,
+    nvm_dirs: &[&str],
+) -> Vec<PrefixCandidate> {
+    let npm_prefix = npm_prefix.filter(|p| !nvm_dirs.iter().any(|dir| is_same_or_nested_path(p, dir)));
+    let default = format!("{}\\npm-global", appdata);
+    let inside_home = format!("{}\\npm-global", nvm_home.trim_end_matches('\\'));
+    let mut candidates: Vec<PrefixCandidate> = Vec::new();
+    for (id, label, path) in [
+        ("configured", "prefix.configured", configured),
+        ("existing", "prefix.existing", npm_prefix),
+        ("default", "prefix.default", Some(default.as_str())),
+        ("nvmHome", "prefix.nvmHome", Some(inside_home.as_str())),
+    ] {
+        let Some(path) = path else { continue };
+        if candidates.iter().any(|c| normalize_dir(&c.path) == normalize_dir(path)) {
+            continue;
+        }
+        candidates.push(PrefixCandidate {
+            id: id.to_string(),
+            label: tr(label),
+            path: path.to_string(),
+            exists: Path::new(path).is_dir(),
+        });
+    }
+    candidates
+}
+
+// globalPrefix 为推荐值（候选中的第一个），globalPrefixCandidates 供界面选择
+#[tauri::command]
+async fn get_default_paths() -> serde_json::Value {
+    let appdata = env::var("APPDATA").unwrap_or_else(|_| "C:\\Users\\Default\\AppData\\Roaming".to_string());
+    let default_home = format!("{}\\nvm", appdata);
+    let config = internal_get_config().await.ok();
+    let nvm_home = env::var("NVM_HOME").unwrap_or_else(|_| default_home.clone());
+    let npm_prefix = get_global_prefix().await.ok().flatten();
+
+    let mut nvm_dirs = vec![nvm_home.as_str()];
+    if let Some(ref config) = config {
+        nvm_dirs.push(&config.nvm_path);
+        nvm_dirs.push(&config.nvm_symlink);
+    }
+    let candidates = build_prefix_candidates(
+        config.as_ref().and_then(|c| c.global_prefix.as_deref()),
+        npm_prefix.as_deref(),
+        &appdata,
+        &nvm_home,
+        &nvm_dirs,
+    );
+    serde_json::json!({
+        "nvmHome": default_home,
+        "nvmSymlink": format!("{}\\nodejs", appdata),
+        "globalPrefix": candidates.first().map(|c| c.path.clone()).unwrap_or_default(),
+        "globalPrefixCandidates": candidates
+    })
+}
+
+// --- 项目自动切换 ---
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectEntry {
+    pub path: String,
+    // "auto" 表示从项目的 .nvmrc / .node-version 读取，否则为固定的版本号
+    #[serde(rename = "versionSource")]
+    pub version_source: String,
+}
+
+// 扫描 nvm 根目录下已安装的版本（不带 v 前缀），只统计包含 node.exe 的完整安装
+fn list_installed_version_names(nvm_path: &Path) -> Vec<String> {
+    let mut versions = Vec::new();
+    if let Ok(entries) = fs::read_dir(nvm_path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() && name.starts_with('v') && name.split('.').count() >= 3 && path.join("node.exe").exists()
+                && !is_unsafe_reparse_point(&path, nvm_path)
+            {
+                versions.push(name[1..].to_string());
+            }
+        }
+    }
+    versions
+}
+
+// 按数值比较 x.y.z 版本号（可带 v 前缀，忽略预发布后缀）
+fn compare_semver(a: &str, b: &str) -> std::cmp::Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim().trim_start_matches('v')
+            .split(['.', '-'])
+            .take(3)
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(a).cmp(&parse(b))
+}
+
+// 带预发布标签的版本，例如 v22.0.0-rc.1、v23.0.0-nightly20240101abcdef
+fn is_prerelease_version(version: &str) -> bool {
+    version.trim().trim_start_matches('v').contains('-')
+}
+
+const LATEST_INSTALLED: &str = "latest-installed";
+
+// 将版本描述解析为已安装的具体版本：支持完整版本号、"20" / "20.11" 这样的前缀（取最新）以及 latest-installed
+fn resolve_installed_version(spec: &str, installed: &[String]) -> Option<String> {
+    let spec = spec.trim().trim_start_matches('v');
+    if spec.is_empty() {
+        return None;
+    }
+    if spec == LATEST_INSTALLED {
+        return installed.iter().max_by(|a, b| compare_semver(a, b)).cloned();
+    }
+    let prefix = format!("{}.", spec);
+    installed
+        .iter()
+        .filter(|v| v.as_str() == spec || v.starts_with(&prefix))
+        .max_by(|a, b| compare_semver(a, b))
+        .cloned()
+}
+
+#[cfg(windows)]
+fn get_foreground_window_title() -> Option<String> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW};
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd == 0 {
+            return None;
+        }
+        let mut buf = [0u16; 512];
+        let len = GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32);
+        if len <= 0 {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&buf[..len as usize]))
+    }
+}
+
+#[cfg(not(windows))]
+fn get_foreground_window_title() -> Option<String> {
+    None
+}
+
+// 终端窗口标题通常包含完整的当前目录；路径之后必须是路径分隔符、窗口标题分隔符 " - " 或结尾，
+// 避免 D:\app 匹配 D:\app2。只显示文件夹名的标题无法区分同名目录，不参与匹配
+fn title_matches_project(title: &str, project_path: &str) -> bool {
+    let title = title.trim().to_lowercase();
+    let path = normalize_dir(project_path);
+    if path.is_empty() {
+        return false;
+    }
+    title.match_indices(&path).any(|(start, _)| {
+        let rest = &title[start + path.len()..];
+        rest.is_empty() || rest.starts_with(['\\', '/']) || rest.starts_with(" - ")
+    })
+}
+
+async fn get_project_version_spec(project: &ProjectEntry) -> Option<String> {
+    if project.version_source == "auto" {
+        read_nvmrc(project.path.clone()).await.ok().flatten().map(|info| info.version)
+    } else {
+        Some(project.version_source.clone())
+    }
+}
+
+// package.json 的 engines.node 是范围，这里近似为版本前缀：^18.17.0 / >=18 / 18.x 取主版本，~18.17 取主次版本；
+// 有多个候选（||）时取最后一个，通常是最新的版本线
+fn engines_range_to_spec(range: &str) -> Option<String> {
+    let last = range.split("||").last()?.trim();
+    let first = last.split_whitespace().next()?;
+    let (keep, rest) = if let Some(rest) = first.strip_prefix('~') {
+        (2, rest)
+    } else {
+        (1, first.trim_start_matches(['^', '>', '=']))
+    };
+    let parts: Vec<&str> = rest
+        .trim_start_matches('v')
+        .split('.')
+        .take_while(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+        .collect();
+    if parts.is_empty() {
+        return None;
+    }
+    // 精确版本号原样保留
+    if parts.len() == 3 && first.chars().next().map(|c| c.is_ascii_digit() || c == 'v').unwrap_or(false) {
+        return Some(parts.join("."));
+    }
+    Some(parts[..parts.len().min(keep)].join("."))
+}
+
+fn read_engines_node(dir: &Path) -> Option<NvmrcInfo> {
+    let path = dir.join("package.json");
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+    let version = manifest["engines"]["node"].as_str()?.trim().to_string();
+    if version.is_empty() {
+        return None;
+    }
+    Some(NvmrcInfo {
+        version,
+        source: "engines".to_string(),
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+// 在可下载的版本中解析：node / latest / stable 为最新版，lts/* 为最新 LTS，lts/<代号> 为该代号的最新版，其余按前缀匹配
+fn resolve_available_version(spec: &str, available: &[AvailableVersion]) -> Option<String> {
+    let spec = spec.trim().trim_start_matches('v').to_lowercase();
+    let lts_name = |v: &AvailableVersion| v.lts.as_str().map(|s| s.to_lowercase());
+    let matches: Vec<&AvailableVersion> = match spec.as_str() {
+        "node" | "latest" | "stable" => available.iter().collect(),
+        "lts/*" | "lts" => available.iter().filter(|v| lts_name(v).is_some()).collect(),
+        _ if spec.starts_with("lts/") => available.iter().filter(|v| lts_name(v).as_deref() == Some(&spec[4..])).collect(),
+        _ => {
+            let prefix = format!("v{}.", spec);
+            let exact = format!("v{}", spec);
+            available.iter().filter(|v| v.version == exact || v.version.starts_with(&prefix)).collect()
+        }
+    };
+    matches
+        .into_iter()
+        .map(|v| v.version.trim_start_matches('v').to_string())
+        .max_by(|a, b| compare_semver(a, b))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirectoryVersionPlan {
+    // 版本来源：.nvmrc / .node-version / engines，没有找到时为空
+    pub source: Option<NvmrcInfo>,
+    // 由来源换算出的版本描述（engines 的范围会被近似为前缀）
+    pub spec: Option<String>,
+    pub resolved: Option<String>,
+    pub installed: bool,
+    pub active: Option<String>,
+    #[serde(rename = "needsSwitch")]
+    pub needs_switch: bool,
+}
+
+// 只读：给出在该目录下 "nvm use" 会选择的版本、是否已安装以及当前激活的版本，不做任何切换。
+// 优先匹配已安装的版本，没有时再到可下载列表中解析，供前端提示安装
+#[tauri::command]
+async fn resolve_for_directory(dir_path: String) -> Result<DirectoryVersionPlan, String> {
+    let config = internal_get_config().await?;
+    let active = get_current_node_version(&config.nvm_symlink);
+    let source = match read_nvmrc(dir_path.clone()).await? {
+        Some(info) => Some(info),
+        None => read_engines_node(Path::new(&dir_path)),
+    };
+    let spec = source.as_ref().and_then(|info| {
+        if info.source == "engines" {
+            engines_range_to_spec(&info.version)
+        } else {
+            Some(info.version.clone())
+        }
+    });
+
+    let installed_versions = list_installed_version_names(Path::new(&config.nvm_path));
+    let is_alias = |spec: &str| {
+        let spec = spec.to_lowercase();
+        spec.starts_with("lts") || ["node", "latest", "stable"].contains(&spec.as_str())
+    };
+    let (resolved, installed) = match spec {
+        Some(ref spec) if !is_alias(spec) => match resolve_installed_version(spec, &installed_versions) {
+            Some(v) => (Some(v), true),
+            None => (fetch_available_versions(false).await.ok().and_then(|a| resolve_available_version(spec, &a)), false),
+        },
+        // 别名只能对照可下载列表解析，再看该版本是否已安装
+        Some(ref spec) => {
+            let resolved = fetch_available_versions(false).await.ok().and_then(|a| resolve_available_version(spec, &a));
+            let installed = resolved.as_ref().map(|v| installed_versions.contains(v)).unwrap_or(false);
+            (resolved, installed)
+        }
+        None => (None, false),
+    };
+    let needs_switch = resolved.is_some() && resolved != active;
+
+    Ok(DirectoryVersionPlan { source, spec, resolved, installed, active, needs_switch })
+}
+
+const PREFETCH_DELAY_SECS: u64 = 5;
+
+// 启动稍后在后台填充可用版本列表和镜像新鲜度缓存，用户打开版本页时数据已就绪。
+// 缓存仍新鲜时跳过；版本列表获取失败视为离线，不再继续检查镜像
+fn start_prefetch<R: Runtime>(app: AppHandle<R>) {
+    let cancel = app.state::<PrefetchState>().cancel_flag.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(tokio::time::Duration::from_secs(PREFETCH_DELAY_SECS)).await;
+        if cancel.load(Ordering::SeqCst) {
+            let _ = app.emit("prefetch:done", serde_json::json!({ "cancelled": true }));
+            return;
+        }
+
+        let skipped = get_from_cache("node_available_versions").await.is_some();
+        let versions_ok = skipped || fetch_available_versions(false).await.is_ok();
+        let freshness_ok = if versions_ok && !cancel.load(Ordering::SeqCst) {
+            check_mirror_freshness().await.is_ok()
+        } else {
+            false
+        };
+
+        let _ = app.emit("prefetch:done", serde_json::json!({
+            "cancelled": cancel.load(Ordering::SeqCst),
+            "skipped": skipped,
+            "versions": versions_ok,
+            "freshness": freshness_ok
+        }));
+    });
+}
+
+#[tauri::command]
+async fn cancel_prefetch(state: tauri::State<'_, PrefetchState>) -> Result<bool, String> {
+    state.cancel_flag.store(true, Ordering::SeqCst);
+    Ok(true)
+}
+
+const VERSION_WATCH_DEBOUNCE_MS: u64 = 800;
+
+// 只关心 nvm_path 直接子级中 vX.Y.Z 目录的创建、删除和重命名
+fn is_version_dir_event(event: &notify::Event) -> bool {
+    use notify::event::{EventKind, ModifyKind};
+    matches!(event.kind, EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_)))
+        && event.paths.iter().any(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy().starts_with('v'))
+                .unwrap_or(false)
+        })
+}
+
+fn start_version_watcher<R: Runtime>(app: &AppHandle<R>, nvm_path: &str) -> Result<(), String> {
+    use notify::Watcher;
+
+    let state = app.state::<VersionWatcherState>();
+    // 先停止旧的监听，nvm_path 为空或不存在时不再监听
+    state.watcher.lock().unwrap().take();
+    if nvm_path.is_empty() || !Path::new(nvm_path).is_dir() {
+        return Ok(());
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            let _ = tx.send(event);
+        }
+    })
+    .map_err(|e| tr_args("error.createWatcher", &[&e]))?;
+    watcher
+        .watch(Path::new(nvm_path), notify::RecursiveMode::NonRecursive)
+        .map_err(|e| tr_args("error.watchDir", &[&e]))?;
+
+    let app_handle = app.clone();
+    let root = PathBuf::from(nvm_path);
+    std::thread::spawn(move || {
+        let quiet = std::time::Duration::from_millis(VERSION_WATCH_DEBOUNCE_MS);
+        while let Ok(event) = rx.recv() {
+            if !is_version_dir_event(&event) {
+                continue;
+            }
+            // 安装解压期间事件密集，等到静默一段时间后只通知一次
+            loop {
+                match rx.recv_timeout(quiet) {
+                    Ok(_) => continue,
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            let _ = app_handle.emit("versions:changed", serde_json::json!({
+                "versions": list_installed_version_names(&root)
+            }));
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = refresh_tray(app_handle).await;
+            });
+        }
+    });
+
+    *state.watcher.lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+// 后台轮询前台窗口，进入已登记的项目目录时按 auto_switch_mode 提示或自动切换
+fn start_auto_switch_daemon<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_project: Option<String> = None;
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+
+            let config = match internal_get_config().await {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+            if config.auto_switch_mode == "off" {
+                last_project = None;
+                continue;
+            }
+
+            let title = match get_foreground_window_title() {
+                Some(t) => t,
+                None => continue,
+            };
+            let project = match load_app_state().projects.into_iter().find(|p| title_matches_project(&title, &p.path)) {
+                Some(p) => p,
+                None => {
+                    last_project = None;
+                    continue;
+                }
+            };
+            // 同一个项目只在进入时触发一次
+            if last_project.as_deref() == Some(project.path.as_str()) {
+                continue;
+            }
+            last_project = Some(project.path.clone());
+
+            let spec = match get_project_version_spec(&project).await {
+                Some(v) => v,
+                None => continue,
+            };
+            let installed = list_installed_version_names(Path::new(&config.nvm_path));
+            let resolved = resolve_installed_version(&spec, &installed);
+            let current = get_current_node_version(&config.nvm_symlink);
+            if resolved.is_some() && resolved == current {
+                continue;
+            }
+
+            let payload = serde_json::json!({
+                "project": project.path,
+                "required": spec,
+                "resolved": resolved,
+                "current": current
+            });
+            match resolved {
+                Some(version) if config.auto_switch_mode == "auto" => {
+                    if switch_version(app.clone(), version).await.is_ok() {
+                        let _ = app.emit("project:switched", payload);
+                        let _ = refresh_tray(app.clone()).await;
+                    }
+                }
+                // prompt 模式，或需要的版本尚未安装时交给前端提示
+                _ => {
+                    let _ = app.emit("project:detected", payload);
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+async fn register_project(dir_path: String, version_source: Option<String>) -> Result<Vec<ProjectEntry>, String> {
+    if !Path::new(&dir_path).is_dir() {
+        return Err(tr_args("error.dirMissing", &[&dir_path]));
     }
-    
-    Ok(true)
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateInfo {
-    #[serde(rename = "hasUpdate")]
-    pub has_update: bool,
-    #[serde(rename = "currentVersion")]
-    pub current_version: String,
-    #[serde(rename = "latestVersion")]
-    pub latest_version: String,
-    #[serde(rename = "releaseUrl")]
-    pub release_url: String,
-    #[serde(rename = "releaseNotes")]
-    pub release_notes: String,
-    #[serde(rename = "publishedAt")]
-    pub published_at: String,
+    let version_source = version_source.unwrap_or_else(|| "auto".to_string());
+    let state = update_app_state(|state| {
+        state.projects.retain(|p| !p.path.eq_ignore_ascii_case(&dir_path));
+        state.projects.push(ProjectEntry { path: dir_path.clone(), version_source });
+    })?;
+    Ok(state.projects)
 }
 
 #[tauri::command]
-async fn check_for_updates() -> Result<UpdateInfo, String> {
-    let client = reqwest::Client::builder()
-        .user_agent("nvm-windows-gui")
-        .build()
-        .map_err(|e| e.to_string())?;
-    
-    // 获取当前版本
-    let current_version = env!("CARGO_PKG_VERSION").to_string();
-    
-    // 查询 GitHub API
-    let response = client
-        .get("https://api.github.com/repos/Mr-Youngs/nvm-windows-GUI/releases/latest")
-        .send()
-        .await
-        .map_err(|e| format!("Network error: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err(format!("GitHub API error: {}", response.status()));
-    }
-    
-    let release: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
-    
-    let latest_version = release["tag_name"]
-        .as_str()
-        .unwrap_or("0.0.0")
-        .trim_start_matches('v')
-        .to_string();
-    
-    let release_url = release["html_url"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
-    
-    let release_notes = release["body"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
-    
-    let published_at = release["published_at"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
-    
-    // 比较版本
-    let has_update = compare_versions(&current_version, &latest_version);
-    
-    Ok(UpdateInfo {
-        has_update,
-        current_version,
-        latest_version,
-        release_url,
-        release_notes,
-        published_at,
-    })
+async fn unregister_project(dir_path: String) -> Result<Vec<ProjectEntry>, String> {
+    let state = update_app_state(|state| {
+        state.projects.retain(|p| !p.path.eq_ignore_ascii_case(&dir_path));
+    })?;
+    Ok(state.projects)
 }
 
-fn compare_versions(current: &str, latest: &str) -> bool {
-    let parse_version = |v: &str| -> Vec<u32> {
-        v.split('.')
-            .filter_map(|s| s.parse().ok())
-            .collect()
-    };
-    
-    let current_parts = parse_version(current);
-    let latest_parts = parse_version(latest);
-    
-    for i in 0..3 {
-        let c = current_parts.get(i).copied().unwrap_or(0);
-        let l = latest_parts.get(i).copied().unwrap_or(0);
-        if l > c {
-            return true;
-        }
-        if l < c {
-            return false;
-        }
-    }
-    false
+#[tauri::command]
+async fn get_registered_projects() -> Result<Vec<ProjectEntry>, String> {
+    Ok(load_app_state().projects)
 }
 
 #[tauri::command]
-fn get_default_paths() -> serde_json::Value {
-    let appdata = env::var("APPDATA").unwrap_or_else(|_| "C:\\Users\\Default\\AppData\\Roaming".to_string());
-    serde_json::json!({
-        "nvmHome": format!("{}\\nvm", appdata),
-        "nvmSymlink": format!("{}\\nodejs", appdata),
-        "globalPrefix": format!("{}\\npm-global", appdata)
-    })
+async fn set_auto_switch_mode(mode: String) -> Result<bool, String> {
+    if !["off", "prompt", "auto"].contains(&mode.as_str()) {
+        return Err(tr_args("error.invalidAutoSwitchMode", &[&mode]));
+    }
+    update_settings_file(&get_settings_path()?, &[("auto_switch_mode", Some(mode))])?;
+    Ok(true)
 }
 
 // --- 托盘菜单增强 ---
@@ -2423,10 +9208,29 @@ fn build_tray_menu<R: Runtime>(app: &AppHandle<R>) -> Result<Menu<R>, String> {
     // 显示当前版本状态
     let version_label = match &current_node {
         Some(v) => format!("Node.js v{}", v),
-        None => "未激活版本".to_string(),
+        None => tr("tray.noActiveVersion"),
     };
     let version_info = MenuItem::with_id(app, "version_info", &version_label, false, None::<&str>).map_err(|e| e.to_string())?;
     menu.append(&version_info).map_err(|e| e.to_string())?;
+
+    // 正在进行的版本安装，空闲时不显示
+    let downloads: Vec<(String, u32)> = app
+        .state::<DownloadState>()
+        .tasks
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|(version, task)| task.progress.as_ref().map(|p| (version.clone(), p.load(Ordering::Relaxed))))
+        .collect();
+    let download_label = match downloads.as_slice() {
+        [] => None,
+        [(version, progress)] => Some(tr_with("tray.downloading", &format!("{} ({}%)", version, progress))),
+        many => Some(tr_with("tray.downloadingMany", &many.len().to_string())),
+    };
+    if let Some(label) = download_label {
+        let item = MenuItem::with_id(app, "download_info", &label, false, None::<&str>).map_err(|e| e.to_string())?;
+        menu.append(&item).map_err(|e| e.to_string())?;
+    }
     
     menu.append(&tauri::menu::PredefinedMenuItem::separator(app).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
 
@@ -2451,9 +9255,31 @@ fn build_tray_menu<R: Runtime>(app: &AppHandle<R>) -> Result<Menu<R>, String> {
     }
     versions.sort_by(|a, b| b.cmp(a));
 
+    // 上一个版本和最近使用，只列出仍然安装着的版本
+    let recent: Vec<String> = load_app_state()
+        .switch_history
+        .into_iter()
+        .filter(|v| Some(v) != current_node.as_ref() && versions.contains(v))
+        .collect();
+    if let Some(prev) = recent.first() {
+        let label = format!("{} (v{})", tr("tray.previousVersion"), prev);
+        let item = MenuItem::with_id(app, "switch:prev", &label, true, None::<&str>).map_err(|e| e.to_string())?;
+        menu.append(&item).map_err(|e| e.to_string())?;
+
+        let recent_submenu = Submenu::with_id(app, "recent_submenu", tr("tray.recentVersions"), true).map_err(|e| e.to_string())?;
+        for v in &recent {
+            let item = MenuItem::with_id(app, format!("switch:{}", v), &format!("v{}", v), true, None::<&str>).map_err(|e| e.to_string())?;
+            recent_submenu.append(&item).map_err(|e| e.to_string())?;
+        }
+        menu.append(&recent_submenu).map_err(|e| e.to_string())?;
+    }
+
     // 版本切换子菜单
     if !versions.is_empty() {
-        let version_submenu = Submenu::with_id(app, "versions_submenu", "切换版本", true).map_err(|e| e.to_string())?;
+        let latest_item = MenuItem::with_id(app, format!("switch:{}", LATEST_INSTALLED), tr("tray.useLatestInstalled"), true, None::<&str>).map_err(|e| e.to_string())?;
+        menu.append(&latest_item).map_err(|e| e.to_string())?;
+
+        let version_submenu = Submenu::with_id(app, "versions_submenu", tr("tray.switchVersion"), true).map_err(|e| e.to_string())?;
         for v in versions {
             let is_checked = Some(v.clone()) == current_node;
             let item = CheckMenuItem::with_id(app, format!("switch:{}", v), &format!("v{}", v), true, is_checked, None::<&str>).map_err(|e| e.to_string())?;
@@ -2465,15 +9291,15 @@ fn build_tray_menu<R: Runtime>(app: &AppHandle<R>) -> Result<Menu<R>, String> {
     menu.append(&tauri::menu::PredefinedMenuItem::separator(app).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
 
     // 窗口控制
-    let show = MenuItem::with_id(app, "show", "显示窗口", true, None::<&str>).map_err(|e| e.to_string())?;
-    let hide = MenuItem::with_id(app, "hide", "隐藏窗口", true, None::<&str>).map_err(|e| e.to_string())?;
+    let show = MenuItem::with_id(app, "show", tr("tray.show"), true, None::<&str>).map_err(|e| e.to_string())?;
+    let hide = MenuItem::with_id(app, "hide", tr("tray.hide"), true, None::<&str>).map_err(|e| e.to_string())?;
     menu.append(&show).map_err(|e| e.to_string())?;
     menu.append(&hide).map_err(|e| e.to_string())?;
     
     menu.append(&tauri::menu::PredefinedMenuItem::separator(app).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
     
     // 退出
-    let quit = MenuItem::with_id(app, "quit", "退出", true, None::<&str>).map_err(|e| e.to_string())?;
+    let quit = MenuItem::with_id(app, "quit", tr("tray.quit"), true, None::<&str>).map_err(|e| e.to_string())?;
     menu.append(&quit).map_err(|e| e.to_string())?;
 
     Ok(menu)
@@ -2500,7 +9326,24 @@ fn main() {
             }
         }))
         .manage(DownloadState { tasks: Mutex::new(HashMap::new()) })
+        .manage(BatchState { current: Mutex::new(None) })
+        .manage(NvmInstallState { cancel_tx: Mutex::new(None) })
+        .manage(PrefixMigrationState {
+            cancel_flag: Mutex::new(Arc::new(AtomicBool::new(false))),
+        })
+        .manage(StorageScanState {
+            cancel_flag: Mutex::new(Arc::new(AtomicBool::new(false))),
+            total_cancel_flag: Mutex::new(Arc::new(AtomicBool::new(false))),
+            total_in_flight: Mutex::new(None),
+            size_cache: Mutex::new(HashMap::new()),
+        })
+        .manage(VersionWatcherState { watcher: Mutex::new(None) })
+        .manage(PrefetchState { cancel_flag: Arc::new(AtomicBool::new(false)) })
         .setup(|app| {
+            // 托盘菜单使用配置中的语言构建
+            if let Ok(content) = get_settings_path().and_then(|p| fs::read_to_string(p).map_err(|e| e.to_string())) {
+                set_current_locale(&parse_nvm_settings(&content).locale);
+            }
             let tray_menu = build_tray_menu(app.handle())?;
             let _tray = TrayIconBuilder::with_id("main_tray")
                 .icon(app.default_window_icon().unwrap().clone())
@@ -2522,11 +9365,29 @@ fn main() {
                                 let _ = window.hide();
                             }
                         }
+                        "switch:prev" => {
+                            let app_handle = app.clone();
+                            tauri::async_runtime::spawn(async move {
+                                // 历史中第一个不是当前版本且仍已安装的即为上一个版本
+                                if let Ok(config) = internal_get_config().await {
+                                    let current = get_current_node_version(&config.nvm_symlink);
+                                    let installed = list_installed_version_names(Path::new(&config.nvm_path));
+                                    let prev = load_app_state()
+                                        .switch_history
+                                        .into_iter()
+                                        .find(|v| Some(v) != current.as_ref() && installed.contains(v));
+                                    if let Some(version) = prev {
+                                        let _ = switch_version(app_handle.clone(), version).await;
+                                    }
+                                }
+                                let _ = refresh_tray(app_handle).await;
+                            });
+                        }
                         _ if id.starts_with("switch:") => {
                             let version = id.strip_prefix("switch:").unwrap().to_string();
                             let app_handle = app.clone();
                             tauri::async_runtime::spawn(async move {
-                                let _ = switch_version(version).await;
+                                let _ = switch_version(app_handle.clone(), version).await;
                                 // 切换后刷新菜单以更新勾选状态
                                 let _ = refresh_tray(app_handle).await;
                             });
@@ -2554,59 +9415,162 @@ fn main() {
                     }
                 })
                 .build(app)?;
+            start_auto_switch_daemon(app.handle().clone());
+            start_prefetch(app.handle().clone());
+            start_permission_check(app.handle().clone());
+            start_theme_watcher(app.handle().clone());
+            if let Ok(content) = get_settings_path().and_then(|p| fs::read_to_string(p).map_err(|e| e.to_string())) {
+                let _ = start_version_watcher(app.handle(), &parse_nvm_settings(&content).nvm_path);
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_config,
             set_config,
             get_installed_versions,
+            stream_installed_versions,
             get_version_size,
+            test_version_runs,
+            list_version_binaries,
+            corepack_status,
+            version_capabilities,
+            corepack_enable,
+            corepack_disable,
+            detect_package_managers,
             get_active_version,
+            get_default_version,
+            set_default_version,
             get_available_versions,
+            get_version_meta,
             get_total_size,
+            cancel_total_size,
+            cancel_prefetch,
+            get_storage_breakdown,
+            cancel_storage_scan,
+            recompute_version_size,
             switch_version,
             install_version,
             uninstall_version,
+            install_version_at,
+            scan_reparse_points,
             get_global_packages,
             search_packages,
+            get_package_download_trend,
             install_global_package,
             uninstall_global_package,
             update_global_package,
             check_outdated_packages,
+            check_all_outdated,
+            audit_global_packages,
+            check_package_outdated,
+            get_linked_packages,
+            detect_bin_conflicts,
+            scan_npm_cruft,
+            clean_npm_cruft,
             get_mirror_presets,
+            set_mirror_visibility,
             get_current_mirror,
             test_all_mirror_speed,
+            check_version_availability,
+            check_mirror_freshness,
+            get_download_size,
+            estimate_batch_size,
+            get_version_changelog,
+            get_cached_archives,
+            clear_archive_cache,
+            get_download_cache_info,
+            clean_download_cache,
             get_arch,
             set_arch,
+            validate_arch_on_mirror,
+            get_close_action,
+            set_close_action,
             select_directory,
             validate_path,
+            check_path_sync_risk,
             refresh_tray,
             // NVM 安装相关
             check_nvm_installation,
             get_nvm_latest_release,
             download_and_install_nvm,
+            cancel_nvm_install,
+            discover_nvm_installs,
+            adopt_nvm_install,
             get_default_paths,
             // 共享全局包相关
             get_global_prefix,
             set_global_prefix,
+            preview_global_prefix_change,
+            cancel_prefix_migration,
+            verify_npm_prefix,
+            fix_npm_prefix,
+            normalize_npmrc_prefix,
+            get_npm_cache_dir,
+            get_npm_quiet,
+            set_npm_quiet,
+            set_npm_cache_dir,
+            check_registry_consistency,
+            reconcile_registry,
+            get_npm_bin_dir,
+            check_npm_bin_on_path,
             get_shared_packages_config,
             check_path_contains,
             add_to_user_path,
+            get_effective_path,
+            check_symlink_on_path,
+            get_last_env_change,
+            get_env_change_history,
+            run_health_scan,
+            check_permissions,
+            broadcast_env_change,
+            uninstall_nvm,
+            env_refresh_status,
+            check_git_installation,
             // 包版本查询
             get_package_versions,
+            resolve_dist_tag,
+            get_package_bins,
             // 下载控制
             pause_download,
             resume_download,
             cancel_download,
+            cancel_all_tasks,
             // 更新检查
             check_for_updates,
             // 导入导出
             export_config,
+            get_summary_text,
+            export_env_script,
+            copy_to_clipboard,
+            get_app_info,
+            monitor_node_processes,
+            check_cache_health,
+            repair_cache,
             import_config,
+            validate_import,
+            install_from_export,
+            pause_batch,
+            resume_batch,
+            cancel_batch,
             save_config_to_file,
             load_config_from_file,
+            export_install_script,
             // .nvmrc 支持
-            read_nvmrc
+            read_nvmrc,
+            resolve_for_directory,
+            // 手动编辑配置文件
+            open_config_file,
+            reload_config,
+            reset_app_settings,
+            set_locale,
+            get_theme,
+            set_theme,
+            get_system_theme,
+            // 项目自动切换
+            register_project,
+            unregister_project,
+            get_registered_projects,
+            set_auto_switch_mode
         ])
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
@@ -2630,45 +9594,42 @@ fn main() {
                     "quit" => app_handle.exit(0),
                     "hide" => { let _ = window_.hide(); },
                     _ => {
+                        // 对话框已打开时忽略重复的关闭事件
+                        if CLOSE_DIALOG_OPEN.swap(true, Ordering::SeqCst) {
+                            return;
+                        }
                         app_handle.dialog()
-                            .message("您想将应用最小化到托盘还是直接退出？\n\n[是]：直接退出并记住选择\n[否]：最小化到托盘并记住选择")
-                            .title("退出确认")
+                            .message(tr("dialog.close.message"))
+                            .title(tr("dialog.close.title"))
                             .kind(MessageDialogKind::Info)
-                            .buttons(MessageDialogButtons::YesNo)
-                            .show(move |result| {
+                            .buttons(MessageDialogButtons::YesNoCancel)
+                            .show_with_result(move |result| {
+                                CLOSE_DIALOG_OPEN.store(false, Ordering::SeqCst);
+                                let (action, remember) = match result {
+                                    MessageDialogResult::Yes => ("quit", true),
+                                    MessageDialogResult::No => ("hide", true),
+                                    // 取消或按 Esc 关闭对话框：只执行本次操作，不修改默认行为
+                                    _ => ("hide", false),
+                                };
                                 let config_path = get_settings_path().unwrap_or_default();
-                                if config_path.exists() {
-                                    let content = fs::read_to_string(&config_path).unwrap_or_default();
-                                    let mut config = parse_nvm_settings(&content);
-                                    if result {
-                                        config.close_action = "quit".to_string();
-                                        // 同步保存选择
-                                        let updated_content = format!(
-                                            "root: {}\npath: {}\nnode_mirror: {}\nnpm_mirror: {}\narch: {}\nclose_action: {}\n",
-                                            config.nvm_path, config.nvm_symlink, config.node_mirror, config.npm_mirror, config.arch, config.close_action
-                                        );
-                                        let _ = fs::write(config_path, updated_content);
-                                        app_handle.exit(0);
-                                    } else {
-                                        config.close_action = "hide".to_string();
-                                        // 同步保存选择
-                                        let updated_content = format!(
-                                            "root: {}\npath: {}\nnode_mirror: {}\nnpm_mirror: {}\narch: {}\nclose_action: {}\n",
-                                            config.nvm_path, config.nvm_symlink, config.node_mirror, config.npm_mirror, config.arch, config.close_action
-                                        );
-                                        let _ = fs::write(config_path, updated_content);
-                                        let _ = window_.hide();
-                                    }
-                                } else {
-                                    if result { app_handle.exit(0); } else { let _ = window_.hide(); }
+                                if remember && config_path.exists() {
+                                    // 只修改 close_action，其余配置以写入时磁盘上的内容为准
+                                    let _ = update_settings_file(&config_path, &[("close_action", Some(action.to_string()))]);
                                 }
+                                if action == "quit" { app_handle.exit(0); } else { let _ = window_.hide(); }
                             });
                     }
                 }
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // 退出时停止目录监听
+            if let tauri::RunEvent::Exit = event {
+                app.state::<VersionWatcherState>().watcher.lock().unwrap().take();
+            }
+        });
 }
 
 #[cfg(test)]
@@ -2694,6 +9655,418 @@ mod tests {
         assert_eq!(config.arch, "32");
     }
 
+    #[test]
+    fn test_normalize_mirror_url() {
+        assert_eq!(normalize_mirror_url("https://npmmirror.com/mirrors/node").unwrap(), "https://npmmirror.com/mirrors/node/");
+        assert_eq!(normalize_mirror_url("https://npmmirror.com/mirrors/node/").unwrap(), "https://npmmirror.com/mirrors/node/");
+        assert_eq!(normalize_mirror_url("https://npmmirror.com/mirrors/node//").unwrap(), "https://npmmirror.com/mirrors/node/");
+        assert_eq!(normalize_mirror_url("  http://10.0.0.1:8080/node  ").unwrap(), "http://10.0.0.1:8080/node/");
+        assert_eq!(normalize_mirror_url("   ").unwrap(), "");
+        assert!(normalize_mirror_url("ftp://mirror.example.com/node/").is_err());
+        assert!(normalize_mirror_url("npmmirror.com/mirrors/node").is_err());
+        assert!(normalize_mirror_url("https://").is_err());
+        assert!(normalize_mirror_url("https://mirror example.com/").is_err());
+    }
+
+    #[test]
+    fn test_rewrite_npmrc_prefix() {
+        let content = "registry=https://registry.npmmirror.com/\nprefix=C:\\old\n# prefix=C:\\commented\n  PREFIX = D:\\other  \nstrict-ssl=false";
+        let (rewritten, removed) = rewrite_npmrc_key(content, "prefix", Some("E:\\shared"));
+        assert_eq!(rewritten, "registry=https://registry.npmmirror.com/\n# prefix=C:\\commented\nstrict-ssl=false\nprefix=E:\\shared");
+        assert_eq!(removed, vec!["C:\\old".to_string(), "D:\\other".to_string()]);
+    }
+
+    #[test]
+    fn test_version_from_link_target() {
+        assert_eq!(version_from_link_target(Path::new("C:\\nvm\\v20.11.0")), Some("20.11.0".to_string()));
+        assert_eq!(version_from_link_target(Path::new("\\\\?\\D:\\node-versions\\v18.19.0\\")), Some("18.19.0".to_string()));
+        assert_eq!(version_from_link_target(Path::new("\\??\\C:\\nvm\\v16.20.2")), Some("16.20.2".to_string()));
+        assert_eq!(version_from_link_target(Path::new("C:\\Program Files\\nodejs")), None);
+        assert_eq!(version_from_link_target(Path::new("C:\\nvm\\vendor")), None);
+    }
+
+    #[test]
+    fn test_build_download_url_for_presets() {
+        let expected = [
+            ("official", "https://nodejs.org/dist/v20.11.0/node-v20.11.0-win-x64.zip"),
+            ("taobao", "https://npmmirror.com/mirrors/node/v20.11.0/node-v20.11.0-win-x64.zip"),
+            ("huawei", "https://repo.huaweicloud.com/nodejs/v20.11.0/node-v20.11.0-win-x64.zip"),
+            ("tsinghua", "https://mirrors.tuna.tsinghua.edu.cn/nodejs-release/v20.11.0/node-v20.11.0-win-x64.zip"),
+        ];
+        let presets = get_all_mirror_presets();
+        assert_eq!(presets.len(), expected.len());
+        for (id, url) in expected {
+            let preset = presets.iter().find(|p| p.id == id).unwrap();
+            assert_eq!(build_download_url(&preset.node_url, "v20.11.0", "x64"), url);
+        }
+        // 自定义镜像沿用官方目录结构，末尾斜杠可有可无
+        assert_eq!(
+            build_download_url("https://mirror.example.com/node", "v18.19.0", "arm64"),
+            "https://mirror.example.com/node/v18.19.0/node-v18.19.0-win-arm64.zip"
+        );
+    }
+
+    #[test]
+    fn test_diff_env_snapshots() {
+        let before = vec![None, Some("C:\\nodejs".to_string()), Some("C:\\Windows;C:\\old-prefix".to_string())];
+        let after = vec![
+            Some("C:\\nvm".to_string()),
+            Some("C:\\nodejs".to_string()),
+            Some("c:\\windows;C:\\nvm;C:\\nodejs".to_string()),
+        ];
+        let (added, removed, changed) = diff_env_snapshots(&before, &after);
+        assert_eq!(added, vec!["C:\\nvm".to_string(), "C:\\nodejs".to_string()]);
+        assert_eq!(removed, vec!["C:\\old-prefix".to_string()]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].name, "NVM_HOME");
+        assert_eq!(changed[0].after.as_deref(), Some("C:\\nvm"));
+    }
+
+    #[test]
+    fn test_parse_package_manager_output() {
+        let pnpm = r#"[{"path":"C:\\pnpm\\global\\5","dependencies":{"typescript":{"version":"5.3.3"},"my-cli":{"version":"link:..\\..\\dev\\my-cli"}}}]"#;
+        let packages = parse_pnpm_list_output(pnpm);
+        assert_eq!(packages.len(), 2);
+        assert!(packages.iter().any(|p| p.name == "typescript" && p.version == "5.3.3" && !p.is_linked));
+        assert!(packages.iter().any(|p| p.name == "my-cli" && p.is_linked));
+
+        let yarn = "{\"type\":\"info\",\"data\":\"\\\"@vue/cli@5.0.8\\\" has binaries:\"}\n{\"type\":\"list\",\"data\":{\"type\":\"bins-@vue/cli\",\"items\":[\"vue\"]}}";
+        let packages = parse_yarn_global_list(yarn);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "@vue/cli");
+        assert_eq!(packages[0].version, "5.0.8");
+
+        let outdated = r#"{"type":"table","data":{"head":["Package","Current","Wanted","Latest","Package Type","URL"],"body":[["typescript","5.0.0","5.0.0","5.3.3","dependencies","https://www.typescriptlang.org/"]]}}"#;
+        let outdated = parse_yarn_outdated(outdated);
+        assert_eq!(outdated.len(), 1);
+        assert_eq!(outdated[0].name, "typescript");
+        assert_eq!(outdated[0].latest, "5.3.3");
+        assert_eq!(outdated[0].manager, "yarn");
+
+        assert_eq!(global_package_args("yarn", "install", "pnpm"), vec!["global", "add", "pnpm"]);
+        assert_eq!(global_package_args("npm", "uninstall", "pnpm"), vec!["uninstall", "-g", "pnpm"]);
+    }
+
+    #[test]
+    fn test_prepare_install_dir_complete() {
+        let dir = env::temp_dir().join(format!("nvm-gui-test-complete-{}", std::process::id()));
+        fs::create_dir_all(dir.join("node_modules")).unwrap();
+        fs::write(dir.join("node.exe"), b"").unwrap();
+        assert_eq!(prepare_install_dir(&dir).unwrap(), InstallDirState::Complete);
+        // 已安装的版本不做任何清理
+        assert!(dir.join("node_modules").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prepare_install_dir_incomplete() {
+        let dir = env::temp_dir().join(format!("nvm-gui-test-incomplete-{}", std::process::id()));
+        fs::create_dir_all(dir.join("node_modules").join("npm")).unwrap();
+        fs::write(dir.join("npm.cmd"), b"").unwrap();
+        fs::write(dir.join("node.zip.part"), b"partial").unwrap();
+        assert_eq!(prepare_install_dir(&dir).unwrap(), InstallDirState::Cleaned { kept_part: true });
+        assert!(!dir.join("node_modules").exists());
+        assert!(!dir.join("npm.cmd").exists());
+        assert_eq!(fs::read(dir.join("node.zip.part")).unwrap(), b"partial");
+
+        // 没有 .part 时清空整个目录
+        fs::remove_file(dir.join("node.zip.part")).unwrap();
+        fs::write(dir.join("node.zip"), b"").unwrap();
+        assert_eq!(prepare_install_dir(&dir).unwrap(), InstallDirState::Cleaned { kept_part: false });
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(prepare_install_dir(&dir).unwrap(), InstallDirState::Missing);
+    }
+
+    #[test]
+    fn test_build_env_script() {
+        let cmd = build_env_script("cmd", "C:\\nvm", "C:\\nvm4w\\nodejs", Some("D:\\npm-global")).unwrap();
+        assert!(cmd.starts_with("@echo off\r\nrem "));
+        assert!(cmd.contains("setx NVM_HOME \"C:\\nvm\""));
+        assert!(cmd.contains("find /i \";D:\\npm-global;\""));
+        // 前置顺序倒序写入，最后处理的 NVM_HOME 排在最前
+        let home = cmd.find("find /i \";C:\\nvm;\"").unwrap();
+        assert!(cmd.find("find /i \";D:\\npm-global;\"").unwrap() < home);
+        assert!(cmd.contains("reg add HKCU\\Environment /v Path /t REG_EXPAND_SZ /d \"%USER_PATH%\" /f"));
+        assert!(!cmd.contains("setx PATH"));
+
+        let ps = build_env_script("powershell", "C:\\it's", "C:\\nvm4w\\nodejs", None).unwrap();
+        assert!(ps.starts_with("# "));
+        assert!(ps.contains("$env:NVM_HOME = 'C:\\it''s'"));
+        assert!(ps.contains("foreach ($dir in @('C:\\nvm4w\\nodejs', 'C:\\it''s'))"));
+        assert!(ps.contains("GetValue('Path', '', 'DoNotExpandEnvironmentNames')"));
+        assert!(ps.contains("-Type ExpandString"));
+
+        assert!(build_env_script("bash", "a", "b", None).is_err());
+    }
+
+    #[test]
+    fn test_build_prefix_candidates() {
+        let appdata = "C:\\Users\\me\\AppData\\Roaming";
+        let nvm_dirs = ["C:\\nvm", "C:\\nvm4w\\nodejs"];
+        let ids = |c: &[PrefixCandidate]| c.iter().map(|c| c.id.clone()).collect::<Vec<_>>();
+
+        let fresh = build_prefix_candidates(None, None, appdata, "C:\\nvm", &nvm_dirs);
+        assert_eq!(ids(&fresh), vec!["default", "nvmHome"]);
+        assert_eq!(fresh[1].path, "C:\\nvm\\npm-global");
+
+        // npm 默认 prefix 指向当前版本目录时不作为候选
+        let per_version = build_prefix_candidates(None, Some("C:\\nvm4w\\nodejs"), appdata, "C:\\nvm", &nvm_dirs);
+        assert_eq!(ids(&per_version), vec!["default", "nvmHome"]);
+
+        let existing = build_prefix_candidates(Some("D:\\shared"), Some("d:\\shared\\"), appdata, "C:\\nvm", &nvm_dirs);
+        assert_eq!(ids(&existing), vec!["configured", "default", "nvmHome"]);
+
+        let npm = build_prefix_candidates(None, Some("D:\\npm"), appdata, "C:\\nvm", &nvm_dirs);
+        assert_eq!(npm[0].id, "existing");
+        assert_eq!(npm[0].label, tr("prefix.existing"));
+    }
+
+    #[test]
+    fn test_meets_feature_threshold() {
+        let corepack = &["14.19.0", "16.9.0"];
+        assert!(!meets_feature_threshold("14.18.3", corepack));
+        assert!(meets_feature_threshold("v14.19.0", corepack));
+        assert!(!meets_feature_threshold("15.14.0", corepack));
+        assert!(!meets_feature_threshold("16.8.0", corepack));
+        assert!(meets_feature_threshold("16.20.2", corepack));
+        assert!(meets_feature_threshold("22.0.0", corepack));
+        assert!(!meets_feature_threshold("17.0.0", &["18.0.0"]));
+        assert!(meets_feature_threshold("18.0.0", &["18.0.0"]));
+    }
+
+    #[test]
+    fn test_dist_tag_specs() {
+        assert!(is_dist_tag_spec("next"));
+        assert!(is_dist_tag_spec("beta"));
+        assert!(is_dist_tag_spec("next-15"));
+        assert!(!is_dist_tag_spec("1.2.3"));
+        assert!(!is_dist_tag_spec("^5.0.0"));
+        assert!(!is_dist_tag_spec("v5.0.0"));
+        assert!(!is_dist_tag_spec("x"));
+        assert!(!is_dist_tag_spec("github:user/repo"));
+
+        assert_eq!(split_package_spec("typescript@next"), ("typescript".to_string(), Some("next".to_string())));
+        assert_eq!(split_package_spec("@vue/cli@beta"), ("@vue/cli".to_string(), Some("beta".to_string())));
+        assert_eq!(split_package_spec("@vue/cli"), ("@vue/cli".to_string(), None));
+
+        let tags = serde_json::json!({ "latest": "5.3.3", "next": "5.4.0-dev.20240101" });
+        assert_eq!(lookup_dist_tag(&tags, "typescript", "next").unwrap(), "5.4.0-dev.20240101");
+        let err = lookup_dist_tag(&tags, "typescript", "beta").unwrap_err();
+        assert!(err.contains("latest, next"));
+    }
+
+    #[test]
+    fn test_uninstall_backup_survives_removal() {
+        let base = env::temp_dir().join(format!("nvm-gui-test-uninstall-{}", std::process::id()));
+        let home = base.join("nvm");
+        let local = base.join("local");
+        fs::create_dir_all(home.join("v20.11.1")).unwrap();
+        fs::create_dir_all(home.join("backups")).unwrap();
+        fs::write(home.join("settings.txt"), b"root: x").unwrap();
+
+        let home_str = home.to_string_lossy().to_string();
+        let root = uninstall_backup_root(Some(&local.to_string_lossy()), &[home_str.as_str()]);
+        assert!(!is_same_or_nested_path(&root.to_string_lossy(), &home_str));
+        let backup_dir = root.join("uninstall-test");
+        copy_backup_files(&backup_dir, &[home.join("settings.txt")]).unwrap();
+
+        let (mut removed, mut kept) = (Vec::new(), Vec::new());
+        remove_nvm_dir(&home, true, &[], &mut removed, &mut kept).unwrap();
+        assert!(home.join("v20.11.1").exists());
+        assert!(!home.join("backups").exists());
+        // 保留了版本的目录没有被删除，不计入 removed
+        assert!(removed.is_empty());
+        remove_nvm_dir(&home, false, &[], &mut removed, &mut kept).unwrap();
+        assert!(!home.exists());
+        assert_eq!(fs::read(backup_dir.join("settings.txt")).unwrap(), b"root: x");
+
+        // LOCALAPPDATA 位于将被删除的目录中时不使用
+        let nested = uninstall_backup_root(Some(&home_str), &[home_str.as_str()]);
+        assert!(!is_same_or_nested_path(&nested.to_string_lossy(), &home_str));
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_remove_nvm_dir_keeps_nested_root() {
+        let base = env::temp_dir().join(format!("nvm-gui-test-nested-root-{}", std::process::id()));
+        let home = base.join("nvm");
+        let root = home.join("versions");
+        fs::create_dir_all(root.join("v18.19.0")).unwrap();
+        fs::write(root.join("v18.19.0").join("node.exe"), b"").unwrap();
+        fs::write(home.join("nvm.exe"), b"").unwrap();
+
+        let (mut removed, mut kept) = (Vec::new(), Vec::new());
+        remove_nvm_dir(&root, true, &[], &mut removed, &mut kept).unwrap();
+        remove_nvm_dir(&home, true, &[root.as_path()], &mut removed, &mut kept).unwrap();
+        assert!(root.join("v18.19.0").join("node.exe").exists());
+        assert!(!home.join("nvm.exe").exists());
+        assert_eq!(kept, vec![root.join("v18.19.0").to_string_lossy().to_string()]);
+        assert!(removed.is_empty());
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_rollback_prefix_move() {
+        let base = env::temp_dir().join(format!("nvm-gui-test-prefix-rollback-{}", std::process::id()));
+        let (old, new) = (base.join("old"), base.join("new"));
+        fs::create_dir_all(new.join("node_modules").join("pkg")).unwrap();
+        fs::write(new.join("node_modules").join("pkg").join("index.js"), b"x").unwrap();
+        fs::write(new.join("npm.cmd"), b"@echo").unwrap();
+        // 源位置残留部分目录（跨盘符迁移删除源目录不完整）时也能恢复
+        fs::create_dir_all(old.join("node_modules")).unwrap();
+
+        rollback_prefix_move(&old.join("node_modules"), &new.join("node_modules"));
+        rollback_prefix_move(&old.join("npm.cmd"), &new.join("npm.cmd"));
+        assert_eq!(fs::read(old.join("node_modules").join("pkg").join("index.js")).unwrap(), b"x");
+        assert!(old.join("npm.cmd").exists());
+        assert!(!new.join("node_modules").exists());
+        assert!(!new.join("npm.cmd").exists());
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_title_matches_project() {
+        assert!(title_matches_project("D:\\work\\app", "D:\\Work\\App\\"));
+        assert!(title_matches_project("D:\\work\\app\\src", "D:\\work\\app"));
+        assert!(title_matches_project("MINGW64:/d/work/app/src", "/d/work/app"));
+        assert!(title_matches_project("D:\\work\\app - Windows PowerShell", "D:\\work\\app"));
+        // 路径后紧跟其他字符时不是同一个目录
+        assert!(!title_matches_project("D:\\work\\app2", "D:\\work\\app"));
+        assert!(!title_matches_project("D:\\work\\app-old - cmd", "D:\\work\\app"));
+        // 只有文件夹名的标题不匹配，其他位置的同名目录也不会被误判
+        assert!(!title_matches_project("app", "D:\\work\\app"));
+        assert!(!title_matches_project("", "D:\\work\\app"));
+    }
+
+    #[test]
+    fn test_parse_dist_arch() {
+        assert_eq!(parse_dist_arch("64"), Ok("x64"));
+        assert_eq!(parse_dist_arch("x86"), Ok("x86"));
+        assert_eq!(parse_dist_arch("arm64"), Ok("arm64"));
+        assert!(parse_dist_arch("amd64").is_err());
+        assert!(parse_dist_arch("").is_err());
+    }
+
+    #[test]
+    fn test_tr_args() {
+        assert_eq!(tr_args("error.backupFile", &[&"C:\\{}", &404]), "备份 C:\\{} 失败: 404");
+        assert_eq!(tr_args("error.sizeMismatch", &[&1]), "文件大小不符 (期望 1 字节, 实际 {} 字节)");
+        assert_eq!(tr_args("error.unknown", &[&1]), "error.unknown");
+        // 所有消息码唯一，参数个数在两种语言中一致
+        for (i, (code, zh, en)) in MESSAGES.iter().enumerate() {
+            assert!(MESSAGES[i + 1..].iter().all(|(c, _, _)| c != code), "{}", code);
+            assert_eq!(zh.matches("{}").count(), en.matches("{}").count(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_parse_npm_audit_output_npm6() {
+        let val = serde_json::json!({
+            "advisories": {
+                "1179": {
+                    "module_name": "minimist",
+                    "severity": "low",
+                    "title": "Prototype Pollution",
+                    "url": "https://npmjs.com/advisories/1179",
+                    "patched_versions": ">=1.2.3"
+                },
+                "577": {
+                    "module_name": "lodash",
+                    "severity": "high",
+                    "title": "Prototype Pollution",
+                    "patched_versions": "<0.0.0"
+                }
+            },
+            "metadata": { "vulnerabilities": { "low": 1, "high": 1 } }
+        });
+        let mut findings = parse_npm_audit_output(&val).unwrap();
+        findings.sort_by(|a, b| a.package.cmp(&b.package));
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].package, "lodash");
+        assert!(!findings[0].fix_available);
+        assert_eq!(findings[0].url, None);
+        assert_eq!(findings[1].package, "minimist");
+        assert_eq!(findings[1].severity, "low");
+        assert!(findings[1].fix_available);
+        assert_eq!(findings[1].url.as_deref(), Some("https://npmjs.com/advisories/1179"));
+    }
+
+    #[test]
+    fn test_parse_npm_audit_output_npm7() {
+        let val = serde_json::json!({
+            "auditReportVersion": 2,
+            "vulnerabilities": {
+                "semver": {
+                    "name": "semver",
+                    "severity": "moderate",
+                    "via": [{
+                        "source": 1092310,
+                        "name": "semver",
+                        "title": "semver vulnerable to Regular Expression Denial of Service",
+                        "url": "https://github.com/advisories/GHSA-c2qf-rxjj-qqgw",
+                        "severity": "moderate"
+                    }],
+                    "fixAvailable": { "name": "nodemon", "version": "3.0.1", "isSemVerMajor": true }
+                },
+                "nodemon": {
+                    "name": "nodemon",
+                    "severity": "moderate",
+                    "via": ["semver"],
+                    "fixAvailable": false
+                }
+            }
+        });
+        let findings = parse_npm_audit_output(&val).unwrap();
+        // 只经由其他依赖引入的条目（via 为字符串）不单独计入
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].package, "semver");
+        assert_eq!(findings[0].severity, "moderate");
+        assert!(findings[0].fix_available);
+
+        let clean = serde_json::json!({ "auditReportVersion": 2, "vulnerabilities": {} });
+        assert_eq!(parse_npm_audit_output(&clean).map(|f| f.len()), Some(0));
+    }
+
+    #[test]
+    fn test_parse_npm_audit_output_malformed() {
+        let enolock = serde_json::json!({
+            "error": { "code": "ENOLOCK", "summary": "This command requires an existing lockfile." }
+        });
+        assert!(parse_npm_audit_output(&enolock).is_none());
+        assert!(parse_npm_audit_output(&serde_json::json!({})).is_none());
+        assert!(parse_npm_audit_output(&serde_json::json!("not an object")).is_none());
+        assert!(parse_npm_audit_output(&serde_json::json!({ "vulnerabilities": [] })).is_none());
+
+        // 缺少字段时使用默认值而不是报错
+        let partial = serde_json::json!({ "vulnerabilities": { "pkg": { "via": [{}] } } });
+        let findings = parse_npm_audit_output(&partial).unwrap();
+        assert_eq!(findings[0].package, "pkg");
+        assert_eq!(findings[0].severity, "unknown");
+        assert!(!findings[0].fix_available);
+    }
+
+    #[test]
+    fn test_is_npm_cruft_name() {
+        assert!(is_npm_cruft_name(".staging"));
+        assert!(is_npm_cruft_name(".lodash-Xk3aB9Qz"));
+        assert!(is_npm_cruft_name(".cli-spinners-a1b2c3d4"));
+        // 正常的包名和 node_modules 中的其他条目
+        assert!(!is_npm_cruft_name("staging"));
+        assert!(!is_npm_cruft_name("lodash"));
+        assert!(!is_npm_cruft_name("cli-spinners"));
+        assert!(!is_npm_cruft_name("is-number-12345678"));
+        assert!(!is_npm_cruft_name("@types"));
+        assert!(!is_npm_cruft_name(".bin"));
+        assert!(!is_npm_cruft_name(".package-lock.json"));
+        assert!(!is_npm_cruft_name(".modules.yaml"));
+        // 后缀长度或字符不符合
+        assert!(!is_npm_cruft_name(".lodash-abc123"));
+        assert!(!is_npm_cruft_name(".lodash-abc_1234"));
+        assert!(!is_npm_cruft_name(".-a1b2c3d4"));
+    }
+
     #[test]
     fn test_get_mirror_presets() {
         let presets = get_all_mirror_presets();
@@ -2706,4 +10079,154 @@ mod tests {
         let size = get_dir_size(Path::new("C:\\nonexistent_folder_xyz"));
         assert_eq!(size, 0);
     }
+
+    #[test]
+    fn test_expand_env_vars() {
+        env::set_var("NVM_GUI_TEST_VAR", "C:\\tools");
+        assert_eq!(expand_env_vars("%NVM_GUI_TEST_VAR%\\bin"), "C:\\tools\\bin");
+        assert_eq!(expand_env_vars("%NVM_GUI_UNDEFINED_VAR%\\bin"), "%NVM_GUI_UNDEFINED_VAR%\\bin");
+        assert_eq!(expand_env_vars("50% off"), "50% off");
+    }
+
+    #[test]
+    fn test_parse_pe_machine() {
+        let mut header = vec![0u8; 0x90];
+        header[0..2].copy_from_slice(b"MZ");
+        header[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        header[0x80..0x84].copy_from_slice(b"PE\0\0");
+        header[0x84..0x86].copy_from_slice(&0x8664u16.to_le_bytes());
+        assert_eq!(parse_pe_machine(&header), Some("x64"));
+
+        header[0x84..0x86].copy_from_slice(&0x014Cu16.to_le_bytes());
+        assert_eq!(parse_pe_machine(&header), Some("x86"));
+
+        assert_eq!(parse_pe_machine(b"<html>not a binary</html>"), None);
+    }
+
+    #[test]
+    fn test_merge_settings_content_preserves_unknown_keys() {
+        let content = "root: C:\\nvm\n# comment\nproxy: none\narch: 64\nglobal_prefix: D:\\npm\n";
+        let merged = merge_settings_content(content, &[
+            ("arch", Some("32".to_string())),
+            ("close_action", Some("hide".to_string())),
+            ("global_prefix", None),
+        ]);
+        assert_eq!(merged, "root: C:\\nvm\n# comment\nproxy: none\narch: 32\nclose_action: hide\n");
+    }
+
+    #[test]
+    fn test_update_settings_file_concurrent_writers() {
+        let path = env::temp_dir().join(format!("nvm_gui_settings_test_{}.txt", std::process::id()));
+        fs::write(&path, "root: C:\\nvm\npath: C:\\nodejs\nproxy: none\n").unwrap();
+
+        // 模拟 set_config 与关闭对话框同时写入：各自只改自己的键，互不覆盖
+        let config_writer = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for i in 0..50 {
+                    update_settings_file(&path, &[("node_mirror", Some(format!("https://mirror{}/", i)))]).unwrap();
+                }
+            })
+        };
+        let close_writer = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for i in 0..50 {
+                    let action = if i % 2 == 0 { "hide" } else { "quit" };
+                    update_settings_file(&path, &[("close_action", Some(action.to_string()))]).unwrap();
+                }
+            })
+        };
+        config_writer.join().unwrap();
+        close_writer.join().unwrap();
+
+        let config = parse_nvm_settings(&fs::read_to_string(&path).unwrap());
+        let _ = fs::remove_file(&path);
+        assert_eq!(config.nvm_path, "C:\\nvm");
+        assert_eq!(config.nvm_symlink, "C:\\nodejs");
+        assert_eq!(config.node_mirror, "https://mirror49/");
+        assert_eq!(config.close_action, "quit");
+    }
+
+    #[test]
+    fn test_is_prerelease_version() {
+        assert!(is_prerelease_version("v22.0.0-rc.1"));
+        assert!(is_prerelease_version("v23.0.0-nightly20240101abcdef"));
+        assert!(!is_prerelease_version("v20.11.1"));
+        assert!(!is_prerelease_version("18.0.0"));
+    }
+
+    #[test]
+    fn test_resolve_installed_version() {
+        let installed = vec!["18.19.0".to_string(), "20.9.0".to_string(), "20.11.1".to_string()];
+        assert_eq!(resolve_installed_version("v20", &installed), Some("20.11.1".to_string()));
+        assert_eq!(resolve_installed_version("18.19.0", &installed), Some("18.19.0".to_string()));
+        assert_eq!(resolve_installed_version("2", &installed), None);
+        assert_eq!(resolve_installed_version("16", &installed), None);
+        assert_eq!(resolve_installed_version("latest-installed", &installed), Some("20.11.1".to_string()));
+        assert_eq!(resolve_installed_version("latest-installed", &[]), None);
+    }
+
+    #[test]
+    fn test_resolve_directory_specs() {
+        assert_eq!(engines_range_to_spec(">=18.17.0"), Some("18".to_string()));
+        assert_eq!(engines_range_to_spec("^20.11.0"), Some("20".to_string()));
+        assert_eq!(engines_range_to_spec("~18.19"), Some("18.19".to_string()));
+        assert_eq!(engines_range_to_spec("18.x"), Some("18".to_string()));
+        assert_eq!(engines_range_to_spec("20.11.1"), Some("20.11.1".to_string()));
+        assert_eq!(engines_range_to_spec("^18 || ^20"), Some("20".to_string()));
+        assert_eq!(engines_range_to_spec("*"), None);
+
+        let available = |version: &str, lts: serde_json::Value| AvailableVersion {
+            version: version.to_string(),
+            date: String::new(),
+            files: Vec::new(),
+            npm: None,
+            lts,
+        };
+        let list = vec![
+            available("v22.1.0", serde_json::json!(false)),
+            available("v20.12.0", serde_json::json!("Iron")),
+            available("v20.11.1", serde_json::json!("Iron")),
+            available("v18.20.0", serde_json::json!("Hydrogen")),
+        ];
+        assert_eq!(resolve_available_version("node", &list), Some("22.1.0".to_string()));
+        assert_eq!(resolve_available_version("lts/*", &list), Some("20.12.0".to_string()));
+        assert_eq!(resolve_available_version("lts/hydrogen", &list), Some("18.20.0".to_string()));
+        assert_eq!(resolve_available_version("v20.11", &list), Some("20.11.1".to_string()));
+        assert_eq!(resolve_available_version("16", &list), None);
+    }
+
+    #[test]
+    fn test_copy_dir_with_progress_nested_tree() {
+        let base = env::temp_dir().join(format!("nvm_gui_copy_test_{}", std::process::id()));
+        let src = base.join("src");
+        let dst = base.join("dst");
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(src.join("a").join("b")).unwrap();
+        fs::write(src.join("root.txt"), "12345").unwrap();
+        fs::write(src.join("a").join("one.txt"), "abc").unwrap();
+        fs::write(src.join("a").join("b").join("two.txt"), "xy").unwrap();
+        let mtime = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+        File::options().write(true).open(src.join("root.txt")).unwrap().set_modified(mtime).unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let mut calls = 0;
+        let (files, bytes) = copy_dir_with_progress(&src, &dst, true, &cancel, &mut |_, _| calls += 1).unwrap();
+        assert_eq!((files, bytes), (3, 10));
+        assert_eq!(calls, 3);
+        assert_eq!(count_dir_files(&dst), (3, 10));
+        assert_eq!(fs::metadata(dst.join("root.txt")).unwrap().modified().unwrap(), mtime);
+
+        // 不覆盖时保留目标已有的文件
+        fs::write(dst.join("a").join("one.txt"), "changed").unwrap();
+        copy_dir_with_progress(&src, &dst, false, &cancel, &mut |_, _| {}).unwrap();
+        assert_eq!(fs::read_to_string(dst.join("a").join("one.txt")).unwrap(), "changed");
+
+        cancel.store(true, Ordering::SeqCst);
+        let err = copy_dir_with_progress(&src, &base.join("dst2"), true, &cancel, &mut |_, _| {}).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+
+        let _ = fs::remove_dir_all(&base);
+    }
 }